@@ -7,6 +7,14 @@
 //! partir del `payload` canónico).
 
 use chem_core::typed_artifact;
+use chem_domain::{
+    AggregateMethod, ConformerSet, DomainError, EnumerationLimits, FamilyAggregate, ForceField, IdentityPolicy, Molecule,
+    MoleculeFamily, MoleculeRGroups, OwnedMolecularProperty, PropertyProvider, ScaffoldGroup,
+};
+
+use crate::blobs::{BlobRef, BlobStore};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 
 // Artifact que representa una molécula individual (neutro para el core).
 typed_artifact!(MoleculeArtifact { inchikey: String,
@@ -33,6 +41,40 @@ pub struct PropertyItem {
     pub property_kind: String,
     pub value: serde_json::Value,
     pub units: Option<String>,
+    /// Marca de valor preferido cuando hay varios del mismo tipo para la
+    /// misma molécula. Se omite en el payload si no se indica.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred: Option<bool>,
+    /// Proveedor del valor (p. ej. `experimental` en importaciones
+    /// tabulares). Se omite en el payload si no se indica.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<PropertyProvider>,
+}
+
+impl PropertyItem {
+    /// Propiedad numérica del item para agregarla en el dominio. El valor
+    /// es un número, un objeto con campo `value` o un objeto con un único
+    /// campo numérico (p. ej. `{"score": 27}` del stub de compute). Sin
+    /// proveedor se registra `unspecified`.
+    pub fn to_numeric_property(&self) -> Result<OwnedMolecularProperty<f64, ()>, DomainError> {
+        let value = match &self.value {
+            serde_json::Value::Object(map) if map.contains_key("value") => map.get("value"),
+            serde_json::Value::Object(map) if map.len() == 1 => map.values().next(),
+            other => Some(other),
+        };
+        let value = value.and_then(serde_json::Value::as_f64).ok_or_else(|| {
+                                                                  DomainError::ValidationError(format!("Valor no numérico de '{}' para {}",
+                                                                                                       self.property_kind,
+                                                                                                       self.molecule_inchikey))
+                                                              })?;
+        let provider = match &self.provider {
+            Some(provider) => provider.clone(),
+            None => PropertyProvider::new("unspecified", "unspecified")?,
+        };
+        Ok(OwnedMolecularProperty::new(&self.molecule_inchikey, &self.property_kind, value, provider, ())?
+            .with_units(self.units.as_deref())?
+            .with_preferred(self.preferred.unwrap_or(false)))
+    }
 }
 
 // Artifact que agrupa propiedades stub por familia (uno por pipeline).
@@ -43,10 +85,344 @@ typed_artifact!(FamilyPropertiesArtifact {
 });
 
 // Artifact para una propiedad puntual de molécula (cuando se requiera
-// itemizar).
+// itemizar). Los campos de proveniencia son opcionales para aceptar
+// payloads previos (sin ellos); `OwnedMolecularProperty` los rellena y
+// se reconstruye con `to_property` (ver Requerimientos §3.4).
 typed_artifact!(MolecularPropertyArtifact {
     molecule_inchikey: String,
     property_kind: String,
     value: serde_json::Value,
     units: Option<String>,
+    provider: Option<PropertyProvider>,
+    step_id: Option<String>,
+    quality: Option<String>,
+    preferred: Option<bool>,
+    metadata: Option<serde_json::Value>,
+    value_hash: Option<String>,
+});
+
+impl MolecularPropertyArtifact {
+    /// Empaqueta una propiedad owned sin pérdida (salvo el `id`, que no
+    /// forma parte del payload para mantenerlo determinista).
+    pub fn from_property<V, M>(p: &OwnedMolecularProperty<V, M>) -> Result<Self, DomainError>
+        where V: Serialize + Clone,
+              M: Serialize + Clone
+    {
+        Ok(Self { molecule_inchikey: p.molecule_inchikey().to_string(),
+                  property_kind: p.property_type().to_string(),
+                  value: serde_json::to_value(p.value())?,
+                  units: p.units().map(str::to_string),
+                  provider: Some(p.provider().clone()),
+                  step_id: p.step_id().map(str::to_string),
+                  quality: p.quality().cloned(),
+                  preferred: Some(p.preferred()),
+                  metadata: Some(serde_json::to_value(p.metadata())?),
+                  value_hash: Some(p.value_hash().to_string()),
+                  schema_version: 1 })
+    }
+
+    /// Reconstruye la propiedad owned. Requiere `provider`; si el payload
+    /// trae `value_hash`, debe coincidir con el recalculado.
+    pub fn to_property<V, M>(&self) -> Result<OwnedMolecularProperty<V, M>, DomainError>
+        where V: Serialize + DeserializeOwned + Clone,
+              M: Serialize + DeserializeOwned + Clone
+    {
+        let provider =
+            self.provider
+                .clone()
+                .ok_or_else(|| DomainError::ValidationError("MolecularPropertyArtifact sin proveedor".to_string()))?;
+        let value: V = serde_json::from_value(self.value.clone())?;
+        let metadata: M = serde_json::from_value(self.metadata.clone().unwrap_or(serde_json::Value::Null))?;
+        let property = OwnedMolecularProperty::new(&self.molecule_inchikey, &self.property_kind, value, provider, metadata)?
+            .with_units(self.units.as_deref())?
+            .with_step_id(self.step_id.as_deref())
+            .with_quality(self.quality.clone())
+            .with_preferred(self.preferred.unwrap_or(false));
+        if let Some(expected) = &self.value_hash {
+            if expected != property.value_hash() {
+                return Err(DomainError::ValidationError(format!("value_hash no coincide para {} ({})",
+                                                                self.molecule_inchikey,
+                                                                self.property_kind)));
+            }
+        }
+        Ok(property)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chem_core::model::ArtifactSpec;
+    use chem_domain::PropertyProvider;
+
+    #[test]
+    fn molecular_property_artifact_round_trip() {
+        let provider = PropertyProvider::new("rdkit", "2024.03").unwrap();
+        let property =
+            OwnedMolecularProperty::new("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                                        "tpsa",
+                                        20.23,
+                                        provider,
+                                        serde_json::json!({"method": "ertl"})).unwrap()
+                                                                              .with_units(Some("Å²"))
+                                                                              .unwrap()
+                                                                              .with_step_id(Some("compute_properties"))
+                                                                              .with_preferred(true);
+        let artifact = MolecularPropertyArtifact::from_property(&property).unwrap();
+        let decoded = MolecularPropertyArtifact::from_artifact(&artifact.clone().into_artifact()).unwrap();
+        let back: OwnedMolecularProperty<f64, serde_json::Value> = decoded.to_property().unwrap();
+        assert_eq!(back, property);
+        assert_eq!(back.units(), Some("Å²"));
+        assert_eq!(back.step_id(), Some("compute_properties"));
+        assert!(back.preferred());
+
+        let mut tampered = artifact;
+        tampered.value = serde_json::json!(99.0);
+        assert!(tampered.to_property::<f64, serde_json::Value>().is_err());
+    }
+
+    #[test]
+    fn family_structures_keep_identity_and_parameters() {
+        let ethanol = Molecule::from_parts("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                                           "CCO",
+                                           "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
+                                           serde_json::json!({})).unwrap();
+        let methane =
+            Molecule::from_parts("VNWKTOKETHGBQD-UHFFFAOYSA-N", "C", "InChI=1S/CH4/h1H4", serde_json::json!({})).unwrap();
+        let family = MoleculeFamily::new_with_identity(vec![ethanol, methane],
+                                                       serde_json::json!({}),
+                                                       serde_json::json!({"source_step": "acquire"}),
+                                                       IdentityPolicy::Connectivity).unwrap();
+        let artifact = FamilyStructuresArtifact::from_family(&family);
+        let decoded = FamilyStructuresArtifact::from_artifact(&artifact.clone().into_artifact()).unwrap();
+        let rebuilt = decoded.to_family().unwrap();
+        assert_eq!(rebuilt.family_hash(), family.family_hash());
+        assert_eq!(rebuilt.identity(), &IdentityPolicy::Connectivity);
+        assert_eq!(rebuilt.parameters(), family.parameters());
+
+        let mut stripped = artifact;
+        stripped.identity = None;
+        assert!(stripped.to_family().is_err());
+    }
+}
+
+// Miembro de una familia con su estructura (necesaria para steps que
+// consultan RDKit, p. ej. filtros por subestructura).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct FamilyMember {
+    pub inchikey: String,
+    pub smiles: String,
+    pub inchi: String,
+}
+
+// Familia con estructuras: mismo `family_hash` que `FamilyArtifact` pero
+// cada miembro lleva SMILES/InChI, en el orden de la familia, junto con la
+// política de identidad y los parámetros que entran en el hash (ausentes en
+// payloads antiguos = `IdentityPolicy::Full` y `{}`).
+typed_artifact!(FamilyStructuresArtifact {
+    family_hash: String,
+    molecules: Vec<FamilyMember>,
+    identity: Option<IdentityPolicy>,
+    parameters: Option<serde_json::Value>,
+});
+
+impl FamilyStructuresArtifact {
+    /// Empaqueta `family` con las estructuras de sus miembros, su política de
+    /// identidad y sus parámetros.
+    pub fn from_family(family: &MoleculeFamily) -> Self {
+        let molecules = family.molecules()
+                              .iter()
+                              .map(|m| FamilyMember { inchikey: m.inchikey().to_string(),
+                                                      smiles: m.smiles().to_string(),
+                                                      inchi: m.inchi().to_string() })
+                              .collect();
+        Self { family_hash: family.family_hash().to_string(),
+               molecules,
+               identity: Some(family.identity().clone()),
+               parameters: Some(family.parameters().clone()),
+               schema_version: 1 }
+    }
+
+    /// Reconstruye la familia de dominio del artifact.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si el `family_hash` reconstruido no
+    /// coincide con el del artifact (estructuras, identidad o parámetros
+    /// alterados).
+    pub fn to_family(&self) -> Result<MoleculeFamily, DomainError> {
+        let family = self.subfamily(&self.molecules, serde_json::json!({ "source": "family_structures" }))?;
+        if family.family_hash() != self.family_hash {
+            return Err(DomainError::ValidationError(format!("family_hash reconstruido {} no coincide con el del artifact {}",
+                                                            family.family_hash(),
+                                                            self.family_hash)));
+        }
+        Ok(family)
+    }
+
+    /// Familia formada por `members` (p. ej. los retenidos por un filtro) con
+    /// la misma política de identidad y parámetros que este artifact.
+    pub fn subfamily(&self, members: &[FamilyMember], provenance: serde_json::Value) -> Result<MoleculeFamily, DomainError> {
+        let molecules = members.iter()
+                               .map(|m| Molecule::from_parts(&m.inchikey, &m.smiles, &m.inchi, serde_json::json!({})))
+                               .collect::<Result<Vec<_>, _>>()?;
+        MoleculeFamily::new_with_identity(molecules,
+                                          provenance,
+                                          self.parameters.clone().unwrap_or_else(|| serde_json::json!({})),
+                                          self.identity.clone().unwrap_or_default())
+    }
+}
+
+// Molécula descartada por un filtro, con las alertas que la descartaron
+// (ordenadas como `"<catalogo>:<descripcion>"`).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RejectedMolecule {
+    pub inchikey: String,
+    pub alerts: Vec<String>,
+}
+
+// Producto de una enumeración por reacción con los InChIKeys de los
+// reactivos que lo generan (uno por plantilla).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EnumeratedProduct {
+    pub inchikey: String,
+    pub smiles: String,
+    pub inchi: String,
+    pub reagents: Vec<String>,
+}
+
+// Familia enumerada a partir de un SMARTS de reacción: productos en orden de
+// enumeración (sin InChIKeys repetidos), familias de reactivos y límites.
+typed_artifact!(EnumeratedFamilyArtifact {
+    family_hash: String,
+    reaction: String,
+    reagent_family_hashes: Vec<String>,
+    limits: EnumerationLimits,
+    products: Vec<EnumeratedProduct>,
+    n_duplicates: usize,
+    n_failed: usize,
+    truncated: bool,
+});
+
+// Tabla derivada (DerivedTable) del análisis de scaffolds: scaffold →
+// InChIKeys de sus miembros y R-grupos por molécula respecto a `cores`.
+// Orden determinista (ver `chem_domain::ScaffoldAnalysis`).
+typed_artifact!(ScaffoldTableArtifact {
+    family_hash: String,
+    cores: Vec<String>,
+    scaffolds: Vec<ScaffoldGroup>,
+    rgroups: Vec<MoleculeRGroups>,
+});
+
+// Resultado de filtrar una familia: miembros retenidos (nueva familia),
+// descartados y catálogos aplicados (orden canónico). Si el filtro descarta
+// todas las moléculas no hay familia retenida: `family_hash` es `None` y
+// `molecules` está vacío.
+typed_artifact!(FilteredFamilyArtifact {
+    family_hash: Option<String>,
+    parent_family_hash: String,
+    molecules: Vec<FamilyMember>,
+    rejected: Vec<RejectedMolecule>,
+    catalogs: Vec<String>,
+});
+
+// Agregado de familia calculado por `FamilyAggregateStep`. `aggregate_hash`
+// depende solo de (family_hash, propiedad, método, unidades); ver
+// `chem_domain::aggregate_hash`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AggregateItem {
+    pub aggregate_name: String,
+    pub property_kind: String,
+    pub method: AggregateMethod,
+    pub value: f64,
+    pub units: Option<String>,
+    pub n_values: usize,
+    pub aggregate_hash: String,
+}
+
+impl From<FamilyAggregate> for AggregateItem {
+    fn from(aggregate: FamilyAggregate) -> Self {
+        Self { aggregate_name: aggregate.name,
+               property_kind: aggregate.provenance.property_type,
+               method: aggregate.provenance.method,
+               value: aggregate.value,
+               units: aggregate.provenance.units,
+               n_values: aggregate.provenance.n_values,
+               aggregate_hash: aggregate.provenance.aggregate_hash }
+    }
+}
+
+// Agregados de una familia, en el orden de las especificaciones.
+typed_artifact!(FamilyAggregatesArtifact {
+    family_hash: String,
+    aggregates: Vec<AggregateItem>,
+});
+
+// Registro importado de un fichero: estructura canónica y campos del
+// registro de origen (SD tags, columnas extra, nombre).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MoleculeRecord {
+    pub inchikey: String,
+    pub smiles: String,
+    pub inchi: String,
+    pub tags: std::collections::BTreeMap<String, String>,
+}
+
+// Registro de entrada que no pudo importarse (`index` = posición del
+// registro en el fichero, base 0).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RecordError {
+    pub index: usize,
+    pub message: String,
+}
+
+// Familia importada de un fichero (o lista para exportar): registros en
+// orden de fichero, propiedades por molécula y errores por registro.
+// - source_hash: SHA-256 del contenido del fichero (también en la provenance y
+//   los parámetros de la familia, luego en `family_hash`).
+typed_artifact!(FamilyRecordsArtifact {
+    family_hash: String,
+    format: String,
+    source_hash: String,
+    records: Vec<MoleculeRecord>,
+    properties: Vec<PropertyItem>,
+    errors: Vec<RecordError>,
+});
+
+// Resultado de exportar una familia a fichero.
+typed_artifact!(FileExportArtifact { family_hash: String,
+                                     path: String,
+                                     format: String,
+                                     content_hash: String,
+                                     n_records: usize });
+
+// Conformeros de una molécula: en línea (`conformers`) o fuera de banda
+// (`blob`, JSON de un `ConformerSet`) si superan el límite del step. `error`
+// recoge los fallos por molécula (p. ej. sin parámetros MMFF).
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConformerEntry {
+    pub inchikey: String,
+    pub conformers: Option<ConformerSet>,
+    pub blob: Option<BlobRef>,
+    pub error: Option<String>,
+}
+
+impl ConformerEntry {
+    /// Conformeros de la entrada, leyendo (y verificando) el blob de `store`
+    /// si hace falta. `None` si la molécula falló.
+    pub fn load(&self, store: &BlobStore) -> Result<Option<ConformerSet>, DomainError> {
+        match (&self.conformers, &self.blob) {
+            (Some(set), _) => Ok(Some(set.clone())),
+            (None, Some(blob)) => Ok(Some(serde_json::from_slice(&store.read(blob)?)?)),
+            (None, None) => Ok(None),
+        }
+    }
+}
+
+// Conjuntos de conformeros por familia, con los parámetros de generación.
+typed_artifact!(ConformerSetsArtifact {
+    family_hash: String,
+    n_conformers: usize,
+    seed: u32,
+    force_field: ForceField,
+    entries: Vec<ConformerEntry>,
 });
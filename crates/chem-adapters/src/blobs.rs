@@ -0,0 +1,113 @@
+//! Almacenamiento fuera de banda (out-of-band) de payloads grandes.
+//!
+//! Los artifacts deben ser pequeños (el engine los guarda y hashea enteros).
+//! Los datos voluminosos (p. ej. coordenadas de conformeros) se escriben en
+//! un `BlobStore` direccionado por contenido y el artifact guarda sólo un
+//! `BlobRef` con el SHA-256 del contenido, nunca una ruta del host: el
+//! artifact sigue siendo determinista y portable, y la lectura verifica la
+//! integridad.
+//!
+//! El almacén debe configurarse explícitamente (`BlobStore::new` o la
+//! variable `CHEMFLOW_BLOB_DIR`) con un directorio durable; no hay valor por
+//! defecto en el directorio temporal, que puede vaciarse y dejar artifacts
+//! con referencias rotas.
+
+use std::path::{Path, PathBuf};
+
+use chem_domain::DomainError;
+
+use crate::formats::content_hash;
+
+/// Variable de entorno con el directorio del almacén de blobs.
+pub const BLOB_DIR_ENV: &str = "CHEMFLOW_BLOB_DIR";
+
+/// Referencia a un blob: hash SHA-256 (hex) del contenido y tamaño en
+/// bytes. El fichero se localiza dentro del `BlobStore`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BlobRef {
+    pub content_hash: String,
+    pub size: usize,
+}
+
+/// Almacén durable de blobs: `<root>/<sha256>.json`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlobStore {
+    root: PathBuf,
+}
+
+impl BlobStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Almacén configurado en `CHEMFLOW_BLOB_DIR`.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si la variable no está definida o está
+    /// vacía.
+    pub fn from_env() -> Result<Self, DomainError> {
+        match std::env::var(BLOB_DIR_ENV) {
+            Ok(dir) if !dir.trim().is_empty() => Ok(Self::new(dir)),
+            _ => Err(DomainError::ValidationError(format!("No hay almacén de blobs configurado: defina \
+                                                           {BLOB_DIR_ENV} con un directorio durable"))),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, hash: &str) -> PathBuf {
+        self.root.join(format!("{hash}.json"))
+    }
+
+    /// Escribe `bytes` (si no existe ya) y devuelve su referencia.
+    pub fn write(&self, bytes: &[u8]) -> Result<BlobRef, DomainError> {
+        let hash = content_hash(bytes);
+        let path = self.path(&hash);
+        if !path.exists() {
+            std::fs::create_dir_all(&self.root).map_err(|e| {
+                                                   DomainError::ExternalError(format!("No se puede crear '{}': {e}",
+                                                                                      self.root.display()))
+                                               })?;
+            std::fs::write(&path, bytes).map_err(|e| {
+                                            DomainError::ExternalError(format!("No se puede escribir '{}': {e}",
+                                                                               path.display()))
+                                        })?;
+        }
+        Ok(BlobRef { content_hash: hash,
+                     size: bytes.len() })
+    }
+
+    /// Lee un blob y verifica que su contenido coincide con el hash
+    /// registrado.
+    pub fn read(&self, blob: &BlobRef) -> Result<Vec<u8>, DomainError> {
+        let path = self.path(&blob.content_hash);
+        let bytes = std::fs::read(&path).map_err(|e| {
+                                            DomainError::ExternalError(format!("No se puede leer '{}': {e}", path.display()))
+                                        })?;
+        if content_hash(&bytes) != blob.content_hash {
+            return Err(DomainError::ValidationError(format!("El blob '{}' no coincide con su hash", path.display())));
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blobs_are_content_addressed_and_verified() {
+        let dir = std::env::temp_dir().join(format!("chemflow_blobs_{}", std::process::id()));
+        let store = BlobStore::new(&dir);
+        let blob = store.write(b"{\"x\":1}").unwrap();
+        assert_eq!(blob, store.write(b"{\"x\":1}").unwrap());
+        assert_eq!(store.read(&blob).unwrap(), b"{\"x\":1}");
+        let json = serde_json::to_value(&blob).unwrap();
+        assert_eq!(json.as_object().unwrap().len(), 2, "sin rutas del host: {json}");
+        std::fs::write(dir.join(format!("{}.json", blob.content_hash)), b"tampered").unwrap();
+        assert!(store.read(&blob).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -21,6 +21,9 @@ pub trait DomainArtifactEncoder {
     fn encode_molecule(&self, m: &Molecule) -> Artifact;
     /// Empaqueta una familia a artifact `FamilyArtifact`-like.
     fn encode_family(&self, f: &MoleculeFamily) -> Artifact;
+    /// Empaqueta una familia con las estructuras de sus miembros
+    /// (`FamilyStructuresArtifact`).
+    fn encode_family_structures(&self, f: &MoleculeFamily) -> Artifact;
     /// Empaqueta una propiedad molecular a artifact neutro.
     fn encode_property<'a, V, M>(&self, p: &MolecularProperty<'a, V, M>) -> Artifact
         where V: serde::Serialize + Clone,
@@ -32,6 +35,8 @@ pub trait DomainArtifactEncoder {
 /// Mantiene un esquema mínimo de payloads:
 /// - Molecule: { inchikey, smiles, inchi }
 /// - Family: { family_hash, ordered_keys: [...] }
+/// - FamilyStructures: { family_hash, molecules: [{ inchikey, smiles, .. }],
+///   identity, parameters }
 /// - Property: { molecule_inchikey, property_kind, value, units? }
 #[derive(Clone, Default)]
 pub struct SimpleDomainEncoder;
@@ -55,6 +60,10 @@ impl DomainArtifactEncoder for SimpleDomainEncoder {
         typed.into_artifact()
     }
 
+    fn encode_family_structures(&self, f: &MoleculeFamily) -> Artifact {
+        crate::artifacts::FamilyStructuresArtifact::from_family(f).into_artifact()
+    }
+
     fn encode_property<'a, V, M>(&self, p: &MolecularProperty<'a, V, M>) -> Artifact
         where V: serde::Serialize + Clone,
               M: serde::Serialize + Clone
@@ -63,7 +72,47 @@ impl DomainArtifactEncoder for SimpleDomainEncoder {
                                                                   property_kind: p.property_type().to_string(),
                                                                   value: json!(p.value()),
                                                                   units: None,
+                                                                  provider: None,
+                                                                  step_id: None,
+                                                                  quality: p.quality().cloned(),
+                                                                  preferred: Some(p.preferred()),
+                                                                  metadata: None,
+                                                                  value_hash: None,
                                                                   schema_version: 1 };
         typed.into_artifact()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chem_core::hashing::{hash_value, to_canonical_json};
+
+    fn ethanol(source: &str) -> Molecule {
+        Molecule::from_parts("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                             "CCO",
+                             "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
+                             json!({ "source": source })).unwrap()
+    }
+
+    #[test]
+    fn encoding_is_byte_stable() {
+        let enc = SimpleDomainEncoder;
+        // Dos construcciones independientes (metadata distinta) codifican
+        // exactamente igual.
+        let a = enc.encode_molecule(&ethanol("run_1"));
+        let b = enc.encode_molecule(&ethanol("run_2"));
+        assert_eq!(serde_json::to_vec(&a.payload).unwrap(),
+                   serde_json::to_vec(&b.payload).unwrap());
+        // Snapshot: fija los bytes entre ejecuciones y versiones.
+        assert_eq!(to_canonical_json(&a.payload),
+                   r#"{"inchi":"InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3","inchikey":"LFQSCWFLJHTTHZ-UHFFFAOYSA-N","schema_version":1,"smiles":"CCO"}"#);
+
+        let fam_a = MoleculeFamily::new(vec![ethanol("run_1")], json!({})).unwrap();
+        let fam_b = MoleculeFamily::new(vec![ethanol("run_2")], json!({})).unwrap();
+        assert_eq!(hash_value(&enc.encode_family(&fam_a).payload),
+                   hash_value(&enc.encode_family(&fam_b).payload));
+        assert_eq!(hash_value(&enc.encode_family_structures(&fam_a).payload),
+                   hash_value(&enc.encode_family_structures(&fam_b).payload));
+    }
+}
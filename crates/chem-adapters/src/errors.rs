@@ -0,0 +1,42 @@
+//! Traducción de errores de dominio a errores del core.
+//!
+//! El core no conoce la química: sólo distingue entradas inválidas
+//! (permanentes), fallos transitorios y errores internos. `classify_error`
+//! decide con eso si un step merece reintento.
+
+use chem_core::errors::CoreEngineError;
+use chem_domain::DomainError;
+
+/// Error del core equivalente a `e`: los errores químicos y de validación
+/// son `InvalidInput`, los fallos del intérprete `Transient` y el resto
+/// (motor no disponible, E/S…) `Internal`.
+pub fn core_error(e: DomainError) -> CoreEngineError {
+    if e.is_permanent() {
+        CoreEngineError::InvalidInput(e.to_string())
+    } else if e.is_transient() {
+        CoreEngineError::Transient(e.to_string())
+    } else {
+        CoreEngineError::Internal(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chem_core::errors::{classify_error, ErrorClass};
+
+    #[test]
+    fn chemistry_errors_are_permanent() {
+        let invalid = DomainError::InvalidSmiles { smiles: "CCX".into(),
+                                                   position: Some(2),
+                                                   message: "syntax error".into() };
+        let class = classify_error(&core_error(invalid));
+        assert_eq!(class, ErrorClass::Permanent);
+        assert!(!class.is_retryable());
+
+        let transient = classify_error(&core_error(DomainError::InterpreterError("MemoryError".into())));
+        assert!(transient.is_retryable());
+        assert_eq!(classify_error(&core_error(DomainError::EngineUnavailable("rdkit".into()))),
+                   ErrorClass::Runtime);
+    }
+}
@@ -0,0 +1,237 @@
+//! Formatos de fichero para importar/exportar familias (SDF/MOL, SMILES,
+//! CSV/TSV).
+//!
+//! Sólo texto: aquí no se interpreta química. Los bloques MOL y SMILES se
+//! pasan al dominio (`Molecule::from_molblock_batch`,
+//! `Molecule::from_smiles_batch`), que reporta errores por registro.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+/// Formato de un fichero de moléculas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    Sdf,
+    Mol,
+    Smiles,
+    Csv,
+    Tsv,
+}
+
+impl FileFormat {
+    /// Nombre estable (el mismo que en serde).
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileFormat::Sdf => "sdf",
+            FileFormat::Mol => "mol",
+            FileFormat::Smiles => "smiles",
+            FileFormat::Csv => "csv",
+            FileFormat::Tsv => "tsv",
+        }
+    }
+
+    /// Deduce el formato por la extensión (sin distinguir mayúsculas).
+    pub fn from_path(path: &Path) -> Option<FileFormat> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "sdf" | "sd" => Some(FileFormat::Sdf),
+            "mol" => Some(FileFormat::Mol),
+            "smi" | "smiles" => Some(FileFormat::Smiles),
+            "csv" => Some(FileFormat::Csv),
+            "tsv" | "tab" => Some(FileFormat::Tsv),
+            _ => None,
+        }
+    }
+}
+
+/// SHA-256 (hex) del contenido de un fichero tal como se leyó.
+pub fn content_hash(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Registro de un SDF: bloque MOL (hasta `M  END` inclusive) y sus campos
+/// SD (`> <NOMBRE>`). El título del bloque, si no está vacío, se expone como
+/// el campo `_Name` (convención de RDKit).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdfRecord {
+    pub molblock: String,
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Divide un SDF en registros (separador `$$$$`). Los registros vacíos se
+/// ignoran; los valores multilínea de un campo se unen con `\n`.
+pub fn parse_sdf(text: &str) -> Vec<SdfRecord> {
+    let text = text.replace("\r\n", "\n");
+    text.split("\n$$$$")
+        // El `\n` inicial es el fin de línea del `$$$$` anterior.
+        .map(|chunk| chunk.strip_prefix('\n').unwrap_or(chunk))
+        .filter(|chunk| !chunk.trim().is_empty())
+        .map(parse_sdf_record)
+        .collect()
+}
+
+fn parse_sdf_record(chunk: &str) -> SdfRecord {
+    let (molblock, data) = match chunk.find("M  END") {
+        Some(pos) => chunk.split_at(pos + "M  END".len()),
+        None => (chunk, ""),
+    };
+    let mut tags = BTreeMap::new();
+    if let Some(title) = molblock.lines().next().map(str::trim).filter(|t| !t.is_empty()) {
+        tags.insert("_Name".to_string(), title.to_string());
+    }
+    let mut current: Option<(String, Vec<&str>)> = None;
+    for line in data.lines() {
+        if line.starts_with('>') {
+            if let Some((name, values)) = current.take() {
+                tags.insert(name, values.join("\n"));
+            }
+            let name = line.split_once('<')
+                           .and_then(|(_, rest)| rest.split_once('>'))
+                           .map(|(name, _)| name.to_string())
+                           .unwrap_or_default();
+            current = Some((name, Vec::new()));
+        } else if let Some((_, values)) = current.as_mut() {
+            if line.trim().is_empty() {
+                if let Some((name, values)) = current.take() {
+                    tags.insert(name, values.join("\n"));
+                }
+            } else {
+                values.push(line.trim_end());
+            }
+        }
+    }
+    if let Some((name, values)) = current {
+        tags.insert(name, values.join("\n"));
+    }
+    SdfRecord { molblock: format!("{molblock}\n"),
+                tags }
+}
+
+/// Serializa registros a SDF. Los campos se escriben en orden alfabético;
+/// `_Name` no se escribe como campo (va en el título del bloque).
+pub fn write_sdf(records: &[SdfRecord]) -> String {
+    let mut out = String::new();
+    for record in records {
+        let mut lines = record.molblock.trim_end_matches('\n').lines();
+        let title = record.tags.get("_Name").cloned().unwrap_or_default();
+        // Se sustituye el título del bloque por `_Name`.
+        lines.next();
+        out.push_str(&title);
+        out.push('\n');
+        for line in lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for (name, value) in record.tags.iter().filter(|(name, _)| name.as_str() != "_Name") {
+            out.push_str(&format!(">  <{name}>\n{value}\n\n"));
+        }
+        out.push_str("$$$$\n");
+    }
+    out
+}
+
+/// Tabla delimitada (CSV/TSV) con cabecera.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Table {
+    pub header: Vec<String>,
+    /// Filas con su índice de línea en el texto (base 0, cabecera incluida).
+    pub rows: Vec<(usize, Vec<String>)>,
+}
+
+impl Table {
+    /// Índice de la columna `name` (sin distinguir mayúsculas).
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.header.iter().position(|h| h.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Lee una tabla delimitada con cabecera. Soporta campos entre comillas
+/// dobles (con `""` como comilla escapada); las líneas vacías se ignoran.
+pub fn parse_delimited(text: &str, delimiter: char) -> Table {
+    let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let header = lines.next()
+                      .map(|(_, line)| split_fields(line, delimiter))
+                      .unwrap_or_default();
+    let rows = lines.map(|(n, line)| (n, split_fields(line, delimiter))).collect();
+    Table { header, rows }
+}
+
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut field).trim().to_string()),
+            c => field.push(c),
+        }
+    }
+    fields.push(field.trim().to_string());
+    fields
+}
+
+/// Lee un fichero SMILES: `SMILES [nombre]` por línea, separados por
+/// espacio o tabulador. Ignora líneas vacías y comentarios (`#`). Devuelve
+/// (índice de línea base 0, SMILES, nombre).
+pub fn parse_smiles_lines(text: &str) -> Vec<(usize, String, Option<String>)> {
+    text.lines()
+        .enumerate()
+        .map(|(n, line)| (n, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(n, line)| {
+            let mut parts = line.splitn(2, char::is_whitespace);
+            let smiles = parts.next().unwrap_or_default().to_string();
+            let name = parts.next().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string);
+            (n, smiles, name)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDF: &str = "ethanol\n  RDKit          2D\n\n  3  2  0  0  0  0  0  0  0  0999 V2000\n    0.0000    0.0000    \
+                       0.0000 C   0  0\n    1.2990    0.7500    0.0000 C   0  0\n    2.5981   -0.0000    0.0000 O   0  \
+                       0\n  1  2  1  0\n  2  3  1  0\nM  END\n>  <pIC50>\n6.5\n\n>  <note>\nline 1\nline 2\n\n$$$$\n\n  \
+                       empty\n\nM  END\n$$$$\n";
+
+    #[test]
+    fn sdf_round_trip_keeps_blocks_and_tags() {
+        let records = parse_sdf(SDF);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].tags["_Name"], "ethanol");
+        assert_eq!(records[0].tags["pIC50"], "6.5");
+        assert_eq!(records[0].tags["note"], "line 1\nline 2");
+        assert!(records[0].molblock.ends_with("M  END\n"));
+        assert!(!records[1].tags.contains_key("_Name"));
+
+        let again = parse_sdf(&write_sdf(&records));
+        assert_eq!(again, records);
+    }
+
+    #[test]
+    fn delimited_and_smiles_parsing() {
+        let table = parse_delimited("SMILES,name,\"pIC50, nM\"\nCCO,\"eth\"\"anol\",6.5\n\nc1ccccc1,benzene,\n",
+                                    ',');
+        assert_eq!(table.header, vec!["SMILES", "name", "pIC50, nM"]);
+        assert_eq!(table.column("smiles"), Some(0));
+        assert_eq!(table.rows[0], (1, vec!["CCO".into(), "eth\"anol".into(), "6.5".into()]));
+        assert_eq!(table.rows[1].0, 3);
+
+        let lines = parse_smiles_lines("# comentario\nCCO ethanol\nc1ccccc1\n");
+        assert_eq!(lines,
+                   vec![(1, "CCO".to_string(), Some("ethanol".to_string())),
+                        (2, "c1ccccc1".to_string(), None)]);
+        assert_eq!(FileFormat::from_path(Path::new("x/lib.SDF")), Some(FileFormat::Sdf));
+    }
+}
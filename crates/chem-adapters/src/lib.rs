@@ -7,13 +7,33 @@
 //! - Steps iniciales: `AcquireMoleculesStep` (Source determinista) y
 //!   `ComputePropertiesStep` (Transform stub) para validar el pipeline
 //!   Acquire→Compute.
+//! - `StructuralAlertFilterStep`: filtro determinista por catálogos PAINS/Brenk
+//!   sobre un `FamilyStructuresArtifact`.
+//! - `FamilyAggregateStep`: agregados de familia (media, percentiles, conteos
+//!   por umbral…) con proveniencia del método.
+//! - `FileSourceStep` / `SdfSinkStep`: importación desde SDF/MOL, SMILES o CSV
+//!   y exportación a SDF (ver `formats`).
+//! - `TabularImportStep`: CSV/TSV con mapeo de columnas a propiedades medidas
+//!   (proveedor `experimental`).
+//! - `ConformerStep`: conformeros 3D (ETKDG + MMFF/UFF) con almacenamiento
+//!   fuera de banda de los conjuntos grandes en un almacén durable (ver
+//!   `blobs`).
+//! - `ReactionEnumerationStep`: productos de un SMARTS de reacción sobre listas
+//!   de reactivos, deduplicados por InChIKey.
+//! - `ScaffoldAnalysisStep`: scaffolds de Bemis–Murcko y R-grupos por molécula
+//!   como tabla derivada.
+//! - `errors::core_error`: errores de dominio → `CoreEngineError`, separando
+//!   entradas químicas inválidas (permanentes) de fallos transitorios.
 //!
 //! Nota: El core sólo conoce `Artifact { kind, hash, payload, metadata }`
 //! y `ArtifactKind::GenericJson`. Aquí nos apoyamos en artifacts tipados que
 //! serializan a payload JSON y en los macros del core para Steps tipados.
 
 pub mod artifacts;
+pub mod blobs;
 pub mod encoder;
+pub mod errors;
+pub mod formats;
 pub mod injectors;
 pub mod steps;
 
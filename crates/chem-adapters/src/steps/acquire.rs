@@ -3,22 +3,44 @@
 //! - Emite un único artifact de familia (`FamilyArtifact`) derivado de un
 //!   dataset sintético y determinista.
 //! - No accede a IO externo; sólo crea estructuras en memoria.
+//! - Las versiones del toolkit químico forman parte de los parámetros (y del
+//!   fingerprint): el mismo dataset con otro RDKit es otra ejecución.
 //! - El motor calculará el hash del artifact a partir del payload canónico.
 
-use chem_core::typed_step;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{check_toolkit, DomainError, Molecule, MoleculeFamily, ToolkitVersions};
 
 use crate::artifacts::FamilyArtifact;
-use chem_domain::{DomainError, Molecule, MoleculeFamily};
+use crate::errors::core_error;
+use crate::steps::fill_toolkit;
 
 /// Parámetros del step. En F4 mantenemos un solo dataset sintético.
 #[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct AcquireParams {
     /// Nombre del dataset sintético. Por defecto: "synthetic_v1".
     pub dataset: String,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+impl AcquireParams {
+    /// Parámetros para `dataset` con las versiones del toolkit del entorno
+    /// actual. `Default` no las consulta: deja `toolkit` vacío.
+    pub fn new(dataset: impl Into<String>) -> Self {
+        let mut params = Self { dataset: dataset.into(),
+                                toolkit: None };
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
 }
 
 /// Construye una familia determinista a partir de un dataset sintético.
-fn build_synthetic_family(dataset: &str) -> Result<MoleculeFamily, DomainError> {
+///
+/// # Errores
+/// `DomainError::ValidationError` si el dataset no es uno de los conocidos.
+fn build_synthetic_family(dataset: &str, toolkit: Option<&ToolkitVersions>) -> Result<MoleculeFamily, DomainError> {
+    check_toolkit(toolkit)?;
     // Elegimos SMILES simples y estables; RDKit/chemengine generará inchikeys.
     // Nota: Evitar cambios de orden o contenido para preservar determinismo.
     let smiles_list: &[&str] = match dataset {
@@ -26,9 +48,8 @@ fn build_synthetic_family(dataset: &str) -> Result<MoleculeFamily, DomainError>
                                              "CCO",         // Ethanol
                                              "CC(=O)O"      /* Acetic acid */],
         other => {
-            // Para datasets no reconocidos, usamos el default pero dejamos trazabilidad.
-            let _ = other; // reservado para futura extensión
-            &["C1=CC=CC=C1", "CCO", "CC(=O)O"]
+            return Err(DomainError::ValidationError(format!("Dataset sintético desconocido: '{other}' (soportados: \
+                                                             synthetic_v1, default)")));
         }
     };
 
@@ -46,25 +67,60 @@ fn build_synthetic_family(dataset: &str) -> Result<MoleculeFamily, DomainError>
     MoleculeFamily::new(mols, provenance)
 }
 
-// Step tipado (Source): sin input, output = FamilyArtifact, params =
-// AcquireParams.
-typed_step! {
-    source AcquireMoleculesStep {
-        id: "acquire_molecules",
-        output: FamilyArtifact,
-        params: AcquireParams,
-        run(_me, p) {{
-            // Construcción determinista de la familia.
-            let fam = build_synthetic_family(&p.dataset).expect("synthetic family build");
-            // Convertir a artifact tipado (payload estable). El engine añadirá hash.
-            FamilyArtifact { family_hash: fam.family_hash().to_string(),
-                             ordered_keys: fam.molecules().iter().map(|m| m.inchikey().to_string()).collect(),
-                             schema_version: 1 }
-        }}
+/// Step Source: sin input, output = `FamilyArtifact`, params =
+/// `AcquireParams`.
+#[derive(Clone, Debug, Default)]
+pub struct AcquireMoleculesStep;
+
+impl AcquireMoleculesStep {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl TypedStep for AcquireMoleculesStep {
+    type Params = AcquireParams;
+    type Input = FamilyArtifact; // ignorado (Source)
+    type Output = FamilyArtifact;
+
+    fn id(&self) -> &'static str {
+        "acquire_molecules"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Source
+    }
+    fn params_default(&self) -> Self::Params {
+        AcquireParams::new("")
+    }
+
+    fn run_typed(&self, _input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        // Construcción determinista de la familia; un dataset desconocido o
+        // un cambio de entorno es un fallo del step, no un pánico.
+        match build_synthetic_family(&params.dataset, params.toolkit.as_ref()) {
+            Ok(fam) => {
+                // Artifact tipado (payload estable). El engine añadirá el hash.
+                let out =
+                    FamilyArtifact { family_hash: fam.family_hash().to_string(),
+                                     ordered_keys: fam.molecules().iter().map(|m| m.inchikey().to_string()).collect(),
+                                     schema_version: 1 };
+                StepRunResultTyped::Success { outputs: vec![out] }
+            }
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
     }
 }
 
-// El macro `typed_step!` genera un struct unitario `AcquireMoleculesStep`.
-// El builder del engine requiere que los tipos de step implementen `Debug`.
-// Ahora el macro incluye Debug automáticamente, por lo que no necesitamos
-// implementación manual.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_dataset_is_rejected() {
+        assert!(matches!(build_synthetic_family("chembl_v2", None),
+                         Err(DomainError::ValidationError(_))));
+        let params = AcquireParams { dataset: "chembl_v2".to_string(),
+                                     toolkit: None };
+        assert!(matches!(AcquireMoleculesStep::new().run_typed(None, params),
+                         StepRunResultTyped::Failure { .. }));
+    }
+}
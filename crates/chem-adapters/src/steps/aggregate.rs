@@ -0,0 +1,139 @@
+//! FamilyAggregateStep (Transform, sin selección)
+//!
+//! - Recibe un `FamilyPropertiesArtifact` y produce un
+//!   `FamilyAggregatesArtifact` con los agregados pedidos (media, mediana,
+//!   percentiles, conteos por umbral, media ponderada…).
+//! - Los valores se normalizan a la unidad canónica antes de agregar; cada
+//!   agregado registra método, unidades, número de valores y su
+//!   `aggregate_hash` (dependiente solo del `family_hash` y los parámetros).
+//! - Determinista: sin especificaciones se calcula la media de cada
+//!   `property_kind` presente, en orden alfabético.
+
+use std::collections::BTreeSet;
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{AggregateMethod, AggregateSpec, DomainError, FamilyAggregator};
+
+use crate::artifacts::{AggregateItem, FamilyAggregatesArtifact, FamilyPropertiesArtifact};
+use crate::errors::core_error;
+
+/// Parámetros del step. `specs` vacío = `mean` de cada propiedad presente.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct AggregateParams {
+    #[serde(default)]
+    pub specs: Vec<AggregateSpec>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FamilyAggregateStep;
+
+impl FamilyAggregateStep {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Agrega con `chem_domain::FamilyAggregator`. El artifact no trae
+/// estructuras: los miembros son los InChIKeys de los items, en orden de
+/// aparición.
+fn aggregate_family(input: FamilyPropertiesArtifact,
+                    params: &AggregateParams)
+                    -> Result<FamilyAggregatesArtifact, DomainError> {
+    let properties = input.items
+                          .iter()
+                          .map(|item| item.to_numeric_property())
+                          .collect::<Result<Vec<_>, _>>()?;
+    let mut seen = BTreeSet::new();
+    let members = properties.iter().map(|p| p.molecule_inchikey()).filter(|k| seen.insert(*k));
+    let aggregator = FamilyAggregator::for_members(&input.family_hash, members);
+    let specs = if params.specs.is_empty() {
+        properties.iter()
+                  .map(|p| p.property_type())
+                  .collect::<BTreeSet<_>>()
+                  .into_iter()
+                  .map(|kind| AggregateSpec { property_type: kind.to_string(),
+                                              method: AggregateMethod::Mean })
+                  .collect()
+    } else {
+        params.specs.clone()
+    };
+    let aggregates = aggregator.aggregate_all(&properties, &specs)?
+                               .into_iter()
+                               .map(AggregateItem::from)
+                               .collect();
+    Ok(FamilyAggregatesArtifact { family_hash: input.family_hash,
+                                  aggregates,
+                                  schema_version: 1 })
+}
+
+impl TypedStep for FamilyAggregateStep {
+    type Params = AggregateParams;
+    type Input = FamilyPropertiesArtifact;
+    type Output = FamilyAggregatesArtifact;
+
+    fn id(&self) -> &'static str {
+        "aggregate_family"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Transform
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        match aggregate_family(inp, &params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::PropertyItem;
+    use chem_domain::aggregate_hash;
+    use serde_json::json;
+
+    fn item(key: &str, kind: &str, value: serde_json::Value, units: Option<&str>) -> PropertyItem {
+        PropertyItem { molecule_inchikey: key.to_string(),
+                       property_kind: kind.to_string(),
+                       value,
+                       units: units.map(str::to_string),
+                       preferred: None,
+                       provider: None }
+    }
+
+    #[test]
+    fn aggregates_in_canonical_units_with_provenance() {
+        let input = FamilyPropertiesArtifact { family_hash: "fh".to_string(),
+                                               items: vec![item("A", "dG", json!(-1.0), Some("kcal/mol")),
+                                                           item("B", "dG", json!({"value": -4.184}), Some("kJ/mol")),
+                                                           item("A", "StubScore", json!({"score": 27}), Some("au")),
+                                                           item("B", "StubScore", json!({"score": 29}), Some("au"))],
+                                               schema_version: 1 };
+        let defaults = aggregate_family(input.clone(), &AggregateParams::default()).unwrap();
+        let names: Vec<_> = defaults.aggregates.iter().map(|a| a.aggregate_name.as_str()).collect();
+        assert_eq!(names, vec!["mean(StubScore)", "mean(dG)"]);
+        assert_eq!(defaults.aggregates[0].value, 28.0);
+        assert!((defaults.aggregates[1].value + 4.184).abs() < 1e-12);
+        assert_eq!(defaults.aggregates[1].units.as_deref(), Some("kJ/mol"));
+
+        let spec = AggregateSpec { property_type: "dG".to_string(),
+                                   method: AggregateMethod::CountBelow { threshold: -3.0 } };
+        let counted = aggregate_family(input.clone(), &AggregateParams { specs: vec![spec.clone()] }).unwrap();
+        let aggregate = &counted.aggregates[0];
+        assert_eq!((aggregate.value, aggregate.units.clone(), aggregate.n_values), (2.0, None, 2));
+        assert_eq!(aggregate.aggregate_hash, aggregate_hash("fh", &spec, None));
+
+        let mut duplicated = input;
+        duplicated.items.push(item("A", "dG", json!(0.0), Some("kJ/mol")));
+        assert!(aggregate_family(duplicated.clone(), &AggregateParams::default()).is_err());
+        // Con un único valor marcado como preferido se usa ese
+        duplicated.items.last_mut().unwrap().preferred = Some(true);
+        let preferred = aggregate_family(duplicated, &AggregateParams { specs: vec![spec] }).unwrap();
+        assert_eq!((preferred.aggregates[0].value, preferred.aggregates[0].n_values), (1.0, 2));
+    }
+}
@@ -31,7 +31,9 @@ typed_step! {
                                              .map(|k| PropertyItem { molecule_inchikey: k.clone(),
                                                                       property_kind: "StubScore".to_string(),
                                                                       value: serde_json::json!({ "score": k.len() }),
-                                                                      units: Some("au".to_string()) })
+                                                                      units: Some("au".to_string()),
+                                                                      preferred: None,
+                                                                      provider: None })
                                              .collect();
             FamilyPropertiesArtifact { family_hash: inp.family_hash,
                                        items,
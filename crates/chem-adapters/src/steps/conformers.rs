@@ -0,0 +1,195 @@
+//! ConformerStep (Transform, sin selección)
+//!
+//! - Recibe un `FamilyStructuresArtifact` y genera conformeros 3D (ETKDG +
+//!   MMFF/UFF) para cada molécula, en el orden de la familia.
+//! - Determinista: semilla fija; la semilla y el resto de parámetros son los
+//!   `params` del step, así que forman parte de su fingerprint, igual que las
+//!   versiones del toolkit (RDKit/Python) con las que se generan.
+//! - Los conjuntos que superan `inline_limit_bytes` se guardan fuera de banda
+//!   en el `BlobStore` del step (obligatorio: `with_blob_store` o
+//!   `CHEMFLOW_BLOB_DIR`) y el artifact sólo lleva su `BlobRef`. El almacén no
+//!   forma parte de los parámetros ni del artifact.
+//! - Un fallo en una molécula queda en `ConformerEntry::error` sin abortar el
+//!   resto.
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{check_toolkit, ConformerSet, DomainError, ForceField, ToolkitVersions};
+
+use crate::artifacts::{ConformerEntry, ConformerSetsArtifact, FamilyStructuresArtifact};
+use crate::blobs::BlobStore;
+use crate::errors::core_error;
+use crate::steps::fill_toolkit;
+
+/// Parámetros de generación y almacenamiento.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ConformerParams {
+    pub n_conformers: usize,
+    /// Semilla de ETKDG, como mucho `ConformerSet::MAX_SEED`.
+    pub seed: u32,
+    pub force_field: ForceField,
+    /// Tamaño máximo (JSON, bytes) de un conjunto en línea.
+    pub inline_limit_bytes: usize,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+impl Default for ConformerParams {
+    fn default() -> Self {
+        Self { n_conformers: 10,
+               seed: 42,
+               force_field: ForceField::Mmff,
+               inline_limit_bytes: 64 * 1024,
+               toolkit: None }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ConformerStep {
+    pub params: ConformerParams,
+    /// Almacén de blobs; sin él se usa `BlobStore::from_env`.
+    pub blob_store: Option<BlobStore>,
+}
+
+impl ConformerStep {
+    pub fn new(params: ConformerParams) -> Self {
+        Self { params,
+               blob_store: None }
+    }
+
+    pub fn with_blob_store(mut self, store: BlobStore) -> Self {
+        self.blob_store = Some(store);
+        self
+    }
+
+    fn blob_store(&self) -> Result<BlobStore, DomainError> {
+        match &self.blob_store {
+            Some(store) => Ok(store.clone()),
+            None => BlobStore::from_env(),
+        }
+    }
+}
+
+/// Entrada del artifact para un conjunto: en línea o como blob según su
+/// tamaño serializado.
+fn to_entry(inchikey: &str,
+            set: ConformerSet,
+            params: &ConformerParams,
+            store: &BlobStore)
+            -> Result<ConformerEntry, DomainError> {
+    let bytes = serde_json::to_vec(&set)?;
+    let (conformers, blob) = if bytes.len() > params.inline_limit_bytes {
+        (None, Some(store.write(&bytes)?))
+    } else {
+        (Some(set), None)
+    };
+    Ok(ConformerEntry { inchikey: inchikey.to_string(),
+                        conformers,
+                        blob,
+                        error: None })
+}
+
+fn embed_family(input: FamilyStructuresArtifact,
+                params: &ConformerParams,
+                store: &BlobStore)
+                -> Result<ConformerSetsArtifact, DomainError> {
+    if params.n_conformers == 0 {
+        return Err(DomainError::ValidationError("n_conformers debe ser mayor que 0".to_string()));
+    }
+    if params.seed > ConformerSet::MAX_SEED {
+        return Err(DomainError::ValidationError(format!("seed debe ser como mucho {}", ConformerSet::MAX_SEED)));
+    }
+    check_toolkit(params.toolkit.as_ref())?;
+    let family = input.to_family()?;
+    let mut entries = Vec::with_capacity(family.len());
+    for molecule in family.molecules() {
+        let entry = match molecule.embed_conformers(params.n_conformers, params.seed, params.force_field) {
+            Ok(set) => to_entry(molecule.inchikey(), set, params, store)?,
+            Err(e) => ConformerEntry { inchikey: molecule.inchikey().to_string(),
+                                       conformers: None,
+                                       blob: None,
+                                       error: Some(e.to_string()) },
+        };
+        entries.push(entry);
+    }
+    Ok(ConformerSetsArtifact { family_hash: input.family_hash,
+                               n_conformers: params.n_conformers,
+                               seed: params.seed,
+                               force_field: params.force_field,
+                               entries,
+                               schema_version: 1 })
+}
+
+impl TypedStep for ConformerStep {
+    type Params = ConformerParams;
+    type Input = FamilyStructuresArtifact;
+    type Output = ConformerSetsArtifact;
+
+    fn id(&self) -> &'static str {
+        "embed_conformers"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Transform
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = self.params.clone();
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        let result = self.blob_store().and_then(|store| embed_family(inp, &params, &store));
+        match result {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chem_domain::Conformer;
+
+    fn set(n_atoms: usize) -> ConformerSet {
+        ConformerSet { smiles: "C".to_string(),
+                       atoms: vec!["C".to_string(); n_atoms],
+                       force_field: ForceField::Mmff,
+                       seed: 42,
+                       conformers: vec![Conformer { coordinates: vec![[0.0, 1.0, 2.0]; n_atoms],
+                                                    energy: Some(-1.5),
+                                                    converged: true }] }
+    }
+
+    #[test]
+    fn large_sets_go_out_of_band() {
+        let dir = std::env::temp_dir().join(format!("chemflow_conformers_{}", std::process::id()));
+        let store = BlobStore::new(&dir);
+        let params = ConformerParams { inline_limit_bytes: 200,
+                                       ..Default::default() };
+        let small = to_entry("A", set(1), &params, &store).unwrap();
+        assert!(small.conformers.is_some() && small.blob.is_none());
+        let large = to_entry("B", set(50), &params, &store).unwrap();
+        assert!(large.conformers.is_none());
+        assert_eq!(large.load(&store).unwrap(), Some(set(50)));
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let json = serde_json::to_value(ConformerParams::default()).unwrap();
+        assert_eq!(json["seed"], 42);
+        assert_eq!(json["force_field"], "mmff");
+
+        // RDKit recibe la semilla como `int` de C
+        let input = FamilyStructuresArtifact { family_hash: "h".to_string(),
+                                               molecules: vec![],
+                                               identity: None,
+                                               parameters: None,
+                                               schema_version: 1 };
+        let params = ConformerParams { seed: ConformerSet::MAX_SEED + 1,
+                                       ..Default::default() };
+        assert!(matches!(embed_family(input, &params, &store), Err(DomainError::ValidationError(_))));
+    }
+}
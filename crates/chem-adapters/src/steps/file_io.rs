@@ -0,0 +1,469 @@
+//! FileSourceStep (Source desde fichero) y SdfSinkStep (Sink a SDF)
+//!
+//! - `FileSourceStep` lee SDF/MOL, SMILES o CSV/TSV y emite un
+//!   `FamilyRecordsArtifact`. Los SD tags (y columnas extra del CSV) pasan al
+//!   metadata de cada molécula; los registros que no se pueden leer (o
+//!   duplicados) quedan en `errors` sin abortar la importación.
+//! - El hash del contenido del fichero entra en la provenance y en los
+//!   parámetros de la familia (luego en su `family_hash`) y en los parámetros
+//!   del step (fingerprint), de modo que un fichero modificado invalida la
+//!   caché. Las versiones del toolkit químico se fijan igual en los parámetros
+//!   de ambos steps.
+//! - `SdfSinkStep` escribe la familia con sus propiedades preferidas como SD
+//!   tags y devuelve un `FileExportArtifact` con el hash de lo escrito.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{check_toolkit, DomainError, Molecule, MoleculeFamily, ToolkitVersions};
+
+use crate::artifacts::{FamilyRecordsArtifact, FileExportArtifact, MoleculeRecord, PropertyItem, RecordError};
+use crate::errors::core_error;
+use crate::formats::{self, FileFormat, SdfRecord};
+use crate::steps::fill_toolkit;
+
+/// Parámetros de importación. `format` se deduce de la extensión si no se
+/// indica; `smiles_column` (CSV/TSV) por defecto es `smiles`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FileSourceParams {
+    pub path: String,
+    #[serde(default)]
+    pub format: Option<FileFormat>,
+    #[serde(default)]
+    pub smiles_column: Option<String>,
+    /// Hash del contenido esperado. `params_default` lo rellena leyendo el
+    /// fichero; si al ejecutar no coincide, el step falla.
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+impl FileSourceParams {
+    /// Formato explícito o deducido de la extensión.
+    pub fn effective_format(&self) -> Result<FileFormat, DomainError> {
+        self.format
+            .or_else(|| FileFormat::from_path(Path::new(&self.path)))
+            .ok_or_else(|| DomainError::ValidationError(format!("No se puede deducir el formato de '{}'", self.path)))
+    }
+}
+
+/// Lee el fichero `path` y devuelve (texto, hash del contenido).
+pub(crate) fn read_source(path: &str) -> Result<(String, String), DomainError> {
+    let bytes = std::fs::read(path).map_err(|e| DomainError::ExternalError(format!("No se puede leer '{path}': {e}")))?;
+    let hash = formats::content_hash(&bytes);
+    let text =
+        String::from_utf8(bytes).map_err(|e| DomainError::ValidationError(format!("'{path}' no es UTF-8 válido: {e}")))?;
+    Ok((text, hash))
+}
+
+/// Hash del contenido actual de `path`, si se puede leer.
+pub(crate) fn current_hash(path: &str) -> Option<String> {
+    std::fs::read(path).ok().map(|bytes| formats::content_hash(&bytes))
+}
+
+/// Comprueba que el fichero no cambió desde que se fijaron los parámetros.
+pub(crate) fn check_hash(expected: Option<&str>, actual: &str, path: &str) -> Result<(), DomainError> {
+    match expected {
+        Some(expected) if expected != actual => {
+            Err(DomainError::ValidationError(format!("El contenido de '{path}' cambió (hash {actual}, se esperaba {expected})")))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileSourceStep {
+    pub params: FileSourceParams,
+}
+
+impl FileSourceStep {
+    pub fn new(params: FileSourceParams) -> Self {
+        Self { params }
+    }
+}
+
+/// Registro de entrada antes de pasar por el motor químico.
+enum Structure {
+    MolBlock(String),
+    Smiles(String),
+}
+
+type Tags = BTreeMap<String, String>;
+
+/// Registro leído (con sus tags) o mensaje de error del registro.
+type RecordResult<T> = Result<(T, Tags), String>;
+
+/// Extrae los registros del texto según el formato: (estructura, tags) o
+/// error por registro, en orden de fichero.
+fn read_records(text: &str, format: FileFormat, smiles_column: &str) -> Result<Vec<RecordResult<Structure>>, DomainError> {
+    Ok(match format {
+        FileFormat::Sdf | FileFormat::Mol => {
+            formats::parse_sdf(text).into_iter()
+                                    .map(|SdfRecord { molblock, tags }| Ok((Structure::MolBlock(molblock), tags)))
+                                    .collect()
+        }
+        FileFormat::Smiles => {
+            formats::parse_smiles_lines(text).into_iter()
+                                             .map(|(_, smiles, name)| {
+                                                 let tags = name.map(|n| ("_Name".to_string(), n)).into_iter().collect();
+                                                 Ok((Structure::Smiles(smiles), tags))
+                                             })
+                                             .collect()
+        }
+        FileFormat::Csv | FileFormat::Tsv => {
+            let delimiter = if format == FileFormat::Csv { ',' } else { '\t' };
+            let table = formats::parse_delimited(text, delimiter);
+            let column =
+                table.column(smiles_column)
+                     .ok_or_else(|| DomainError::ValidationError(format!("Falta la columna de SMILES '{smiles_column}'")))?;
+            table.rows
+                 .iter()
+                 .map(|(line, row)| {
+                     let smiles = row.get(column)
+                                     .filter(|s| !s.is_empty())
+                                     .ok_or_else(|| format!("Línea {line}: SMILES vacío"))?;
+                     let tags = table.header
+                                     .iter()
+                                     .zip(row)
+                                     .enumerate()
+                                     .filter(|(i, (_, value))| *i != column && !value.is_empty())
+                                     .map(|(_, (name, value))| (name.clone(), value.clone()))
+                                     .collect();
+                     Ok((Structure::Smiles(smiles.clone()), tags))
+                 })
+                 .collect()
+        }
+    })
+}
+
+/// Convierte los registros en moléculas con una llamada por lote al motor.
+fn to_molecules(records: Vec<RecordResult<Structure>>) -> Result<Vec<RecordResult<Molecule>>, DomainError> {
+    let mut molblocks = Vec::new();
+    let mut smiles = Vec::new();
+    for (structure, _) in records.iter().flatten() {
+        match structure {
+            Structure::MolBlock(b) => molblocks.push(b.as_str()),
+            Structure::Smiles(s) => smiles.push(s.as_str()),
+        }
+    }
+    let mut from_blocks = Molecule::from_molblock_batch(&molblocks)?.into_iter();
+    let mut from_smiles = Molecule::from_smiles_batch(&smiles)?.into_iter();
+    Ok(records.into_iter()
+              .map(|record| {
+                  let (structure, tags) = record?;
+                  let molecule = match structure {
+                      Structure::MolBlock(_) => from_blocks.next(),
+                      Structure::Smiles(_) => from_smiles.next(),
+                  };
+                  match molecule {
+                      Some(Ok(m)) => Ok((m, tags)),
+                      Some(Err(e)) => Err(e.to_string()),
+                      None => Err("Respuesta del motor incompleta".to_string()),
+                  }
+              })
+              .collect())
+}
+
+/// Error de una importación sin ningún registro válido: lleva los errores de
+/// cada registro, que de otro modo se perderían con el artifact.
+fn no_valid_records(path: &str, errors: &[RecordError]) -> DomainError {
+    if errors.is_empty() {
+        return DomainError::ValidationError(format!("'{path}' no contiene registros"));
+    }
+    let details: Vec<String> = errors.iter()
+                                     .map(|e| format!("registro {}: {}", e.index, e.message))
+                                     .collect();
+    DomainError::ValidationError(format!("Ningún registro válido en '{path}' ({} errores): {}",
+                                         errors.len(),
+                                         details.join("; ")))
+}
+
+fn import_file(params: &FileSourceParams) -> Result<FamilyRecordsArtifact, DomainError> {
+    let format = params.effective_format()?;
+    let (text, source_hash) = read_source(&params.path)?;
+    check_hash(params.content_hash.as_deref(), &source_hash, &params.path)?;
+    check_toolkit(params.toolkit.as_ref())?;
+    let smiles_column = params.smiles_column.as_deref().unwrap_or("smiles");
+    let converted = to_molecules(read_records(&text, format, smiles_column)?)?;
+
+    let mut molecules = Vec::new();
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = BTreeMap::new();
+    for (index, item) in converted.into_iter().enumerate() {
+        let (molecule, tags) = match item {
+            Ok(ok) => ok,
+            Err(message) => {
+                errors.push(RecordError { index, message });
+                continue;
+            }
+        };
+        if let Some(first) = seen.get(molecule.inchikey()) {
+            errors.push(RecordError { index,
+                                      message: format!("InChIKey duplicado {} (registro {first})", molecule.inchikey()) });
+            continue;
+        }
+        seen.insert(molecule.inchikey().to_string(), index);
+        let metadata = serde_json::json!({
+            "source": "file",
+            "format": format.name(),
+            "record": index,
+            "tags": tags,
+        });
+        molecules.push(Molecule::from_parts(molecule.inchikey(), molecule.smiles(), molecule.inchi(), metadata)?);
+        records.push(MoleculeRecord { inchikey: molecule.inchikey().to_string(),
+                                      smiles: molecule.smiles().to_string(),
+                                      inchi: molecule.inchi().to_string(),
+                                      tags });
+    }
+    let provenance = serde_json::json!({
+        "source": "file",
+        "path": params.path,
+        "format": format.name(),
+        "content_hash": source_hash,
+        "n_records": records.len() + errors.len(),
+        "n_errors": errors.len(),
+    });
+    let parameters = serde_json::json!({
+        "source": "file",
+        "format": format.name(),
+        "content_hash": source_hash,
+    });
+    if molecules.is_empty() {
+        return Err(no_valid_records(&params.path, &errors));
+    }
+    let family = MoleculeFamily::new_with_parameters(molecules, provenance, parameters)?;
+    Ok(FamilyRecordsArtifact { family_hash: family.family_hash().to_string(),
+                               format: format.name().to_string(),
+                               source_hash,
+                               records,
+                               properties: Vec::new(),
+                               errors,
+                               schema_version: 1 })
+}
+
+impl TypedStep for FileSourceStep {
+    type Params = FileSourceParams;
+    type Input = FamilyRecordsArtifact; // ignorado (Source)
+    type Output = FamilyRecordsArtifact;
+
+    fn id(&self) -> &'static str {
+        "import_file"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Source
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = self.params.clone();
+        if params.content_hash.is_none() {
+            params.content_hash = current_hash(&params.path);
+        }
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, _input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        match import_file(&params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+/// Parámetros de exportación: ruta del SDF a escribir.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SdfSinkParams {
+    pub path: String,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SdfSinkStep {
+    pub params: SdfSinkParams,
+}
+
+impl SdfSinkStep {
+    pub fn new(params: SdfSinkParams) -> Self {
+        Self { params }
+    }
+}
+
+/// Texto de un valor de propiedad para un SD tag: números y cadenas tal cual,
+/// objetos con `value` por su valor y el resto como JSON.
+fn tag_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Object(map) if map.contains_key("value") => tag_value(&map["value"]),
+        other => other.to_string(),
+    }
+}
+
+/// Tags de propiedades preferidas por InChIKey: `<kind>` con el valor y
+/// `<kind>_units` si hay unidades. Por (molécula, tipo) se usa el único
+/// valor o el marcado `preferred`; si no se puede decidir, error.
+fn property_tags(items: &[PropertyItem]) -> Result<BTreeMap<&str, BTreeMap<String, String>>, DomainError> {
+    let mut grouped: BTreeMap<(&str, &str), Vec<&PropertyItem>> = BTreeMap::new();
+    for item in items {
+        grouped.entry((item.molecule_inchikey.as_str(), item.property_kind.as_str()))
+               .or_default()
+               .push(item);
+    }
+    let mut tags: BTreeMap<&str, BTreeMap<String, String>> = BTreeMap::new();
+    for ((inchikey, kind), candidates) in grouped {
+        let preferred: Vec<_> = candidates.iter().filter(|p| p.preferred == Some(true)).collect();
+        let chosen = match (candidates.as_slice(), preferred.as_slice()) {
+            ([only], _) => *only,
+            (_, [p]) => **p,
+            _ => return Err(DomainError::ValidationError(format!("Valor preferido ambiguo de '{kind}' para {inchikey}"))),
+        };
+        let entry = tags.entry(inchikey).or_default();
+        entry.insert(kind.to_string(), tag_value(&chosen.value));
+        if let Some(units) = &chosen.units {
+            entry.insert(format!("{kind}_units"), units.clone());
+        }
+    }
+    Ok(tags)
+}
+
+fn export_sdf(input: &FamilyRecordsArtifact, params: &SdfSinkParams) -> Result<FileExportArtifact, DomainError> {
+    check_toolkit(params.toolkit.as_ref())?;
+    let path = params.path.as_str();
+    let molecules = input.records
+                         .iter()
+                         .map(|r| Molecule::from_parts(&r.inchikey, &r.smiles, &r.inchi, serde_json::json!({})))
+                         .collect::<Result<Vec<_>, _>>()?;
+    let family = MoleculeFamily::new(molecules, serde_json::json!({ "source": "sdf_export" }))?;
+    let mut properties = property_tags(&input.properties)?;
+    for key in properties.keys() {
+        if !family.contains(key) {
+            return Err(DomainError::ValidationError(format!("Propiedad de una molécula ajena a la familia: {key}")));
+        }
+    }
+    let records: Vec<SdfRecord> = input.records
+                                       .iter()
+                                       .zip(family.molblocks()?)
+                                       .map(|(record, molblock)| {
+                                           let mut tags = record.tags.clone();
+                                           tags.extend(properties.remove(record.inchikey.as_str()).unwrap_or_default());
+                                           SdfRecord { molblock, tags }
+                                       })
+                                       .collect();
+    let text = formats::write_sdf(&records);
+    std::fs::write(path, &text).map_err(|e| DomainError::ExternalError(format!("No se puede escribir '{path}': {e}")))?;
+    Ok(FileExportArtifact { family_hash: input.family_hash.clone(),
+                            path: path.to_string(),
+                            format: FileFormat::Sdf.name().to_string(),
+                            content_hash: formats::content_hash(text.as_bytes()),
+                            n_records: records.len(),
+                            schema_version: 1 })
+}
+
+impl TypedStep for SdfSinkStep {
+    type Params = SdfSinkParams;
+    type Input = FamilyRecordsArtifact;
+    type Output = FileExportArtifact;
+
+    fn id(&self) -> &'static str {
+        "export_sdf"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Sink
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = self.params.clone();
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        match export_sdf(&inp, &params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chem_domain::toolkit_versions;
+    use serde_json::json;
+
+    fn item(key: &str, kind: &str, value: serde_json::Value, preferred: Option<bool>) -> PropertyItem {
+        PropertyItem { molecule_inchikey: key.to_string(),
+                       property_kind: kind.to_string(),
+                       value,
+                       units: Some("kJ/mol".to_string()),
+                       preferred,
+                       provider: None }
+    }
+
+    #[test]
+    fn property_tags_use_preferred_values() {
+        let items = vec![item("A", "dG", json!(-1.5), None),
+                         item("A", "dG", json!({"value": -2.0}), Some(true)),
+                         item("B", "name", json!("x"), None)];
+        let tags = property_tags(&items).unwrap();
+        assert_eq!(tags["A"]["dG"], "-2.0");
+        assert_eq!(tags["A"]["dG_units"], "kJ/mol");
+        assert_eq!(tags["B"]["name"], "x");
+        let ambiguous = vec![item("A", "dG", json!(1), None), item("A", "dG", json!(2), None)];
+        assert!(property_tags(&ambiguous).is_err());
+    }
+
+    #[test]
+    fn source_params_fold_content_hash() {
+        let path = std::env::temp_dir().join(format!("chemflow_file_io_{}.smi", std::process::id()));
+        std::fs::write(&path, "CCO ethanol\n").unwrap();
+        let step = FileSourceStep::new(FileSourceParams { path: path.to_string_lossy().into_owned(),
+                                                          ..Default::default() });
+        let params = step.params_default();
+        assert_eq!(params.content_hash.as_deref(),
+                   Some(formats::content_hash(b"CCO ethanol\n").as_str()));
+        assert_eq!(params.effective_format().unwrap(), FileFormat::Smiles);
+
+        std::fs::write(&path, "CCN\n").unwrap();
+        let err = import_file(&params).err().unwrap();
+        assert!(err.to_string().contains("cambió"));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn source_params_fold_toolkit_versions() {
+        let path = std::env::temp_dir().join(format!("chemflow_file_io_toolkit_{}.smi", std::process::id()));
+        std::fs::write(&path, "CCO ethanol\n").unwrap();
+        let step = FileSourceStep::new(FileSourceParams { path: path.to_string_lossy().into_owned(),
+                                                          ..Default::default() });
+        let mut params = step.params_default();
+        let current = toolkit_versions().unwrap();
+        assert_eq!(params.toolkit.as_ref(), Some(current));
+
+        params.toolkit = Some(ToolkitVersions { backend: "other".to_string(),
+                                                ..current.clone() });
+        let err = import_file(&params).err().unwrap();
+        assert!(matches!(&err, DomainError::EnvironmentDrift(d) if d.starts_with("backend other →")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn import_without_valid_records_reports_each_error() {
+        let errors = vec![RecordError { index: 0,
+                                        message: "SMILES inválido".into() },
+                          RecordError { index: 1,
+                                        message: "sin estructura".into() }];
+        let message = no_valid_records("a.smi", &errors).to_string();
+        assert!(message.contains("2 errores"), "{message}");
+        assert!(message.contains("registro 0: SMILES inválido; registro 1: sin estructura"),
+                "{message}");
+        assert!(no_valid_records("a.smi", &[]).to_string().contains("no contiene registros"));
+    }
+}
@@ -0,0 +1,188 @@
+//! StructuralAlertFilterStep (Transform con selección)
+//!
+//! - Recibe un `FamilyStructuresArtifact` y descarta las moléculas que disparan
+//!   alertas de los catálogos PAINS/Brenk de RDKit.
+//! - Produce un `FilteredFamilyArtifact` con la familia retenida (nuevo
+//!   `family_hash`), las moléculas descartadas y sus alertas. Que todas las
+//!   moléculas sean descartadas no es un fallo: la familia retenida queda vacía
+//!   (`family_hash: None`).
+//! - Determinista: se conserva el orden de entrada, los catálogos se normalizan
+//!   (ordenados, sin duplicados) y las alertas de cada molécula vienen
+//!   ordenadas por (catálogo, descripción).
+//! - Las versiones del toolkit (RDKit) van en los parámetros y por tanto en el
+//!   fingerprint del step.
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{check_toolkit, DomainError, FilterCatalog, StructuralAlert, ToolkitVersions};
+
+use crate::artifacts::{FamilyMember, FamilyStructuresArtifact, FilteredFamilyArtifact, RejectedMolecule};
+use crate::errors::core_error;
+use crate::steps::fill_toolkit;
+
+/// Parámetros del filtro. `catalogs` vacío = PAINS (A, B, C) + Brenk.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct StructuralAlertParams {
+    #[serde(default)]
+    pub catalogs: Vec<FilterCatalog>,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+impl StructuralAlertParams {
+    /// Catálogos efectivos en orden canónico.
+    pub fn effective_catalogs(&self) -> Vec<FilterCatalog> {
+        let mut catalogs = if self.catalogs.is_empty() {
+            FilterCatalog::ALL.to_vec()
+        } else {
+            self.catalogs.clone()
+        };
+        catalogs.sort();
+        catalogs.dedup();
+        catalogs
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct StructuralAlertFilterStep;
+
+impl StructuralAlertFilterStep {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// Separa los miembros limpios de los que tienen alertas (`alerts` alineado
+/// con `members`), conservando el orden de entrada.
+fn partition_members(members: Vec<FamilyMember>,
+                     alerts: Vec<Vec<StructuralAlert>>)
+                     -> (Vec<FamilyMember>, Vec<RejectedMolecule>) {
+    let mut kept = Vec::new();
+    let mut rejected = Vec::new();
+    for (member, hits) in members.into_iter().zip(alerts) {
+        if hits.is_empty() {
+            kept.push(member);
+        } else {
+            rejected.push(RejectedMolecule { inchikey: member.inchikey,
+                                             alerts: hits.into_iter()
+                                                         .map(|a| format!("{}:{}", a.catalog, a.description))
+                                                         .collect() });
+        }
+    }
+    (kept, rejected)
+}
+
+/// Construye el artifact filtrado a partir de las alertas de cada miembro
+/// (alineadas con `input.molecules`).
+fn filtered_artifact(input: FamilyStructuresArtifact,
+                     alerts: Vec<Vec<StructuralAlert>>,
+                     catalog_names: Vec<String>)
+                     -> Result<FilteredFamilyArtifact, DomainError> {
+    let (kept, rejected) = partition_members(input.molecules.clone(), alerts);
+    let family_hash = if kept.is_empty() {
+        None
+    } else {
+        let filtered = input.subfamily(&kept,
+                                       serde_json::json!({
+                                           "operation": "filter_structural_alerts",
+                                           "catalogs": catalog_names,
+                                           "parent_family_hash": input.family_hash,
+                                       }))?;
+        Some(filtered.family_hash().to_string())
+    };
+    Ok(FilteredFamilyArtifact { family_hash,
+                                parent_family_hash: input.family_hash,
+                                molecules: kept,
+                                rejected,
+                                catalogs: catalog_names,
+                                schema_version: 1 })
+}
+
+fn filter_family(input: FamilyStructuresArtifact,
+                 params: &StructuralAlertParams)
+                 -> Result<FilteredFamilyArtifact, DomainError> {
+    check_toolkit(params.toolkit.as_ref())?;
+    let catalogs = &params.effective_catalogs();
+    let catalog_names: Vec<String> = catalogs.iter().map(|c| c.name().to_string()).collect();
+    let alerts = input.to_family()?.structural_alerts(catalogs)?;
+    filtered_artifact(input, alerts, catalog_names)
+}
+
+impl TypedStep for StructuralAlertFilterStep {
+    type Params = StructuralAlertParams;
+    type Input = FamilyStructuresArtifact;
+    type Output = FilteredFamilyArtifact;
+
+    fn id(&self) -> &'static str {
+        "filter_structural_alerts"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Transform
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = StructuralAlertParams::default();
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        match filter_family(inp, &params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partition_keeps_order_and_records_alerts() {
+        let member = |k: &str| FamilyMember { inchikey: k.to_string(),
+                                              smiles: "C".to_string(),
+                                              inchi: "InChI=1S/CH4/h1H4".to_string() };
+        let alert = StructuralAlert { catalog: "brenk".to_string(),
+                                      description: "catechol".to_string() };
+        let (kept, rejected) =
+            partition_members(vec![member("A"), member("B"), member("C")], vec![vec![], vec![alert], vec![]]);
+        assert_eq!(kept.iter().map(|m| m.inchikey.as_str()).collect::<Vec<_>>(), vec!["A", "C"]);
+        assert_eq!(rejected,
+                   vec![RejectedMolecule { inchikey: "B".to_string(),
+                                           alerts: vec!["brenk:catechol".to_string()] }]);
+
+        let params =
+            StructuralAlertParams { catalogs: vec![FilterCatalog::Brenk, FilterCatalog::PainsA, FilterCatalog::Brenk],
+                                    ..Default::default() };
+        assert_eq!(params.effective_catalogs(), vec![FilterCatalog::PainsA, FilterCatalog::Brenk]);
+        assert_eq!(StructuralAlertParams::default().effective_catalogs(),
+                   FilterCatalog::ALL.to_vec());
+    }
+
+    #[test]
+    fn rejecting_every_member_is_not_a_failure() {
+        let methane = FamilyMember { inchikey: "VNWKTOKETHGBQD-UHFFFAOYSA-N".to_string(),
+                                     smiles: "C".to_string(),
+                                     inchi: "InChI=1S/CH4/h1H4".to_string() };
+        let input = FamilyStructuresArtifact { family_hash: "parent".to_string(),
+                                               molecules: vec![methane.clone()],
+                                               identity: None,
+                                               parameters: None,
+                                               schema_version: 1 };
+        let alert = || StructuralAlert { catalog: "brenk".to_string(),
+                                         description: "catechol".to_string() };
+        let out = filtered_artifact(input.clone(), vec![vec![alert()]], vec!["brenk".to_string()]).unwrap();
+        assert_eq!(out.family_hash, None);
+        assert!(out.molecules.is_empty());
+        assert_eq!(out.rejected.len(), 1);
+        assert_eq!(out.parent_family_hash, "parent");
+
+        let out = filtered_artifact(input, vec![vec![]], vec!["brenk".to_string()]).unwrap();
+        assert!(out.family_hash.is_some());
+        assert_eq!(out.molecules, vec![methane]);
+    }
+}
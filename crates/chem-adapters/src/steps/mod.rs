@@ -1,5 +1,36 @@
-//! Steps iniciales de F4: Acquire (Source) y Compute (Transform stub).
+//! Steps iniciales de F4: Acquire (Source) y Compute (Transform stub), y
+//! filtros estructurales (PAINS/Brenk), agregados de familia e import/export
+//! de ficheros (SDF/MOL, SMILES, CSV), importación tabular de propiedades
+//! medidas, generación de conformeros, enumeración por reacción y análisis
+//! de scaffolds/R-grupos.
+//!
+//! Los steps químicos llevan en sus parámetros las versiones del toolkit
+//! (`toolkit: Option<ToolkitVersions>`), que `fill_toolkit` rellena con las
+//! del entorno al construir los parámetros por defecto. Así entran en el
+//! fingerprint del step y `check_toolkit` falla si cambian al reejecutar.
 
 pub mod acquire;
+pub mod aggregate;
 pub mod compute;
+pub mod conformers;
+pub mod file_io;
+pub mod filter;
 pub mod policy_demo;
+pub mod reaction;
+pub mod scaffolds;
+pub mod tabular;
+
+use chem_domain::{toolkit_versions, ToolkitVersions};
+
+/// Rellena `toolkit` con las versiones del entorno si no se fijaron. Si no
+/// se pueden determinar se deja vacío (el step no comprobará el entorno) y
+/// se avisa en el log.
+pub(crate) fn fill_toolkit(toolkit: &mut Option<ToolkitVersions>) {
+    if toolkit.is_some() {
+        return;
+    }
+    match toolkit_versions() {
+        Ok(current) => *toolkit = Some(current.clone()),
+        Err(e) => log::warn!("No se pueden determinar las versiones del toolkit químico; el step no las fijará: {e}"),
+    }
+}
@@ -0,0 +1,134 @@
+//! ReactionEnumerationStep (Transform, enumeración por SMARTS de reacción)
+//!
+//! - La familia de entrada (`FamilyStructuresArtifact`) aporta los reactivos de
+//!   la primera plantilla; `params.reagents` los de las siguientes (SMILES por
+//!   plantilla), p. ej. ácidos de la familia + aminas para un acoplamiento de
+//!   amidas.
+//! - Produce un `EnumeratedFamilyArtifact` con la familia nueva: su provenance
+//!   y su `family_hash` registran la reacción, los hashes de las familias de
+//!   reactivos y los límites de la enumeración.
+//! - Determinista: combinaciones en orden lexicográfico de reactivos, productos
+//!   ordenados por SMILES dentro de cada combinación y deduplicados por
+//!   InChIKey (primera aparición).
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{
+    check_toolkit, DomainError, EnumerationLimits, Molecule, MoleculeFamily, ReactionEnumerator, ToolkitVersions,
+};
+
+use crate::artifacts::{EnumeratedFamilyArtifact, EnumeratedProduct, FamilyStructuresArtifact};
+use crate::errors::core_error;
+use crate::steps::fill_toolkit;
+
+/// Parámetros de la enumeración.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ReactionParams {
+    /// SMARTS de reacción (`reactivos>>productos`).
+    pub reaction: String,
+    /// Reactivos (SMILES) de la segunda plantilla en adelante.
+    #[serde(default)]
+    pub reagents: Vec<Vec<String>>,
+    #[serde(default)]
+    pub limits: EnumerationLimits,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReactionEnumerationStep {
+    pub params: ReactionParams,
+}
+
+impl ReactionEnumerationStep {
+    pub fn new(params: ReactionParams) -> Self {
+        Self { params }
+    }
+}
+
+/// Familia de reactivos de la plantilla `template` a partir de sus SMILES.
+fn reagent_family(smiles: &[String], template: usize) -> Result<MoleculeFamily, DomainError> {
+    let smiles: Vec<&str> = smiles.iter().map(String::as_str).collect();
+    let molecules = Molecule::from_smiles_batch(&smiles)?.into_iter()
+                                                         .collect::<Result<Vec<_>, _>>()?;
+    MoleculeFamily::new(molecules,
+                        serde_json::json!({ "source": "reaction_reagents", "template": template }))
+}
+
+fn enumerate(input: FamilyStructuresArtifact, params: &ReactionParams) -> Result<EnumeratedFamilyArtifact, DomainError> {
+    check_toolkit(params.toolkit.as_ref())?;
+    let enumerator = ReactionEnumerator::new(params.reaction.as_str(), params.limits)?;
+    let mut families = vec![input.to_family()?];
+    for (i, smiles) in params.reagents.iter().enumerate() {
+        families.push(reagent_family(smiles, i + 1)?);
+    }
+    let refs: Vec<&MoleculeFamily> = families.iter().collect();
+    let enumeration = enumerator.enumerate(&refs)?;
+    let products = enumeration.family
+                              .molecules()
+                              .iter()
+                              .zip(enumeration.reagents)
+                              .map(|(m, reagents)| EnumeratedProduct { inchikey: m.inchikey().to_string(),
+                                                                       smiles: m.smiles().to_string(),
+                                                                       inchi: m.inchi().to_string(),
+                                                                       reagents })
+                              .collect();
+    Ok(EnumeratedFamilyArtifact { family_hash: enumeration.family.family_hash().to_string(),
+                                  reaction: enumerator.reaction().to_string(),
+                                  reagent_family_hashes: families.iter().map(|f| f.family_hash().to_string()).collect(),
+                                  limits: params.limits,
+                                  products,
+                                  n_duplicates: enumeration.n_duplicates,
+                                  n_failed: enumeration.n_failed,
+                                  truncated: enumeration.truncated,
+                                  schema_version: 1 })
+}
+
+impl TypedStep for ReactionEnumerationStep {
+    type Params = ReactionParams;
+    type Input = FamilyStructuresArtifact;
+    type Output = EnumeratedFamilyArtifact;
+
+    fn id(&self) -> &'static str {
+        "enumerate_reaction"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Transform
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = self.params.clone();
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        match enumerate(inp, &params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reagent_families_and_params() {
+        let first = reagent_family(&["CN".to_string(), "NCCO".to_string()], 1).unwrap();
+        let again = reagent_family(&["CN".to_string(), "NCCO".to_string()], 1).unwrap();
+        assert_eq!(first.family_hash(), again.family_hash());
+        assert_eq!(first.provenance()["template"], 1);
+        assert!(reagent_family(&[], 1).is_err());
+        assert!(reagent_family(&["CN".to_string(), "C1CC".to_string()], 1).is_err());
+
+        let params: ReactionParams =
+            serde_json::from_value(serde_json::json!({ "reaction": "[C:1](=O)[OH].[N;!H0:2]>>[C:1](=O)[N:2]" })).unwrap();
+        assert_eq!(params.limits, EnumerationLimits::default());
+        assert!(params.reagents.is_empty());
+    }
+}
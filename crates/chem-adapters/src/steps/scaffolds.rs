@@ -0,0 +1,105 @@
+//! ScaffoldAnalysisStep (Transform de análisis, sin selección)
+//!
+//! - Recibe un `FamilyStructuresArtifact` y produce un `ScaffoldTableArtifact`
+//!   (tabla derivada) para revisiones SAR: scaffold de Bemis–Murcko → InChIKeys
+//!   de sus miembros y R-grupos de cada molécula.
+//! - `params.cores` fija los núcleos de la descomposición; vacío = los
+//!   scaffolds de la propia familia.
+//! - Determinista: scaffolds por número de miembros y SMILES, miembros y
+//!   R-grupos en el orden de la familia, R-grupos por etiqueta.
+
+use chem_core::errors::CoreEngineError;
+use chem_core::step::{StepKind, StepRunResultTyped, TypedStep};
+use chem_domain::{check_toolkit, AnalysisProvider, DomainError, ScaffoldAnalysis, ToolkitVersions};
+
+use crate::artifacts::{FamilyStructuresArtifact, ScaffoldTableArtifact};
+use crate::errors::core_error;
+use crate::steps::fill_toolkit;
+
+/// Parámetros del análisis.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ScaffoldParams {
+    /// Núcleos (SMILES o SMARTS) para los R-grupos.
+    #[serde(default)]
+    pub cores: Vec<String>,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScaffoldAnalysisStep {
+    pub params: ScaffoldParams,
+}
+
+impl ScaffoldAnalysisStep {
+    pub fn new(params: ScaffoldParams) -> Self {
+        Self { params }
+    }
+}
+
+fn analyze(input: FamilyStructuresArtifact, params: &ScaffoldParams) -> Result<ScaffoldTableArtifact, DomainError> {
+    check_toolkit(params.toolkit.as_ref())?;
+    let family = input.to_family()?;
+    let table = ScaffoldAnalysis::new(params.cores.clone()).analyze(&family)?;
+    Ok(ScaffoldTableArtifact { family_hash: input.family_hash,
+                               cores: table.cores,
+                               scaffolds: table.scaffolds,
+                               rgroups: table.rgroups,
+                               schema_version: 1 })
+}
+
+impl TypedStep for ScaffoldAnalysisStep {
+    type Params = ScaffoldParams;
+    type Input = FamilyStructuresArtifact;
+    type Output = ScaffoldTableArtifact;
+
+    fn id(&self) -> &'static str {
+        "analyze_scaffolds"
+    }
+    fn kind(&self) -> StepKind {
+        StepKind::Transform
+    }
+    fn params_default(&self) -> Self::Params {
+        let mut params = self.params.clone();
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+
+    fn run_typed(&self, input: Option<Self::Input>, params: Self::Params) -> StepRunResultTyped<Self::Output> {
+        let Some(inp) = input else {
+            return StepRunResultTyped::Failure { error: CoreEngineError::MissingInputs };
+        };
+        match analyze(inp, &params) {
+            Ok(out) => StepRunResultTyped::Success { outputs: vec![out] },
+            Err(e) => StepRunResultTyped::Failure { error: core_error(e) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::artifacts::FamilyMember;
+
+    #[test]
+    fn recorded_toolkit_is_checked_first() {
+        let input = FamilyStructuresArtifact { family_hash: "h".to_string(),
+                                               molecules: vec![FamilyMember { inchikey:
+                                                                                  "LFQSCWFLJHTTHZ-UHFFFAOYSA-N".to_string(),
+                                                                              smiles: "CCO".to_string(),
+                                                                              inchi: "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3"
+                                                                                                 .to_string() }],
+                                               identity: None,
+                                               parameters: None,
+                                               schema_version: 1 };
+        let params = ScaffoldParams { toolkit: Some(ToolkitVersions { backend: "other".to_string(),
+                                                                      rdkit: None,
+                                                                      python: None,
+                                                                      rust: None }),
+                                      ..Default::default() };
+        assert!(matches!(analyze(input, &params), Err(DomainError::EnvironmentDrift(_))));
+        let params: ScaffoldParams = serde_json::from_value(serde_json::json!({})).unwrap();
+        assert!(params.cores.is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+//! TabularImportStep (Source desde CSV/TSV con propiedades medidas)
+//!
+//! - Lee una hoja de cálculo exportada a CSV/TSV: una columna de SMILES y
+//!   columnas de valores medidos, según un mapeo de columnas (`ColumnMapping`:
+//!   tipo de propiedad, unidades y proveedor).
+//! - Emite dos artifacts: `FamilyPropertiesArtifact` (primero, es el que
+//!   encadena con el siguiente step) y `FamilyArtifact` con la familia.
+//! - Los valores quedan etiquetados con el proveedor `experimental` (o el
+//!   indicado en el mapeo); su versión es el prefijo del hash del fichero.
+//! - El hash del contenido se fija en los parámetros (`base_params`), así que
+//!   entra en el fingerprint del step: una hoja modificada invalida la caché.
+//!   Lo mismo con las versiones del toolkit químico. Las filas inválidas o
+//!   duplicadas se reportan con la señal `TABULAR_IMPORT_ERRORS` sin abortar la
+//!   importación.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use chem_core::model::{ArtifactSpec, ExecutionContext};
+use chem_core::step::{StepDefinition, StepKind, StepRunResult, StepSignal};
+use chem_domain::{check_toolkit, DomainError, Molecule, MoleculeFamily, PropertyProvider, ToolkitVersions, Unit};
+
+use crate::artifacts::{FamilyArtifact, FamilyPropertiesArtifact, PropertyItem, RecordError};
+use crate::errors::core_error;
+use crate::formats::{self, FileFormat};
+use crate::steps::file_io::{check_hash, current_hash, read_source};
+use crate::steps::fill_toolkit;
+
+/// Proveedor por defecto de los valores importados.
+pub const EXPERIMENTAL_PROVIDER: &str = "experimental";
+
+/// Mapeo de una columna de la tabla a una propiedad molecular.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ColumnMapping {
+    pub column: String,
+    /// Tipo de propiedad; por defecto el nombre de la columna.
+    #[serde(default)]
+    pub property_kind: Option<String>,
+    /// Unidades (deben estar registradas en `chem_domain::units`).
+    #[serde(default)]
+    pub units: Option<String>,
+    /// Proveedor; por defecto `experimental`.
+    #[serde(default)]
+    pub provider: Option<String>,
+}
+
+impl ColumnMapping {
+    pub fn property_kind(&self) -> &str {
+        self.property_kind.as_deref().unwrap_or(&self.column)
+    }
+}
+
+/// Parámetros de importación tabular. `delimiter` se deduce de la extensión
+/// (`.tsv`/`.tab` = tabulador) si no se indica.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TabularImportParams {
+    pub path: String,
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    /// Columna de SMILES; por defecto `smiles`.
+    #[serde(default)]
+    pub smiles_column: Option<String>,
+    #[serde(default)]
+    pub properties: Vec<ColumnMapping>,
+    /// Hash del contenido esperado; `effective_params` lo rellena.
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    /// Versiones del toolkit químico (ver `fill_toolkit`).
+    #[serde(default)]
+    pub toolkit: Option<ToolkitVersions>,
+}
+
+impl TabularImportParams {
+    fn effective_delimiter(&self) -> char {
+        self.delimiter
+            .unwrap_or_else(|| match FileFormat::from_path(Path::new(&self.path)) {
+                Some(FileFormat::Tsv) => '\t',
+                _ => ',',
+            })
+    }
+}
+
+/// Resultado de la importación: familia, propiedades medidas y filas
+/// descartadas.
+pub struct TabularImport {
+    pub family: FamilyArtifact,
+    pub properties: FamilyPropertiesArtifact,
+    pub errors: Vec<RecordError>,
+}
+
+/// Valor de una celda: número si se puede interpretar, texto en otro caso
+/// (p. ej. `>10`).
+fn cell_value(cell: &str) -> serde_json::Value {
+    cell.parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .map_or_else(|| serde_json::json!(cell), |v| serde_json::json!(v))
+}
+
+/// Importa la tabla descrita por `params`.
+pub fn import_table(params: &TabularImportParams) -> Result<TabularImport, DomainError> {
+    let (text, source_hash) = read_source(&params.path)?;
+    check_hash(params.content_hash.as_deref(), &source_hash, &params.path)?;
+    check_toolkit(params.toolkit.as_ref())?;
+    let table = formats::parse_delimited(&text, params.effective_delimiter());
+    let smiles_column = params.smiles_column.as_deref().unwrap_or("smiles");
+    let smiles_index =
+        table.column(smiles_column)
+             .ok_or_else(|| DomainError::ValidationError(format!("Falta la columna de SMILES '{smiles_column}'")))?;
+    // Resolver columnas, unidades y proveedores antes de leer filas.
+    let provider_version: String = source_hash.chars().take(12).collect();
+    let mut columns = Vec::with_capacity(params.properties.len());
+    for mapping in &params.properties {
+        let index = table.column(&mapping.column)
+                         .ok_or_else(|| DomainError::ValidationError(format!("Falta la columna '{}'", mapping.column)))?;
+        let units = mapping.units
+                           .as_deref()
+                           .map(|u| Unit::parse(u).map(|u| u.symbol().to_string()))
+                           .transpose()?;
+        let provider = PropertyProvider::new(mapping.provider.as_deref().unwrap_or(EXPERIMENTAL_PROVIDER),
+                                             provider_version.clone())?;
+        columns.push((index, mapping, units, provider));
+    }
+
+    let smiles: Vec<&str> = table.rows
+                                 .iter()
+                                 .map(|(_, row)| row.get(smiles_index).map_or("", String::as_str))
+                                 .collect();
+    let molecules = Molecule::from_smiles_batch(&smiles)?;
+
+    let mut family = Vec::new();
+    let mut items = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen = BTreeSet::new();
+    for (index, ((line, row), molecule)) in table.rows.iter().zip(molecules).enumerate() {
+        let molecule = match molecule {
+            Ok(m) => m,
+            Err(e) => {
+                errors.push(RecordError { index,
+                                          message: format!("Línea {line}: {e}") });
+                continue;
+            }
+        };
+        if !seen.insert(molecule.inchikey().to_string()) {
+            errors.push(RecordError { index,
+                                      message: format!("Línea {line}: InChIKey duplicado {}", molecule.inchikey()) });
+            continue;
+        }
+        for (column, mapping, units, provider) in &columns {
+            let Some(cell) = row.get(*column).filter(|c| !c.is_empty()) else {
+                continue;
+            };
+            items.push(PropertyItem { molecule_inchikey: molecule.inchikey().to_string(),
+                                      property_kind: mapping.property_kind().to_string(),
+                                      value: cell_value(cell),
+                                      units: units.clone(),
+                                      preferred: None,
+                                      provider: Some(provider.clone()) });
+        }
+        family.push(molecule);
+    }
+    let provenance = serde_json::json!({
+        "source": "tabular",
+        "path": params.path,
+        "content_hash": source_hash,
+        "smiles_column": smiles_column,
+        "properties": params.properties,
+    });
+    let parameters = serde_json::json!({
+        "source": "tabular",
+        "content_hash": source_hash,
+    });
+    let family = MoleculeFamily::new_with_parameters(family, provenance, parameters)?;
+    let family_hash = family.family_hash().to_string();
+    Ok(TabularImport { family: FamilyArtifact { family_hash: family_hash.clone(),
+                                                ordered_keys: family.molecules()
+                                                                    .iter()
+                                                                    .map(|m| m.inchikey().to_string())
+                                                                    .collect(),
+                                                schema_version: 1 },
+                       properties: FamilyPropertiesArtifact { family_hash,
+                                                              items,
+                                                              schema_version: 1 },
+                       errors })
+}
+
+/// Step Source de importación tabular. Implementa `StepDefinition`
+/// directamente porque emite dos artifacts de tipos distintos.
+#[derive(Clone, Debug)]
+pub struct TabularImportStep {
+    pub params: TabularImportParams,
+}
+
+impl TabularImportStep {
+    pub fn new(params: TabularImportParams) -> Self {
+        Self { params }
+    }
+
+    /// Parámetros efectivos: los del step con el hash actual del fichero y
+    /// las versiones del toolkit.
+    pub fn effective_params(&self) -> TabularImportParams {
+        let mut params = self.params.clone();
+        if params.content_hash.is_none() {
+            params.content_hash = current_hash(&params.path);
+        }
+        fill_toolkit(&mut params.toolkit);
+        params
+    }
+}
+
+impl StepDefinition for TabularImportStep {
+    fn id(&self) -> &str {
+        "import_tabular"
+    }
+
+    fn base_params(&self) -> serde_json::Value {
+        serde_json::to_value(self.effective_params()).expect("serialize tabular params")
+    }
+
+    fn run(&self, ctx: &ExecutionContext) -> StepRunResult {
+        let params: TabularImportParams = match ctx.params_as() {
+            Ok(params) => params,
+            Err(e) => {
+                let error = DomainError::ValidationError(format!("Parámetros inválidos de import_tabular: {e}"));
+                return StepRunResult::Failure { error: core_error(error) };
+            }
+        };
+        match import_table(&params) {
+            Ok(import) => {
+                let outputs = vec![import.properties.into_artifact(), import.family.into_artifact()];
+                if import.errors.is_empty() {
+                    StepRunResult::Success { outputs }
+                } else {
+                    let data = serde_json::json!({ "errors": import.errors });
+                    StepRunResult::SuccessWithSignals { outputs,
+                                                        signals: vec![StepSignal { signal:
+                                                                                       "TABULAR_IMPORT_ERRORS".into(),
+                                                                                   data }] }
+                }
+            }
+            Err(e) => StepRunResult::Failure { error: core_error(e) },
+        }
+    }
+
+    fn kind(&self) -> StepKind {
+        StepKind::Source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_and_cells() {
+        let path = std::env::temp_dir().join(format!("chemflow_tabular_{}.tsv", std::process::id()));
+        std::fs::write(&path, "smiles\tpIC50\n").unwrap();
+        let step = TabularImportStep::new(TabularImportParams { path: path.to_string_lossy().into_owned(),
+                                                                properties: vec![ColumnMapping { column:
+                                                                                                     "pIC50".into(),
+                                                                                                 ..Default::default() }],
+                                                                ..Default::default() });
+        let params = step.effective_params();
+        assert_eq!(params.effective_delimiter(), '\t');
+        let first = step.base_params();
+        std::fs::write(&path, "smiles\tpIC50\nCCO\t6.5\n").unwrap();
+        assert_ne!(step.base_params(), first, "el hash del fichero entra en los parámetros");
+        assert!(import_table(&params).is_err(), "contenido distinto al fijado");
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(cell_value("6.5"), serde_json::json!(6.5));
+        assert_eq!(cell_value(">10"), serde_json::json!(">10"));
+        assert_eq!(params.properties[0].property_kind(), "pIC50");
+
+        let ctx = ExecutionContext { input: None,
+                                     params: serde_json::json!({ "path": 42 }) };
+        assert!(matches!(step.run(&ctx), StepRunResult::Failure { .. }),
+                "parámetros mal formados no se sustituyen por los del step");
+    }
+}
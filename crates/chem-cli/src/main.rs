@@ -2,11 +2,119 @@
 //!
 //! This is a simple CLI binary that demonstrates the ChemFlow engine.
 //! For more advanced usage, see the main binary in the root.
+//!
+//! Subcommands (database taken from `DATABASE_*` / `.env`):
+//! - `chem-cli migrate-tenant <tenant_id>`: create/migrate the tenant schema.
+//! - `chem-cli list-tenants`: list existing tenant schemas.
+//! - `chem-cli archive-flows <days> [--tenant <id>] [--out <dir>] [--execute]`:
+//!   report (dry-run, default) or export+delete completed flows older than
+//!   `<days>`.
+//! - `chem-cli verify-schema [<tenant_id>]`: compare the live schema with the
+//!   declared Diesel schema and expected constraints.
+//!
+//! Without arguments it runs the in-memory demo flow.
+
+use std::process::ExitCode;
 
 use chem_core::FlowEngine;
 use chem_core::{typed_artifact, typed_step};
+use chem_persistence::{
+    build_dev_pool_and_config_from_env, build_dev_pool_from_env, check_schema, list_tenants, migrate_tenant,
+    verify_tenant_schema, DirectorySink, FlowArchiver, PersistenceError, RetentionPolicy, TenantId,
+};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        None => {
+            run_demo();
+            Ok(())
+        }
+        Some("migrate-tenant") => match args.get(1) {
+            Some(id) => cmd_migrate_tenant(id),
+            None => Err(PersistenceError::Config("usage: chem-cli migrate-tenant <tenant_id>".into())),
+        },
+        Some("list-tenants") => cmd_list_tenants(),
+        Some("archive-flows") => cmd_archive_flows(&args[1..]),
+        Some("verify-schema") => cmd_verify_schema(args.get(1).map(String::as_str)),
+        Some(other) => Err(PersistenceError::Config(format!("unknown command: {other}"))),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("❌ {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn cmd_migrate_tenant(id: &str) -> Result<(), PersistenceError> {
+    let tenant = TenantId::new(id)?;
+    let pool = build_dev_pool_from_env()?;
+    migrate_tenant(&pool, &tenant)?;
+    println!("✅ Tenant '{}' migrated (schema {})", tenant, tenant.schema_name());
+    Ok(())
+}
+
+fn cmd_list_tenants() -> Result<(), PersistenceError> {
+    let pool = build_dev_pool_from_env()?;
+    for tenant in list_tenants(&pool)? {
+        println!("{}\t{}", tenant, tenant.schema_name());
+    }
+    Ok(())
+}
+
+fn cmd_archive_flows(args: &[String]) -> Result<(), PersistenceError> {
+    let usage =
+        || PersistenceError::Config("usage: chem-cli archive-flows <days> [--tenant <id>] [--out <dir>] [--execute]".into());
+    let days: u32 = args.first().and_then(|d| d.parse().ok()).ok_or_else(usage)?;
+    let mut tenant = None;
+    let mut out = None;
+    let mut execute = false;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--tenant" => tenant = Some(TenantId::new(rest.next().ok_or_else(usage)?)?),
+            "--out" => out = Some(rest.next().ok_or_else(usage)?.clone()),
+            "--execute" => execute = true,
+            _ => return Err(usage()),
+        }
+    }
+    let (pool, cfg) = build_dev_pool_and_config_from_env()?;
+    let mut archiver = FlowArchiver::from_config(pool, &cfg);
+    if let Some(t) = tenant {
+        archiver = archiver.with_tenant(t);
+    }
+    let policy = RetentionPolicy::older_than_days(days);
+    let report = if execute {
+        let mut sink =
+            DirectorySink::new(out.ok_or_else(|| PersistenceError::Config("--execute requires --out <dir>".into()))?)?;
+        archiver.archive(&policy, &mut sink)?
+    } else {
+        archiver.plan(&policy)?
+    };
+    println!("{}", serde_json::to_string_pretty(&report).expect("serialize report"));
+    Ok(())
+}
+
+fn cmd_verify_schema(tenant: Option<&str>) -> Result<(), PersistenceError> {
+    let pool = build_dev_pool_from_env()?;
+    let mut conn = pool.get().map_err(|e| PersistenceError::TransientIo(e.to_string()))?;
+    match tenant {
+        Some(id) => verify_tenant_schema(&mut conn, &TenantId::new(id)?)?,
+        None => {
+            let drifts = check_schema(&mut conn)?;
+            if !drifts.is_empty() {
+                println!("{}", serde_json::to_string_pretty(&drifts).expect("serialize drifts"));
+                return Err(PersistenceError::SchemaDrift(drifts));
+            }
+        }
+    }
+    println!("✅ Schema matches declarations");
+    Ok(())
+}
 
-fn main() {
+fn run_demo() {
     println!("🚀 ChemFlow CLI");
     println!("===============");
 
@@ -33,6 +33,13 @@ pub enum CoreEngineError {
     StorageError(String),
     #[error("internal: {0}")]
     Internal(String),
+    /// Entrada inválida para el step (p. ej. una estructura que no se puede
+    /// interpretar): reintentar no cambia el resultado.
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    /// Fallo transitorio del entorno de ejecución; el step puede reintentarse.
+    #[error("transient: {0}")]
+    Transient(String),
 }
 
 /// Clasificación de errores para persistencia extendida (F8)
@@ -44,11 +51,20 @@ pub enum ErrorClass {
     Runtime,
 }
 
+impl ErrorClass {
+    /// Sólo los errores transitorios justifican reintentar el step.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, ErrorClass::Transient)
+    }
+}
+
 /// Clasifica un error del engine en una categoría estable para políticas de
 /// retry y auditoría.
 pub fn classify_error(error: &CoreEngineError) -> ErrorClass {
     match error {
         CoreEngineError::Internal(_) | CoreEngineError::StorageError(_) => ErrorClass::Runtime,
+        CoreEngineError::InvalidInput(_) => ErrorClass::Permanent,
+        CoreEngineError::Transient(_) => ErrorClass::Transient,
         CoreEngineError::InvalidStepIndex
         | CoreEngineError::MissingInputs
         | CoreEngineError::FirstStepMustBeSource
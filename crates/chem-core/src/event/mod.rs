@@ -18,4 +18,4 @@ mod store;
 mod types;
 
 pub use store::{EventStore, InMemoryEventStore};
-pub use types::{FlowEvent, FlowEventKind};
+pub use types::{EventArtifact, FlowEvent, FlowEventKind};
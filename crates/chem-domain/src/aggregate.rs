@@ -0,0 +1,420 @@
+// aggregate.rs
+//
+// Agregados de familia (FamilyAggregate): estadísticos calculados a partir
+// de las propiedades moleculares preferidas de los miembros. El resultado
+// (`FamilyAggregate`, empaquetable como `FamilyProperty`) registra en su
+// `AggregateProvenance` el método, la propiedad de origen, las unidades
+// canónicas y el `aggregate_hash`, que depende solo de (family_hash,
+// propiedad, método, unidades) – invariante INV5 de los requerimientos.
+use crate::{DomainError, FamilyProperty, MoleculeFamily, OwnedMolecularProperty, Unit};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+
+/// Método de agregación y sus parámetros.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum AggregateMethod {
+    Mean,
+    Median,
+    Min,
+    Max,
+    /// Desviación estándar poblacional (ddof = 0).
+    Std,
+    /// Percentil `p` en [0, 100] con interpolación lineal.
+    Percentile {
+        p: f64,
+    },
+    /// Número de valores estrictamente mayores que `threshold`.
+    CountAbove {
+        threshold: f64,
+    },
+    /// Número de valores estrictamente menores que `threshold`.
+    CountBelow {
+        threshold: f64,
+    },
+    /// Media ponderada por otra propiedad molecular (`weight_property`).
+    WeightedMean {
+        weight_property: String,
+    },
+}
+
+impl AggregateMethod {
+    /// Nombre estable del método (p. ej. `"percentile"`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            AggregateMethod::Mean => "mean",
+            AggregateMethod::Median => "median",
+            AggregateMethod::Min => "min",
+            AggregateMethod::Max => "max",
+            AggregateMethod::Std => "std",
+            AggregateMethod::Percentile { .. } => "percentile",
+            AggregateMethod::CountAbove { .. } => "count_above",
+            AggregateMethod::CountBelow { .. } => "count_below",
+            AggregateMethod::WeightedMean { .. } => "weighted_mean",
+        }
+    }
+
+    /// Indica si el resultado es un conteo (adimensional) en lugar de un
+    /// valor en las unidades de la propiedad.
+    pub fn is_count(&self) -> bool {
+        matches!(self, AggregateMethod::CountAbove { .. } | AggregateMethod::CountBelow { .. })
+    }
+
+    /// Aplica el método a `values`. `weights` (alineado con `values`) solo
+    /// se usa en `WeightedMean`, donde es obligatorio.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si no hay valores, hay valores no
+    /// finitos, el percentil está fuera de rango o los pesos son inválidos.
+    pub fn apply(&self, values: &[f64], weights: Option<&[f64]>) -> Result<f64, DomainError> {
+        if values.is_empty() {
+            return Err(DomainError::ValidationError(format!("Agregado '{}' sin valores", self.name())));
+        }
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(DomainError::ValidationError(format!("Agregado '{}' con valores no finitos", self.name())));
+        }
+        let n = values.len() as f64;
+        let mean = values.iter().sum::<f64>() / n;
+        Ok(match self {
+            AggregateMethod::Mean => mean,
+            AggregateMethod::Median => percentile(values, 50.0),
+            AggregateMethod::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            AggregateMethod::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            AggregateMethod::Std => (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt(),
+            AggregateMethod::Percentile { p } => {
+                if !(0.0..=100.0).contains(p) {
+                    return Err(DomainError::ValidationError(format!("Percentil fuera de rango: {p}")));
+                }
+                percentile(values, *p)
+            }
+            AggregateMethod::CountAbove { threshold } => values.iter().filter(|v| *v > threshold).count() as f64,
+            AggregateMethod::CountBelow { threshold } => values.iter().filter(|v| *v < threshold).count() as f64,
+            AggregateMethod::WeightedMean { weight_property } => {
+                let weights = weights.filter(|w| w.len() == values.len()).ok_or_else(|| {
+                                  DomainError::ValidationError(format!("Faltan pesos '{weight_property}' para la media ponderada"))
+                              })?;
+                let total: f64 = weights.iter().sum();
+                if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || total <= 0.0 {
+                    return Err(DomainError::ValidationError(format!("Pesos '{weight_property}' inválidos")));
+                }
+                values.iter().zip(weights).map(|(v, w)| v * w).sum::<f64>() / total
+            }
+        })
+    }
+}
+
+fn percentile(values: &[f64], p: f64) -> f64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (lo, hi) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64)
+}
+
+/// Especificación de un agregado: propiedad de origen y método.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateSpec {
+    pub property_type: String,
+    #[serde(flatten)]
+    pub method: AggregateMethod,
+}
+
+impl AggregateSpec {
+    /// Nombre del agregado resultante: `<método>(<propiedad>)`.
+    pub fn aggregate_name(&self) -> String {
+        format!("{}({})", self.method.name(), self.property_type)
+    }
+}
+
+/// Proveniencia registrada en el metadata del `FamilyProperty` agregado.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AggregateProvenance {
+    pub property_type: String,
+    pub method: AggregateMethod,
+    /// Unidades del resultado (canónicas; `None` en conteos o sin unidades).
+    pub units: Option<String>,
+    pub n_values: usize,
+    pub aggregate_hash: String,
+}
+
+/// Hash del agregado: depende solo de (family_hash, propiedad, método con
+/// parámetros, unidades canónicas).
+pub fn aggregate_hash(family_hash: &str, spec: &AggregateSpec, units: Option<&str>) -> String {
+    let canonical = serde_json::json!({
+        "family_hash": family_hash,
+        "property_type": spec.property_type,
+        "method": spec.method,
+        "units": units,
+    });
+    format!("{:x}", Sha256::digest(canonical.to_string().as_bytes()))
+}
+
+/// Normaliza valores con unidades opcionales a la unidad canónica común.
+/// Todos deben compartir dimensión; no se mezclan valores con y sin
+/// unidades.
+pub fn canonical_values(values: &[(f64, Option<&str>)]) -> Result<(Vec<f64>, Option<&'static str>), DomainError> {
+    let mut common: Option<Option<Unit>> = None;
+    let mut out = Vec::with_capacity(values.len());
+    for (value, units) in values {
+        let unit = units.map(Unit::parse).transpose()?;
+        match (&common, unit) {
+            (None, _) => common = Some(unit),
+            (Some(Some(first)), Some(u)) if first.dimension() == u.dimension() => {}
+            (Some(None), None) => {}
+            (Some(first), _) => {
+                return Err(DomainError::ValidationError(format!("Unidades incompatibles en el agregado: {:?} vs {:?}",
+                                                                first.map(|u| u.symbol()),
+                                                                unit.map(|u| u.symbol()))));
+            }
+        }
+        out.push(unit.map_or(*value, |u| u.to_canonical(*value)));
+    }
+    Ok((out, common.flatten().map(|u| u.canonical().symbol())))
+}
+
+/// Agregado calculado por `FamilyAggregator`: nombre (`<método>(<propiedad>)`),
+/// valor y proveniencia.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FamilyAggregate {
+    pub name: String,
+    pub value: f64,
+    pub provenance: AggregateProvenance,
+}
+
+impl FamilyAggregate {
+    /// Empaqueta el agregado como `FamilyProperty` de `family`, con
+    /// `AggregateProvenance` como metadata.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si el agregado no se calculó sobre
+    /// `family` (su `aggregate_hash` no corresponde a ese `family_hash`).
+    pub fn to_family_property<'a>(&self,
+                                  family: &'a MoleculeFamily)
+                                  -> Result<FamilyProperty<'a, f64, AggregateProvenance>, DomainError> {
+        let spec = AggregateSpec { property_type: self.provenance.property_type.clone(),
+                                   method: self.provenance.method.clone() };
+        if aggregate_hash(family.family_hash(), &spec, self.provenance.units.as_deref()) != self.provenance.aggregate_hash {
+            return Err(DomainError::ValidationError(format!("El agregado '{}' no pertenece a la familia {}",
+                                                            self.name,
+                                                            family.family_hash())));
+        }
+        FamilyProperty::new(family, &self.name, self.value, None, false, self.provenance.clone())
+    }
+}
+
+/// Motor de agregación sobre una familia y sus propiedades moleculares.
+///
+/// Para cada miembro se usa el valor preferido de la propiedad: el marcado
+/// `preferred`, o el único disponible. Varias propiedades sin preferida (o
+/// varias preferidas) para la misma molécula son ambiguas y producen error;
+/// los miembros sin valor se omiten (ver `AggregateProvenance::n_values`).
+#[derive(Debug, Clone)]
+pub struct FamilyAggregator<'a> {
+    family_hash: &'a str,
+    members: Vec<&'a str>,
+}
+
+impl<'a> FamilyAggregator<'a> {
+    pub fn new(family: &'a MoleculeFamily) -> Self {
+        Self::for_members(family.family_hash(), family.molecules().iter().map(|m| m.inchikey()))
+    }
+
+    /// Agregador sobre una familia de la que solo se conocen el hash y los
+    /// InChIKeys de sus miembros, en orden (p. ej. un artifact de
+    /// propiedades sin estructuras).
+    pub fn for_members<I>(family_hash: &'a str, members: I) -> Self
+        where I: IntoIterator<Item = &'a str>
+    {
+        Self { family_hash,
+               members: members.into_iter().collect() }
+    }
+
+    /// Valores preferidos de `property_type` por InChIKey de miembro.
+    fn preferred_values<'p, M>(&self,
+                               properties: &'p [OwnedMolecularProperty<f64, M>],
+                               property_type: &str)
+                               -> Result<HashMap<&'a str, &'p OwnedMolecularProperty<f64, M>>, DomainError>
+        where M: Serialize + Clone
+    {
+        let mut by_key: HashMap<&str, Vec<&OwnedMolecularProperty<f64, M>>> = HashMap::new();
+        // Pertenencia por InChIKey exacto: los valores se buscan luego por el
+        // InChIKey de cada miembro, sea cual sea la política de identidad.
+        let members: HashSet<&str> = self.members.iter().copied().collect();
+        for p in properties.iter().filter(|p| p.property_type() == property_type) {
+            if !members.contains(p.molecule_inchikey()) {
+                return Err(DomainError::ValidationError(format!("Propiedad '{}' de una molécula ajena a la familia: {}",
+                                                                property_type,
+                                                                p.molecule_inchikey())));
+            }
+            by_key.entry(p.molecule_inchikey()).or_default().push(p);
+        }
+        let mut selected = HashMap::new();
+        for &member in &self.members {
+            let Some(candidates) = by_key.get(member) else {
+                continue;
+            };
+            let preferred: Vec<_> = candidates.iter().filter(|p| p.preferred()).collect();
+            let chosen = match (candidates.as_slice(), preferred.as_slice()) {
+                ([only], _) => *only,
+                (_, [p]) => **p,
+                _ => {
+                    return Err(DomainError::ValidationError(format!("Valor preferido ambiguo de '{}' para {}",
+                                                                    property_type, member)))
+                }
+            };
+            selected.insert(member, chosen);
+        }
+        Ok(selected)
+    }
+
+    /// Calcula un agregado (ver `FamilyAggregate::to_family_property` para
+    /// obtenerlo como `FamilyProperty`).
+    pub fn aggregate<M>(&self,
+                        properties: &[OwnedMolecularProperty<f64, M>],
+                        spec: &AggregateSpec)
+                        -> Result<FamilyAggregate, DomainError>
+        where M: Serialize + Clone
+    {
+        let values = self.preferred_values(properties, &spec.property_type)?;
+        let weights = match &spec.method {
+            AggregateMethod::WeightedMean { weight_property } => Some(self.preferred_values(properties, weight_property)?),
+            _ => None,
+        };
+        // Orden de la familia; con pesos, solo miembros que tienen ambos.
+        let members: Vec<&str> =
+            self.members
+                .iter()
+                .copied()
+                .filter(|k| values.contains_key(k) && weights.as_ref().is_none_or(|w| w.contains_key(k)))
+                .collect();
+        let raw: Vec<(f64, Option<&str>)> = members.iter().map(|k| (*values[k].value(), values[k].units())).collect();
+        let (canonical, units) = canonical_values(&raw)?;
+        let weight_values = match &weights {
+            Some(w) => {
+                let raw: Vec<(f64, Option<&str>)> = members.iter().map(|k| (*w[k].value(), w[k].units())).collect();
+                Some(canonical_values(&raw)?.0)
+            }
+            None => None,
+        };
+        let value = spec.method.apply(&canonical, weight_values.as_deref())?;
+        let units = if spec.method.is_count() { None } else { units };
+        let provenance = AggregateProvenance { property_type: spec.property_type.clone(),
+                                               method: spec.method.clone(),
+                                               units: units.map(str::to_string),
+                                               n_values: canonical.len(),
+                                               aggregate_hash: aggregate_hash(self.family_hash, spec, units) };
+        Ok(FamilyAggregate { name: spec.aggregate_name(),
+                             value,
+                             provenance })
+    }
+
+    /// Calcula varios agregados en el orden de `specs`.
+    pub fn aggregate_all<M>(&self,
+                            properties: &[OwnedMolecularProperty<f64, M>],
+                            specs: &[AggregateSpec])
+                            -> Result<Vec<FamilyAggregate>, DomainError>
+        where M: Serialize + Clone
+    {
+        specs.iter().map(|spec| self.aggregate(properties, spec)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Molecule, PropertyProvider};
+    use serde_json::json;
+
+    const KEYS: [&str; 3] = ["AAAAAAAAAAAAAA-AAAAAAAAAA-N",
+                             "BBBBBBBBBBBBBB-BBBBBBBBBB-N",
+                             "CCCCCCCCCCCCCC-CCCCCCCCCC-N"];
+
+    fn family() -> MoleculeFamily {
+        let molecules = KEYS.iter()
+                            .map(|k| Molecule::from_parts(k, "C", "InChI=1S/CH4/h1H4", json!({})).unwrap())
+                            .collect::<Vec<_>>();
+        MoleculeFamily::new(molecules, json!({"source": "test"})).unwrap()
+    }
+
+    fn prop(key: &str, kind: &str, value: f64, units: Option<&str>) -> OwnedMolecularProperty<f64, ()> {
+        OwnedMolecularProperty::new(key, kind, value, PropertyProvider::new("test", "1").unwrap(), ()).unwrap()
+                                                                                                      .with_units(units)
+                                                                                                      .unwrap()
+    }
+
+    #[test]
+    fn methods_compute_expected_statistics() {
+        let v = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(AggregateMethod::Mean.apply(&v, None).unwrap(), 2.5);
+        assert_eq!(AggregateMethod::Median.apply(&v, None).unwrap(), 2.5);
+        assert_eq!(AggregateMethod::Min.apply(&v, None).unwrap(), 1.0);
+        assert_eq!(AggregateMethod::Max.apply(&v, None).unwrap(), 4.0);
+        assert!((AggregateMethod::Std.apply(&v, None).unwrap() - 1.25f64.sqrt()).abs() < 1e-12);
+        assert_eq!(AggregateMethod::Percentile { p: 25.0 }.apply(&v, None).unwrap(), 1.75);
+        assert_eq!(AggregateMethod::CountAbove { threshold: 2.0 }.apply(&v, None).unwrap(), 2.0);
+        assert_eq!(AggregateMethod::CountBelow { threshold: 2.0 }.apply(&v, None).unwrap(), 1.0);
+        let weighted = AggregateMethod::WeightedMean { weight_property: "w".into() };
+        assert_eq!(weighted.apply(&[1.0, 3.0], Some(&[3.0, 1.0])).unwrap(), 1.5);
+        assert!(weighted.apply(&[1.0], None).is_err());
+        assert!(AggregateMethod::Mean.apply(&[], None).is_err());
+        assert!(AggregateMethod::Percentile { p: 120.0 }.apply(&v, None).is_err());
+    }
+
+    #[test]
+    fn aggregator_uses_preferred_values_and_canonical_units() {
+        let family = family();
+        let props = vec![prop(KEYS[0], "dG", -1.0, Some("kcal/mol")),
+                         prop(KEYS[1], "dG", -4.184, Some("kJ/mol")),
+                         prop(KEYS[1], "dG", 100.0, Some("kJ/mol")),
+                         prop(KEYS[1], "dG", -8.368, Some("kJ/mol")).with_preferred(true),
+                         prop(KEYS[0], "w", 1.0, None),
+                         prop(KEYS[1], "w", 3.0, None)];
+        let aggregator = FamilyAggregator::new(&family);
+
+        let mean = AggregateSpec { property_type: "dG".into(),
+                                   method: AggregateMethod::Mean };
+        let result = aggregator.aggregate(&props, &mean)
+                               .unwrap()
+                               .to_family_property(&family)
+                               .unwrap();
+        assert_eq!(result.property_type(), "mean(dG)");
+        assert!((result.value() - (-4.184 - 8.368) / 2.0).abs() < 1e-12);
+        assert_eq!(result.metadata().units.as_deref(), Some("kJ/mol"));
+        assert_eq!(result.metadata().n_values, 2);
+        assert_eq!(result.metadata().aggregate_hash,
+                   aggregate_hash(family.family_hash(), &mean, Some("kJ/mol")));
+
+        let weighted = AggregateSpec { property_type: "dG".into(),
+                                       method: AggregateMethod::WeightedMean { weight_property: "w".into() } };
+        let result = aggregator.aggregate(&props, &weighted).unwrap();
+        assert!((result.value - (-4.184 - 3.0 * 8.368) / 4.0).abs() < 1e-12);
+
+        let count = AggregateSpec { property_type: "dG".into(),
+                                    method: AggregateMethod::CountBelow { threshold: -5.0 } };
+        let result = aggregator.aggregate(&props, &count).unwrap();
+        assert_eq!((result.value, result.provenance.units.clone()), (1.0, None));
+        // Solo con el hash y los miembros se obtiene el mismo agregado, que
+        // no puede empaquetarse para otra familia.
+        let by_keys = FamilyAggregator::for_members(family.family_hash(), KEYS);
+        assert_eq!(by_keys.aggregate(&props, &count).unwrap(), result);
+        let other = MoleculeFamily::new(family.molecules()[..1].to_vec(), json!({})).unwrap();
+        assert!(result.to_family_property(&other).is_err());
+
+        // Dos valores sin preferido para la misma molécula: ambiguo.
+        let ambiguous = vec![prop(KEYS[2], "dG", 1.0, None), prop(KEYS[2], "dG", 2.0, None)];
+        assert!(aggregator.aggregate(&ambiguous, &mean).is_err());
+        // Dimensiones incompatibles.
+        let mixed = vec![prop(KEYS[0], "dG", 1.0, Some("kJ/mol")), prop(KEYS[1], "dG", 1.0, Some("Å²"))];
+        assert!(aggregator.aggregate(&mixed, &mean).is_err());
+    }
+
+    #[test]
+    fn spec_serializes_flat() {
+        let spec = AggregateSpec { property_type: "logP".into(),
+                                   method: AggregateMethod::Percentile { p: 90.0 } };
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json, json!({"property_type": "logP", "method": "percentile", "p": 90.0}));
+        assert_eq!(serde_json::from_value::<AggregateSpec>(json).unwrap(), spec);
+    }
+}
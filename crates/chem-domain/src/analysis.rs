@@ -0,0 +1,154 @@
+// analysis.rs
+//! Análisis de familias (Requerimientos §4, `AnalysisProvider`): resultados
+//! derivados de una familia que no son moléculas nuevas, p. ej. tablas para
+//! revisiones SAR.
+//!
+//! `ScaffoldAnalysis` agrupa la familia por scaffold de Bemis–Murcko y
+//! descompone cada molécula en R-grupos. El orden de las tablas es
+//! determinista: scaffolds por número de miembros (descendente) y SMILES;
+//! miembros y R-grupos en el orden de la familia.
+
+use std::collections::BTreeMap;
+
+#[cfg(feature = "rdkit")]
+use chemengine::RGroups;
+use serde::{Deserialize, Serialize};
+
+use crate::{DomainError, MoleculeFamily};
+
+/// Proveedor de análisis sobre una familia.
+pub trait AnalysisProvider {
+    type Output;
+
+    /// Nombre estable del proveedor.
+    fn name(&self) -> &str;
+
+    /// Versión del proveedor.
+    fn version(&self) -> &str;
+
+    fn analyze(&self, family: &MoleculeFamily) -> Result<Self::Output, DomainError>;
+}
+
+/// Scaffold con los InChIKeys de sus miembros. El scaffold de las moléculas
+/// acíclicas es la cadena vacía.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaffoldGroup {
+    pub scaffold: String,
+    pub members: Vec<String>,
+}
+
+/// R-grupos de una molécula (ver `RGroups`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MoleculeRGroups {
+    pub inchikey: String,
+    pub core: Option<String>,
+    pub rgroups: BTreeMap<String, String>,
+}
+
+/// Resultado de `ScaffoldAnalysis`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScaffoldTable {
+    /// Núcleos usados en la descomposición.
+    pub cores: Vec<String>,
+    pub scaffolds: Vec<ScaffoldGroup>,
+    pub rgroups: Vec<MoleculeRGroups>,
+}
+
+/// Agrupa `inchikeys` por su scaffold (`scaffolds[i]` es el de
+/// `inchikeys[i]`): grupos más poblados primero y, a igualdad, por SMILES.
+pub fn group_by_scaffold(inchikeys: &[&str], scaffolds: &[String]) -> Vec<ScaffoldGroup> {
+    let mut groups: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (inchikey, scaffold) in inchikeys.iter().zip(scaffolds) {
+        groups.entry(scaffold).or_default().push(inchikey.to_string());
+    }
+    let mut groups: Vec<ScaffoldGroup> = groups.into_iter()
+                                               .map(|(scaffold, members)| ScaffoldGroup { scaffold: scaffold.to_string(),
+                                                                                          members })
+                                               .collect();
+    groups.sort_by(|a, b| {
+              b.members
+               .len()
+               .cmp(&a.members.len())
+               .then_with(|| a.scaffold.cmp(&b.scaffold))
+          });
+    groups
+}
+
+/// Scaffolds de Bemis–Murcko y descomposición en R-grupos. Sin `cores`
+/// explícitos se usan los scaffolds no vacíos de la familia, en el orden de
+/// la tabla.
+#[cfg(feature = "rdkit")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScaffoldAnalysis {
+    pub cores: Vec<String>,
+}
+
+#[cfg(feature = "rdkit")]
+impl ScaffoldAnalysis {
+    pub const NAME: &'static str = "rdkit_scaffolds";
+    pub const VERSION: &'static str = "1";
+
+    pub fn new(cores: Vec<String>) -> Self {
+        Self { cores }
+    }
+}
+
+#[cfg(feature = "rdkit")]
+impl AnalysisProvider for ScaffoldAnalysis {
+    type Output = ScaffoldTable;
+
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn version(&self) -> &str {
+        Self::VERSION
+    }
+
+    fn analyze(&self, family: &MoleculeFamily) -> Result<ScaffoldTable, DomainError> {
+        let inchikeys: Vec<&str> = family.molecules().iter().map(|m| m.inchikey()).collect();
+        let scaffolds = group_by_scaffold(&inchikeys, &family.murcko_scaffolds()?);
+        let cores: Vec<String> = if self.cores.is_empty() {
+            scaffolds.iter()
+                     .filter(|g| !g.scaffold.is_empty())
+                     .map(|g| g.scaffold.clone())
+                     .collect()
+        } else {
+            self.cores.clone()
+        };
+        let rgroups = if cores.is_empty() {
+            vec![RGroups::default(); inchikeys.len()]
+        } else {
+            family.rgroup_decomposition(&cores.iter().map(String::as_str).collect::<Vec<_>>())?
+        };
+        let rgroups = inchikeys.iter()
+                               .zip(rgroups)
+                               .map(|(inchikey, r)| MoleculeRGroups { inchikey: inchikey.to_string(),
+                                                                      core: r.core,
+                                                                      rgroups: r.rgroups })
+                               .collect();
+        Ok(ScaffoldTable { cores,
+                           scaffolds,
+                           rgroups })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scaffold_groups_are_ordered() {
+        let scaffolds = ["c1ccccc1", "", "c1ccncc1", "c1ccccc1", "c1ccncc1", "C1CC1"].map(String::from);
+        let groups = group_by_scaffold(&["A", "B", "C", "D", "E", "F"], &scaffolds);
+        let order: Vec<(&str, Vec<&str>)> =
+            groups.iter()
+                  .map(|g| (g.scaffold.as_str(), g.members.iter().map(String::as_str).collect()))
+                  .collect();
+        assert_eq!(order,
+                   vec![("c1ccccc1", vec!["A", "D"]),
+                        ("c1ccncc1", vec!["C", "E"]),
+                        ("", vec!["B"]),
+                        ("C1CC1", vec!["F"])]);
+    }
+}
@@ -0,0 +1,295 @@
+// backend.rs
+//! Backends moleculares: quién interpreta los SMILES y calcula los
+//! identificadores de `Molecule`.
+//!
+//! - `RdkitBackend`: RDKit embebido vía pyo3 (InChI/InChIKey estándar; feature
+//!   `rdkit`).
+//! - `RustBackend`: lector en Rust puro (`smiles`, sobre `purr`), sin Python.
+//!   No canonicaliza: sus claves dependen de cómo se escribió el SMILES y
+//!   tienen formato de InChIKey no estándar (indicador `N`), así que nunca
+//!   coinciden con las de RDKit.
+//!
+//! El backend se elige explícitamente la primera vez que se usa, con
+//! `set_backend` o con la variable de entorno `CHEMFLOW_MOLECULE_BACKEND`
+//! (`rdkit` o `rust`; por defecto `rdkit`). No hay sustitución automática:
+//! si RDKit no arranca, cada uso devuelve el error en lugar de pasar a Rust,
+//! porque eso cambiaría todos los InChIKeys. El resto de operaciones
+//! (descriptores, fingerprints, estandarización, conformeros…) siguen
+//! necesitando RDKit. Sin la feature `rdkit` pedir `rdkit` (también por
+//! defecto) es `EngineUnavailable`: hay que elegir `rust`.
+
+use std::sync::OnceLock;
+
+use chemengine::BatchItemError;
+
+#[cfg(feature = "rdkit")]
+use crate::molecule::engine;
+use crate::molecule::incomplete_response;
+use crate::smiles::{self, RUST_IDENTIFIER_PREFIX};
+use crate::DomainError;
+
+/// Variable de entorno que selecciona el backend.
+pub const BACKEND_ENV: &str = "CHEMFLOW_MOLECULE_BACKEND";
+
+/// Interpretación de SMILES e identidad canónica de las moléculas.
+pub trait MoleculeBackend: Send + Sync {
+    /// Nombre estable del backend (`rdkit`, `rust`).
+    fn name(&self) -> &'static str;
+
+    /// Interpreta un lote de SMILES. La posición `i` del resultado
+    /// corresponde a `smiles[i]`; los inválidos son `Err` sin abortar el
+    /// lote.
+    fn parse_smiles_batch(&self, smiles: &[&str]) -> Result<Vec<Result<chemengine::Molecule, BatchItemError>>, DomainError>;
+
+    /// Interpreta un único SMILES.
+    fn parse_smiles(&self, smiles: &str) -> Result<chemengine::Molecule, DomainError> {
+        self.parse_smiles_batch(&[smiles])?
+            .pop()
+            .ok_or_else(incomplete_response)?
+            .map_err(DomainError::from)
+    }
+
+    /// Recalcula la clave a partir de cada identificador del backend (InChI
+    /// o `RUST_IDENTIFIER_PREFIX`), con errores por ítem como
+    /// `parse_smiles_batch`.
+    fn inchikeys_from_identifiers(&self, identifiers: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, DomainError>;
+}
+
+/// Backend RDKit (Python embebido).
+#[cfg(feature = "rdkit")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RdkitBackend;
+
+#[cfg(feature = "rdkit")]
+impl MoleculeBackend for RdkitBackend {
+    fn name(&self) -> &'static str {
+        "rdkit"
+    }
+
+    fn parse_smiles_batch(&self, smiles: &[&str]) -> Result<Vec<Result<chemengine::Molecule, BatchItemError>>, DomainError> {
+        Ok(engine()?.get_molecules(smiles)?)
+    }
+
+    fn parse_smiles(&self, smiles: &str) -> Result<chemengine::Molecule, DomainError> {
+        Ok(engine()?.get_molecule(smiles)?)
+    }
+
+    fn inchikeys_from_identifiers(&self, identifiers: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, DomainError> {
+        Ok(engine()?.inchikeys_from_inchi(identifiers)?)
+    }
+}
+
+/// Backend en Rust puro: conserva el SMILES de entrada (no canónico), clave
+/// no estándar con formato de InChIKey y `RUST_IDENTIFIER_PREFIX` en lugar
+/// del InChI.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RustBackend;
+
+impl RustBackend {
+    fn molecule(smiles: &str) -> Result<chemengine::Molecule, smiles::SmilesError> {
+        let parsed = smiles::parse(smiles)?;
+        Ok(chemengine::Molecule { inchikey: parsed.identity_key(),
+                                  inchi: format!("{RUST_IDENTIFIER_PREFIX}{}/{}", parsed.formula(), parsed.smiles()),
+                                  smiles: parsed.smiles().to_string(),
+                                  num_atoms: parsed.num_atoms() as u32,
+                                  mol_weight: parsed.mol_weight(),
+                                  mol_formula: parsed.formula().to_string() })
+    }
+}
+
+impl MoleculeBackend for RustBackend {
+    fn name(&self) -> &'static str {
+        "rust"
+    }
+
+    fn parse_smiles_batch(&self, smiles: &[&str]) -> Result<Vec<Result<chemengine::Molecule, BatchItemError>>, DomainError> {
+        Ok(smiles.iter()
+                 .enumerate()
+                 .map(|(index, s)| {
+                     Self::molecule(s).map_err(|e| BatchItemError { index,
+                                                                    smiles: s.to_string(),
+                                                                    kind: e.kind(),
+                                                                    message: e.message })
+                 })
+                 .collect())
+    }
+
+    /// El identificador lleva el SMILES de entrada: se vuelve a interpretar y
+    /// debe reproducir el identificador exacto (fórmula incluida).
+    fn inchikeys_from_identifiers(&self, identifiers: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, DomainError> {
+        Ok(identifiers.iter()
+                      .enumerate()
+                      .map(|(index, identifier)| {
+                          let error = |message: String| BatchItemError { index,
+                                                                         smiles: identifier.to_string(),
+                                                                         kind: chemengine::ItemErrorKind::Other,
+                                                                         message };
+                          let smiles = identifier.strip_prefix(RUST_IDENTIFIER_PREFIX)
+                                                 .and_then(|rest| rest.split_once('/'))
+                                                 .map(|(_, smiles)| smiles)
+                                                 .ok_or_else(|| error("Identificador no reconocido".to_string()))?;
+                          let molecule = Self::molecule(smiles).map_err(|e| error(e.message))?;
+                          if molecule.inchi != *identifier {
+                              return Err(error(format!("El identificador no coincide con su SMILES (se esperaba '{}')",
+                                                       molecule.inchi)));
+                          }
+                          Ok(molecule.inchikey)
+                      })
+                      .collect())
+    }
+}
+
+/// Backend solicitado.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackendKind {
+    #[default]
+    Rdkit,
+    Rust,
+}
+
+impl BackendKind {
+    pub fn parse(name: &str) -> Result<Self, DomainError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "rdkit" => Ok(BackendKind::Rdkit),
+            "rust" => Ok(BackendKind::Rust),
+            other => Err(DomainError::ValidationError(format!("Backend molecular desconocido '{other}' (rdkit, rust)"))),
+        }
+    }
+
+    /// Nombre estable (`rdkit`, `rust`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            BackendKind::Rdkit => "rdkit",
+            BackendKind::Rust => "rust",
+        }
+    }
+
+    /// Backend indicado en `CHEMFLOW_MOLECULE_BACKEND` (`Rdkit` si no está).
+    pub fn from_env() -> Result<Self, DomainError> {
+        std::env::var(BACKEND_ENV).map_or(Ok(BackendKind::Rdkit), |v| Self::parse(&v))
+    }
+}
+
+/// Backend elegido; se fija en el primer uso.
+static SELECTED: OnceLock<BackendKind> = OnceLock::new();
+
+/// Fija el backend del proceso. Debe llamarse antes del primer uso; falla
+/// si ya se eligió otro (las identidades de ambos no son comparables) o si
+/// se pidió RDKit y el motor no arranca.
+pub fn set_backend(kind: BackendKind) -> Result<&'static dyn MoleculeBackend, DomainError> {
+    let selected = *SELECTED.get_or_init(|| kind);
+    if selected != kind {
+        return Err(DomainError::ValidationError(format!("El backend molecular ya está fijado a '{}'", selected.name())));
+    }
+    checked(selected)
+}
+
+/// Backend activo, eligiéndolo desde el entorno si aún no se fijó.
+pub fn molecule_backend() -> Result<&'static dyn MoleculeBackend, DomainError> {
+    match SELECTED.get() {
+        Some(kind) => checked(*kind),
+        None => set_backend(BackendKind::from_env()?),
+    }
+}
+
+/// Backend RDKit, si el motor se inicializa.
+#[cfg(feature = "rdkit")]
+fn rdkit_backend() -> Result<&'static dyn MoleculeBackend, DomainError> {
+    engine()?;
+    Ok(&RdkitBackend)
+}
+
+/// Sin la feature `rdkit` el backend RDKit nunca está disponible.
+#[cfg(not(feature = "rdkit"))]
+fn rdkit_backend() -> Result<&'static dyn MoleculeBackend, DomainError> {
+    Err(DomainError::EngineUnavailable("chem-domain compilado sin la feature `rdkit`".to_string()))
+}
+
+/// Backend capaz de verificar `identifier`: el de Rust para sus propios
+/// identificadores y RDKit para los InChI, sea cual sea el backend activo.
+pub(crate) fn identity_backend(identifier: &str) -> Result<&'static dyn MoleculeBackend, DomainError> {
+    if identifier.starts_with(RUST_IDENTIFIER_PREFIX) {
+        checked(BackendKind::Rust)
+    } else {
+        checked(BackendKind::Rdkit)
+    }
+}
+
+/// Instancia del backend, comprobando que RDKit esté disponible si se pidió.
+fn checked(kind: BackendKind) -> Result<&'static dyn MoleculeBackend, DomainError> {
+    match kind {
+        BackendKind::Rust => Ok(&RustBackend),
+        BackendKind::Rdkit => rdkit_backend(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rust_backend_builds_valid_molecules() -> Result<(), DomainError> {
+        let backend = RustBackend;
+        let ethanol = backend.parse_smiles("OCC")?;
+        assert_eq!(ethanol.smiles, "OCC");
+        assert_eq!(ethanol.inchi, format!("{RUST_IDENTIFIER_PREFIX}C2H6O/OCC"));
+        // Sin canonicalización: la misma molécula escrita de otra forma es otra clave
+        assert_ne!(ethanol.inchikey, backend.parse_smiles("CCO")?.inchikey);
+        assert_eq!(ethanol.num_atoms, 3);
+        let molecule =
+            crate::Molecule::from_parts(&ethanol.inchikey, &ethanol.smiles, &ethanol.inchi, serde_json::json!({}))?;
+        assert_eq!(molecule.inchikey(), ethanol.inchikey);
+
+        let batch = backend.parse_smiles_batch(&["CCO", "C1CC", "c1ccccc1"])?;
+        assert!(batch[0].is_ok() && batch[2].is_ok());
+        let err = batch[1].as_ref().unwrap_err();
+        assert_eq!((err.index, err.smiles.as_str()), (1, "C1CC"));
+        assert_eq!(err.kind, chemengine::ItemErrorKind::InvalidSmiles { position: Some(1) });
+        assert!(matches!(backend.parse_smiles("CC(C)(C)(C)(C)C"),
+                         Err(DomainError::SanitizationError { issue: chemengine::SanitizationIssue::Valence,
+                                                              .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn rust_identifiers_round_trip() -> Result<(), DomainError> {
+        let backend = RustBackend;
+        let ethanol = backend.parse_smiles("CCO")?;
+        let keys = backend.inchikeys_from_identifiers(&[&ethanol.inchi,
+                                                        &format!("{RUST_IDENTIFIER_PREFIX}C2H6/CCO"),
+                                                        "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3"])?;
+        assert_eq!(keys[0].as_deref(), Ok(ethanol.inchikey.as_str()));
+        assert!(keys[1].is_err() && keys[2].is_err());
+        Ok(())
+    }
+
+    /// Backend que no devuelve resultado por ítem.
+    struct Truncating;
+
+    impl MoleculeBackend for Truncating {
+        fn name(&self) -> &'static str {
+            "truncating"
+        }
+        fn parse_smiles_batch(&self, _: &[&str]) -> Result<Vec<Result<chemengine::Molecule, BatchItemError>>, DomainError> {
+            Ok(Vec::new())
+        }
+        fn inchikeys_from_identifiers(&self, _: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, DomainError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn incomplete_batches_are_errors() {
+        assert!(matches!(Truncating.parse_smiles("CCO"), Err(DomainError::ExternalError(_))));
+    }
+
+    #[test]
+    fn backend_kind_parsing() {
+        assert_eq!(BackendKind::parse("RUST").unwrap(), BackendKind::Rust);
+        assert_eq!(BackendKind::default(), BackendKind::Rdkit);
+        // Sin sustitución automática: hay que nombrar el backend
+        for name in ["", "auto", "openbabel"] {
+            assert!(BackendKind::parse(name).is_err(), "{name}");
+        }
+    }
+}
@@ -0,0 +1,80 @@
+// descriptors.rs
+use crate::{DomainError, MolecularProperty, Molecule};
+use chemengine::Descriptors;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Proveedor registrado en los metadatos de los descriptores calculados.
+pub const DESCRIPTOR_PROVIDER: &str = "rdkit";
+
+/// Metadatos de una propiedad calculada como descriptor RDKit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DescriptorMetadata {
+    pub provider: String,
+    pub descriptor: String,
+    pub unit: Option<String>,
+}
+
+/// Descriptores de una molécula expresados como `MolecularProperty`, junto
+/// con los errores individuales de los descriptores que no pudieron
+/// calcularse.
+#[derive(Debug, Clone)]
+pub struct MolecularDescriptors<'a> {
+    pub properties: Vec<MolecularProperty<'a, f64, DescriptorMetadata>>,
+    pub errors: BTreeMap<String, String>,
+}
+
+impl<'a> MolecularDescriptors<'a> {
+    /// Convierte el resultado del motor en propiedades de la molécula (una
+    /// por descriptor con valor, en orden estable).
+    pub fn from_engine(molecule: &'a Molecule, descriptors: &Descriptors) -> Result<Self, DomainError> {
+        let properties = descriptors.values()
+                                    .into_iter()
+                                    .map(|(d, value)| {
+                                        MolecularProperty::new(molecule,
+                                                               d.name(),
+                                                               value,
+                                                               None,
+                                                               false,
+                                                               DescriptorMetadata { provider:
+                                                                                        DESCRIPTOR_PROVIDER.to_string(),
+                                                                                    descriptor: d.name().to_string(),
+                                                                                    unit: d.unit().map(str::to_string) })
+                                    })
+                                    .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { properties,
+                  errors: descriptors.errors.clone() })
+    }
+
+    /// Propiedad de un descriptor por nombre (p.ej. "tpsa").
+    pub fn get(&self, name: &str) -> Option<&MolecularProperty<'a, f64, DescriptorMetadata>> {
+        self.properties.iter().find(|p| p.property_type() == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn engine_descriptors_become_molecular_properties() {
+        let molecule = Molecule::from_parts("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                                            "CCO",
+                                            "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
+                                            serde_json::json!({})).unwrap();
+        let mut descriptors = Descriptors { smiles: "CCO".into(),
+                                            tpsa: Some(20.23),
+                                            hbd: Some(1),
+                                            ..Default::default() };
+        descriptors.errors.insert("qed".into(), "ValueError: boom".into());
+
+        let props = MolecularDescriptors::from_engine(&molecule, &descriptors).unwrap();
+        assert_eq!(props.properties.len(), 2);
+        let tpsa = props.get("tpsa").unwrap();
+        assert_eq!(*tpsa.value(), 20.23);
+        assert_eq!(tpsa.metadata().unit.as_deref(), Some("Å²"));
+        assert_eq!(tpsa.molecule().inchikey(), "LFQSCWFLJHTTHZ-UHFFFAOYSA-N");
+        assert_eq!(*props.get("hbd").unwrap().value(), 1.0);
+        assert_eq!(props.errors.get("qed").map(String::as_str), Some("ValueError: boom"));
+    }
+}
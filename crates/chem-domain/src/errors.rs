@@ -1,5 +1,7 @@
 // errors.rs
+#[cfg(feature = "rdkit")]
 use chemengine::EngineError;
+use chemengine::{BatchItemError, ItemErrorKind, SanitizationIssue};
 use thiserror::Error;
 
 /// Error personalizado del dominio para la aplicación química
@@ -13,12 +15,93 @@ pub enum DomainError {
 
     #[error("Error de serialización: {0}")]
     SerializationError(String),
+
+    /// SMILES sintácticamente inválido (`position` en bytes desde 0).
+    #[error("SMILES inválido '{smiles}'{}: {message}", position_suffix(.position))]
+    InvalidSmiles {
+        smiles: String,
+        position: Option<usize>,
+        message: String,
+    },
+
+    /// La estructura se lee pero no supera la sanitización.
+    #[error("Sanitización fallida de '{smiles}' ({issue}): {message}")]
+    SanitizationError {
+        smiles: String,
+        issue: SanitizationIssue,
+        message: String,
+    },
+
+    /// El motor químico no se pudo inicializar (p. ej. falta RDKit).
+    #[error("Motor químico no disponible: {0}")]
+    EngineUnavailable(String),
+
+    /// Fallo del intérprete de Python durante una llamada.
+    #[error("Fallo del intérprete: {0}")]
+    InterpreterError(String),
+
+    /// Las versiones del toolkit registradas no coinciden con las actuales.
+    #[error("Cambio de entorno químico: {0}")]
+    EnvironmentDrift(String),
+
+    /// InChIKey, InChI y SMILES de una molécula no describen el mismo
+    /// compuesto (ver `Molecule::verify_identity`).
+    #[error("Identidad inconsistente de {inchikey}: {message}")]
+    IdentityMismatch { inchikey: String, message: String },
+}
+
+fn position_suffix(position: &Option<usize>) -> String {
+    position.map(|p| format!(" (posición {p})")).unwrap_or_default()
+}
+
+impl DomainError {
+    /// Error de la entrada química: reintentar no cambia el resultado.
+    pub fn is_permanent(&self) -> bool {
+        matches!(self,
+                 DomainError::InvalidSmiles { .. }
+                 | DomainError::SanitizationError { .. }
+                 | DomainError::ValidationError(_)
+                 | DomainError::IdentityMismatch { .. })
+    }
+
+    /// Fallo en tiempo de ejecución que puede resolverse reintentando.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DomainError::InterpreterError(_))
+    }
 }
 
 // Implementación de conversión desde EngineError a DomainError
+#[cfg(feature = "rdkit")]
 impl From<EngineError> for DomainError {
     fn from(e: EngineError) -> Self {
-        DomainError::ExternalError(e.to_string())
+        match e {
+            EngineError::InvalidSmiles { smiles,
+                                         position,
+                                         message, } => DomainError::InvalidSmiles { smiles,
+                                                                                    position,
+                                                                                    message },
+            EngineError::Sanitization { smiles, issue, message } => {
+                DomainError::SanitizationError { smiles, issue, message }
+            }
+            EngineError::MissingModule { .. } | EngineError::Init(_) => DomainError::EngineUnavailable(e.to_string()),
+            EngineError::Interpreter(_) => DomainError::InterpreterError(e.to_string()),
+            e => DomainError::ExternalError(e.to_string()),
+        }
+    }
+}
+
+// Error de un ítem de lote: tipado si es químico, externo en otro caso
+impl From<BatchItemError> for DomainError {
+    fn from(e: BatchItemError) -> Self {
+        match e.kind {
+            ItemErrorKind::InvalidSmiles { position } => DomainError::InvalidSmiles { smiles: e.smiles,
+                                                                                      position,
+                                                                                      message: e.message },
+            ItemErrorKind::Sanitization(issue) => DomainError::SanitizationError { smiles: e.smiles,
+                                                                                   issue,
+                                                                                   message: e.message },
+            ItemErrorKind::Other => DomainError::ExternalError(e.to_string()),
+        }
     }
 }
 
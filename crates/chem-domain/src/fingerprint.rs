@@ -0,0 +1,219 @@
+// fingerprint.rs
+use crate::DomainError;
+use chemengine::FingerprintKind;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Métrica de similitud entre fingerprints binarios.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Similarity {
+    /// |A∩B| / |A∪B|
+    Tanimoto,
+    /// 2|A∩B| / (|A|+|B|)
+    Dice,
+}
+
+/// Fingerprint binario compacto (bitset en palabras de 64 bits) junto con el
+/// tipo/parámetros que lo generaron. Solo fingerprints del mismo
+/// `FingerprintKind` son comparables.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    kind: FingerprintKind,
+    n_bits: u32,
+    words: Vec<u64>,
+}
+
+impl Fingerprint {
+    /// Construye el bitset a partir de los índices de bits encendidos.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si `n_bits` es 0 o algún índice queda
+    /// fuera de rango.
+    pub fn from_on_bits<I>(kind: FingerprintKind, n_bits: u32, on_bits: I) -> Result<Self, DomainError>
+        where I: IntoIterator<Item = u32>
+    {
+        if n_bits == 0 {
+            return Err(DomainError::ValidationError("Un fingerprint debe tener al menos un bit".to_string()));
+        }
+        let mut words = vec![0u64; n_bits.div_ceil(64) as usize];
+        for bit in on_bits {
+            if bit >= n_bits {
+                return Err(DomainError::ValidationError(format!("Bit {bit} fuera de rango (n_bits = {n_bits})")));
+            }
+            words[(bit / 64) as usize] |= 1u64 << (bit % 64);
+        }
+        Ok(Self { kind, n_bits, words })
+    }
+
+    /// Convierte el fingerprint crudo del motor.
+    pub fn from_engine(kind: FingerprintKind, bits: chemengine::FingerprintBits) -> Result<Self, DomainError> {
+        Self::from_on_bits(kind, bits.n_bits, bits.on_bits)
+    }
+
+    /// Tipo y parámetros del fingerprint
+    pub fn kind(&self) -> &FingerprintKind {
+        &self.kind
+    }
+
+    /// Longitud del vector de bits
+    pub fn n_bits(&self) -> u32 {
+        self.n_bits
+    }
+
+    /// Cantidad de bits encendidos
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|w| w.count_ones()).sum()
+    }
+
+    /// Indica si el bit está encendido
+    pub fn contains(&self, bit: u32) -> bool {
+        bit < self.n_bits && self.words[(bit / 64) as usize] & (1u64 << (bit % 64)) != 0
+    }
+
+    /// Índices de los bits encendidos, en orden ascendente
+    pub fn on_bits(&self) -> Vec<u32> {
+        (0..self.n_bits).filter(|b| self.contains(*b)).collect()
+    }
+
+    fn common_bits(&self, other: &Fingerprint) -> Result<u32, DomainError> {
+        if self.kind != other.kind || self.n_bits != other.n_bits {
+            return Err(DomainError::ValidationError(format!("Fingerprints no comparables: {:?} vs {:?}",
+                                                            self.kind, other.kind)));
+        }
+        Ok(self.words.iter().zip(&other.words).map(|(a, b)| (a & b).count_ones()).sum())
+    }
+
+    /// Similitud de Tanimoto. Dos fingerprints vacíos tienen similitud 0.
+    pub fn tanimoto(&self, other: &Fingerprint) -> Result<f64, DomainError> {
+        let common = self.common_bits(other)?;
+        let union = self.count_ones() + other.count_ones() - common;
+        Ok(if union == 0 {
+            0.0
+        } else {
+            f64::from(common) / f64::from(union)
+        })
+    }
+
+    /// Similitud de Dice. Dos fingerprints vacíos tienen similitud 0.
+    pub fn dice(&self, other: &Fingerprint) -> Result<f64, DomainError> {
+        let common = self.common_bits(other)?;
+        let total = self.count_ones() + other.count_ones();
+        Ok(if total == 0 {
+            0.0
+        } else {
+            2.0 * f64::from(common) / f64::from(total)
+        })
+    }
+
+    /// Similitud según la métrica indicada
+    pub fn similarity(&self, other: &Fingerprint, metric: Similarity) -> Result<f64, DomainError> {
+        match metric {
+            Similarity::Tanimoto => self.tanimoto(other),
+            Similarity::Dice => self.dice(other),
+        }
+    }
+}
+
+// Implementación de Display para formato legible
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+               "Fingerprint(kind: {:?}, bits: {}/{})",
+               self.kind,
+               self.count_ones(),
+               self.n_bits)
+    }
+}
+
+/// Índices de los `k` fingerprints más similares a `query`, con su
+/// similitud. Orden: similitud descendente y, a igualdad, índice ascendente
+/// (determinista).
+pub fn nearest_indices(query: &Fingerprint,
+                       candidates: &[Fingerprint],
+                       k: usize,
+                       metric: Similarity)
+                       -> Result<Vec<(usize, f64)>, DomainError> {
+    let mut scored = candidates.iter()
+                               .enumerate()
+                               .map(|(i, fp)| query.similarity(fp, metric).map(|s| (i, s)))
+                               .collect::<Result<Vec<_>, _>>()?;
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    scored.truncate(k);
+    Ok(scored)
+}
+
+/// Clustering de Butina (Taylor-Butina) sobre fingerprints: vecinos con
+/// similitud `>= threshold`; los centroides se eligen por mayor número de
+/// vecinos (a igualdad, menor índice). Cada cluster devuelve el centroide
+/// primero y luego sus miembros en orden de índice. Determinista.
+pub fn butina_clusters(fingerprints: &[Fingerprint],
+                       threshold: f64,
+                       metric: Similarity)
+                       -> Result<Vec<Vec<usize>>, DomainError> {
+    let n = fingerprints.len();
+    let mut neighbors: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if fingerprints[i].similarity(&fingerprints[j], metric)? >= threshold {
+                neighbors[i].push(j);
+                neighbors[j].push(i);
+            }
+        }
+    }
+    let mut order: Vec<usize> = (0..n).collect();
+    order.sort_by(|a, b| neighbors[*b].len().cmp(&neighbors[*a].len()).then(a.cmp(b)));
+    let mut assigned = vec![false; n];
+    let mut clusters = Vec::new();
+    for centroid in order {
+        if assigned[centroid] {
+            continue;
+        }
+        assigned[centroid] = true;
+        let mut members: Vec<usize> = neighbors[centroid].iter().copied().filter(|j| !assigned[*j]).collect();
+        members.sort_unstable();
+        for j in &members {
+            assigned[*j] = true;
+        }
+        let mut cluster = vec![centroid];
+        cluster.extend(members);
+        clusters.push(cluster);
+    }
+    Ok(clusters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(bits: &[u32]) -> Fingerprint {
+        Fingerprint::from_on_bits(FingerprintKind::Morgan { radius: 2, n_bits: 128 }, 128, bits.iter().copied()).unwrap()
+    }
+
+    #[test]
+    fn similarity_metrics() {
+        let a = fp(&[1, 2, 3, 100]);
+        let b = fp(&[2, 3, 4, 100]);
+        assert_eq!(a.count_ones(), 4);
+        assert!(a.contains(100) && !a.contains(4));
+        assert_eq!(a.on_bits(), vec![1, 2, 3, 100]);
+        assert!((a.tanimoto(&b).unwrap() - 3.0 / 5.0).abs() < 1e-12);
+        assert!((a.dice(&b).unwrap() - 6.0 / 8.0).abs() < 1e-12);
+        assert_eq!(a.tanimoto(&a).unwrap(), 1.0);
+        assert_eq!(fp(&[]).tanimoto(&fp(&[])).unwrap(), 0.0);
+
+        let maccs = Fingerprint::from_on_bits(FingerprintKind::Maccs, 167, [1]).unwrap();
+        assert!(a.tanimoto(&maccs).is_err());
+        assert!(Fingerprint::from_on_bits(FingerprintKind::Maccs, 167, [167]).is_err());
+    }
+
+    #[test]
+    fn nearest_and_butina_are_deterministic() {
+        let fps = vec![fp(&[1, 2, 3]), fp(&[50, 51]), fp(&[1, 2, 3, 4]), fp(&[1, 2, 3])];
+        let near = nearest_indices(&fp(&[1, 2, 3]), &fps, 3, Similarity::Tanimoto).unwrap();
+        assert_eq!(near.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 3, 2]);
+
+        let clusters = butina_clusters(&fps, 0.7, Similarity::Tanimoto).unwrap();
+        assert_eq!(clusters, vec![vec![0, 2, 3], vec![1]]);
+    }
+}
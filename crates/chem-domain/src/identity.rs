@@ -0,0 +1,168 @@
+// identity.rs
+//! Políticas de identidad: qué cuenta como "la misma molécula" al construir
+//! familias, detectar duplicados y combinar familias.
+//!
+//! - `Full`: InChIKey completo (estereoisómeros e isotopólogos distintos).
+//! - `Connectivity`: primer bloque del InChIKey (esqueleto); fusiona
+//!   estereoisómeros, isotopólogos y estados de protonación.
+//! - `Layers`: InChI reducido a la capa principal más las capas elegidas, p.
+//!   ej. solo `isotope` para distinguir isotopólogos pero no estereoisómeros.
+//!   Requiere un InChI real (no vale con los identificadores del backend
+//!   `rust`).
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{DomainError, Molecule};
+
+/// Capas opcionales de un InChI estándar. La capa principal (fórmula,
+/// conexiones `/c` e hidrógenos `/h`) siempre participa.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InchiLayer {
+    /// Carga y protonación (`/q`, `/p`).
+    Charge,
+    /// Estereoquímica de dobles enlaces y tetraédrica (`/b`, `/t`, `/m`,
+    /// `/s`).
+    Stereo,
+    /// Capa isotópica (`/i` y sus subcapas).
+    Isotope,
+    /// Hidrógenos fijos e InChI reconectado (`/f`, `/r`; solo InChI no
+    /// estándar).
+    FixedH,
+}
+
+impl InchiLayer {
+    /// Capa a la que pertenece el prefijo `prefix` de una subcapa.
+    fn of_prefix(prefix: char) -> Option<Self> {
+        match prefix {
+            'q' | 'p' => Some(InchiLayer::Charge),
+            'b' | 't' | 'm' | 's' => Some(InchiLayer::Stereo),
+            'i' => Some(InchiLayer::Isotope),
+            'f' | 'r' => Some(InchiLayer::FixedH),
+            _ => None,
+        }
+    }
+}
+
+/// Criterio de identidad de las moléculas de una familia. Se registra en el
+/// `family_hash` (salvo `Full`, el criterio por defecto).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "layers")]
+pub enum IdentityPolicy {
+    /// InChIKey completo.
+    #[default]
+    Full,
+    /// Primer bloque del InChIKey.
+    Connectivity,
+    /// Capa principal del InChI más las capas indicadas.
+    Layers(BTreeSet<InchiLayer>),
+}
+
+impl IdentityPolicy {
+    /// Política de capas a partir de una lista (el orden no importa).
+    pub fn layers(layers: impl IntoIterator<Item = InchiLayer>) -> Self {
+        IdentityPolicy::Layers(layers.into_iter().collect())
+    }
+
+    /// Clave de identidad de `molecule` según la política.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si la política es `Layers` y la
+    /// molécula no tiene un InChI estándar.
+    pub fn key(&self, molecule: &Molecule) -> Result<String, DomainError> {
+        match self {
+            IdentityPolicy::Full => Ok(molecule.inchikey().to_string()),
+            IdentityPolicy::Connectivity => Ok(connectivity_block(molecule.inchikey()).to_string()),
+            IdentityPolicy::Layers(layers) => filter_layers(molecule.inchi(), layers),
+        }
+    }
+
+    /// Clave de identidad a partir de un InChIKey suelto (la misma que
+    /// `key` para una molécula con ese InChIKey).
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` con la política `Layers`: el InChIKey
+    /// no permite separar capas y hace falta la molécula con su InChI.
+    pub fn inchikey_key(&self, inchikey: &str) -> Result<String, DomainError> {
+        match self {
+            IdentityPolicy::Full => Ok(inchikey.to_string()),
+            IdentityPolicy::Connectivity => Ok(connectivity_block(inchikey).to_string()),
+            IdentityPolicy::Layers(_) => {
+                Err(DomainError::ValidationError(format!("La política de capas necesita el InChI, no basta el InChIKey \
+                                                          {inchikey}")))
+            }
+        }
+    }
+}
+
+fn connectivity_block(inchikey: &str) -> &str {
+    inchikey.split('-').next().unwrap_or(inchikey)
+}
+
+/// InChI con la capa principal y las subcapas de `layers`. Las subcapas
+/// que siguen a `/i` son isotópicas: se conservan si se pidió `Isotope` y,
+/// en el caso de la estereoquímica isotópica, también `Stereo`.
+fn filter_layers(inchi: &str, layers: &BTreeSet<InchiLayer>) -> Result<String, DomainError> {
+    let mut parts = inchi.split('/');
+    let (Some(version), Some(formula)) = (parts.next(), parts.next()) else {
+        return Err(DomainError::ValidationError(format!("InChI sin capa principal: {inchi}")));
+    };
+    if !version.starts_with("InChI=") {
+        return Err(DomainError::ValidationError(format!("La política de capas requiere un InChI: {inchi}")));
+    }
+    let mut key = format!("{version}/{formula}");
+    let mut current: Option<InchiLayer> = None;
+    for part in parts {
+        let prefix = part.chars().next().unwrap_or_default();
+        current = match (current, InchiLayer::of_prefix(prefix)) {
+            // Dentro de la capa isotópica hasta que empiecen los H fijos
+            (Some(InchiLayer::Isotope), Some(InchiLayer::FixedH)) => Some(InchiLayer::FixedH),
+            (Some(InchiLayer::Isotope), _) => Some(InchiLayer::Isotope),
+            (Some(InchiLayer::FixedH), _) => Some(InchiLayer::FixedH),
+            (_, layer) => layer,
+        };
+        let keep = match current {
+            None => true,
+            Some(InchiLayer::Isotope) if matches!(prefix, 'b' | 't' | 'm' | 's') => {
+                layers.contains(&InchiLayer::Isotope) && layers.contains(&InchiLayer::Stereo)
+            }
+            Some(layer) => layers.contains(&layer),
+        };
+        if keep {
+            key.push('/');
+            key.push_str(part);
+        }
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALANINE_L: &str = "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1";
+    const ALANINE_13C: &str = "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)/t2-/m0/s1/i1+1";
+
+    #[test]
+    fn layers_are_filtered() -> Result<(), DomainError> {
+        let main = "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)";
+        assert_eq!(filter_layers(ALANINE_L, &BTreeSet::new())?, main);
+        assert_eq!(filter_layers(ALANINE_13C, &BTreeSet::from([InchiLayer::Isotope]))?,
+                   format!("{main}/i1+1"));
+        assert_eq!(filter_layers(ALANINE_13C, &BTreeSet::from([InchiLayer::Stereo]))?, ALANINE_L);
+        assert!(filter_layers("chemflow-rs=1/C2H6O/CCO", &BTreeSet::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn connectivity_compares_first_block() {
+        let key = "QNAYBMKLOCPYGJ-REOHCLBHSA-N";
+        assert_eq!(IdentityPolicy::Connectivity.inchikey_key(key).unwrap(), "QNAYBMKLOCPYGJ");
+        assert_eq!(IdentityPolicy::Full.inchikey_key(key).unwrap(), key);
+        assert!(IdentityPolicy::layers([InchiLayer::Isotope]).inchikey_key(key).is_err());
+        assert_eq!(serde_json::to_value(IdentityPolicy::layers([InchiLayer::Isotope])).unwrap(),
+                   serde_json::json!({"kind": "layers", "layers": ["isotope"]}));
+    }
+}
@@ -1,11 +1,58 @@
+//! Dominio químico de chemflow: moléculas, familias y propiedades con
+//! identidad y provenance reproducibles.
+//!
+//! La feature `rdkit` (por defecto) incluye el motor RDKit embebido y las
+//! operaciones que solo él calcula (estandarización, descriptores,
+//! fingerprints, scaffolds, reacciones…). Sin ella queda el backend `rust`.
+
+mod aggregate;
+mod analysis;
+mod backend;
+mod descriptors;
 mod errors;
 mod family_property;
+mod fingerprint;
+mod identity;
 mod molecular_property;
 mod molecule;
 mod molecule_family;
+mod owned_property;
+mod reaction;
+mod smiles;
+mod standardization;
+mod toolkit;
+pub mod units;
 
+pub use aggregate::{
+    aggregate_hash, canonical_values, AggregateMethod, AggregateProvenance, AggregateSpec, FamilyAggregate, FamilyAggregator,
+};
+#[cfg(feature = "rdkit")]
+pub use analysis::ScaffoldAnalysis;
+pub use analysis::{group_by_scaffold, AnalysisProvider, MoleculeRGroups, ScaffoldGroup, ScaffoldTable};
+#[cfg(feature = "rdkit")]
+pub use backend::RdkitBackend;
+pub use backend::{molecule_backend, set_backend, BackendKind, MoleculeBackend, RustBackend, BACKEND_ENV};
+pub use chemengine::{
+    Conformer, ConformerSet, EnumerationLimits, FilterCatalog, FingerprintKind, ForceField, RGroups, StandardizeOptions,
+    StructuralAlert,
+};
+pub use descriptors::{DescriptorMetadata, MolecularDescriptors, DESCRIPTOR_PROVIDER};
 pub use errors::DomainError;
 pub use family_property::FamilyProperty;
+pub use fingerprint::{butina_clusters, nearest_indices, Fingerprint, Similarity};
+pub use identity::{IdentityPolicy, InchiLayer};
 pub use molecular_property::MolecularProperty;
-pub use molecule::Molecule;
-pub use molecule_family::MoleculeFamily;
+pub use molecule::{IdentityCheck, Molecule};
+pub use molecule_family::{MoleculeFamily, FAMILY_HASH_SCHEMA_VERSION};
+pub use owned_property::{OwnedFamilyProperty, OwnedMolecularProperty, PropertyProvider};
+pub use reaction::TransformationProvider;
+#[cfg(feature = "rdkit")]
+pub use reaction::{ReactionEnumeration, ReactionEnumerator};
+pub use smiles::{ParsedSmiles, SmilesError, RUST_BACKEND_VERSION, RUST_IDENTIFIER_PREFIX};
+pub use standardization::{
+    standardization_params_hash, standardization_provenance, STANDARDIZATION_SCHEMA_VERSION, STANDARDIZER,
+};
+#[cfg(feature = "rdkit")]
+pub use toolkit::rdkit_toolkit_versions;
+pub use toolkit::{check_toolkit, toolkit_versions, ToolkitDrift, ToolkitVersions};
+pub use units::{Dimension, Unit};
@@ -1,18 +1,58 @@
 // molecule.rs
-use crate::DomainError;
-use chemengine::ChemEngine;
-use chrono::Utc;
+use crate::backend::identity_backend;
+use crate::smiles::RUST_IDENTIFIER_PREFIX;
+use crate::standardization::standardization_provenance;
+use crate::{molecule_backend, toolkit_versions, DomainError, ToolkitVersions};
+#[cfg(feature = "rdkit")]
+use crate::{rdkit_toolkit_versions, Fingerprint, MolecularDescriptors};
+use chemengine::StandardizeOptions;
+#[cfg(feature = "rdkit")]
+use chemengine::{ChemEngine, ConformerSet, FingerprintKind, ForceField};
+#[cfg(feature = "rdkit")]
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
 /// Inicialización segura del motor químico con manejo de errores
-static ENGINE: Lazy<Result<ChemEngine, DomainError>> = Lazy::new(|| {
-    ChemEngine::init().map_err(|e| DomainError::ExternalError(format!("Error al inicializar el motor químico: {}", e)))
-});
+#[cfg(feature = "rdkit")]
+static ENGINE: Lazy<Result<ChemEngine, String>> = Lazy::new(|| ChemEngine::init().map_err(|e| e.to_string()));
+
+/// Acceso compartido al motor químico del crate.
+///
+/// # Errores
+/// `DomainError::EngineUnavailable` si Python/RDKit no se pudo inicializar.
+#[cfg(feature = "rdkit")]
+pub(crate) fn engine() -> Result<&'static ChemEngine, DomainError> {
+    ENGINE.as_ref().map_err(|e| DomainError::EngineUnavailable(e.clone()))
+}
+
+/// Error de un lote al que el motor no devolvió un resultado por ítem.
+pub(crate) fn incomplete_response() -> DomainError {
+    DomainError::ExternalError("Respuesta del motor incompleta: falta el resultado de alguna molécula".to_string())
+}
+
+/// Validación de identidad al construir una molécula a partir de sus partes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityCheck {
+    /// Solo formato: InChIKey 14-10-1 y SMILES/InChI no vacíos.
+    #[default]
+    Format,
+    /// Además recalcula el InChIKey desde el InChI y desde el SMILES con el
+    /// backend químico (ver `Molecule::verify_identity`).
+    Strict,
+}
 
 /// Representa una molécula química con sus identificadores únicos y metadatos
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// La identidad (`PartialEq`/`Eq`/`Hash`) se basa solo en la estructura
+/// canónica (InChIKey, InChI y SMILES canónico); `metadata` es provenance
+/// informativa y no participa. Los metadatos generados por el crate son
+/// deterministas (sin marcas de tiempo): la misma entrada produce
+/// exactamente la misma molécula en el mismo entorno. Las moléculas creadas
+/// por el backend registran sus versiones en `metadata["toolkit"]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Molecule {
     inchikey: String,
     smiles: String,
@@ -73,28 +113,133 @@ impl Molecule {
         Self::new(inchikey, smiles, inchi, metadata)
     }
 
+    /// `from_parts` con el nivel de validación `check`. En modo `Strict` las
+    /// partes que no describen el mismo compuesto producen
+    /// `DomainError::IdentityMismatch`.
+    pub fn from_parts_checked(inchikey: &str,
+                              smiles: &str,
+                              inchi: &str,
+                              metadata: serde_json::Value,
+                              check: IdentityCheck)
+                              -> Result<Self, DomainError> {
+        let molecule = Self::new(inchikey, smiles, inchi, metadata)?;
+        if check == IdentityCheck::Strict {
+            molecule.verify_identity()?;
+        }
+        Ok(molecule)
+    }
+
     pub fn from_smiles(smiles: &str) -> Result<Self, DomainError> {
         // Verificar entrada vacía
         if smiles.trim().is_empty() {
             return Err(DomainError::ValidationError("SMILES de entrada no puede estar vacío".to_string()));
         }
-        // Obtener instancia del motor con manejo de errores
-        let engine = ENGINE.as_ref()
-                           .map_err(|e| DomainError::ExternalError(format!("Motor químico no disponible: {}", e)))?;
+        // Interpretar con el backend activo (RDKit o Rust puro)
+        let chem_molecule = molecule_backend()?.parse_smiles(smiles)?;
+
+        Ok(Self::from_engine_molecule(&chem_molecule, smiles, None)?.with_toolkit(toolkit_versions()?))
+    }
+
+    /// Versión por lotes de `from_smiles`: una sola llamada al motor para
+    /// todos los SMILES. El resultado conserva el orden de la entrada y cada
+    /// SMILES inválido produce su propio `Err` (el error indica el índice)
+    /// sin abortar el resto. Solo falla entero si el backend no está
+    /// disponible.
+    pub fn from_smiles_batch(smiles: &[&str]) -> Result<Vec<Result<Self, DomainError>>, DomainError> {
+        let results = molecule_backend()?.parse_smiles_batch(smiles)?;
+        Ok(Self::from_engine_batch(results, smiles, None, toolkit_versions()?))
+    }
+
+    /// Versión por lotes a partir de bloques MOL (registros de SDF/MOL). La
+    /// posición `i` del resultado corresponde a `molblocks[i]`; los bloques
+    /// inválidos producen su `Err` tipado (como en `from_smiles_batch`) sin
+    /// abortar el lote. Siempre lee RDKit, así que las versiones registradas
+    /// son las de `rdkit_toolkit_versions` sea cual sea el backend activo.
+    #[cfg(feature = "rdkit")]
+    pub fn from_molblock_batch(molblocks: &[&str]) -> Result<Vec<Result<Self, DomainError>>, DomainError> {
+        let results = engine()?.get_molecules_from_molblocks(molblocks)?;
+        let toolkit = rdkit_toolkit_versions()?;
+        Ok(results.into_iter()
+                  .map(|item| {
+                      let m = item?;
+                      Self::new(&m.inchikey,
+                                &m.smiles,
+                                &m.inchi,
+                                serde_json::json!({ "source": "created_from_molblock" })).map(|m| m.with_toolkit(toolkit))
+                  })
+                  .collect())
+    }
+
+    /// Crea la molécula a partir de la estructura estandarizada con
+    /// `rdMolStandardize` (sales, cargas, tautómeros, metales según
+    /// `options`), de modo que formas equivalentes del mismo compuesto
+    /// comparten InChIKey. Las opciones y su hash quedan en
+    /// `metadata["standardization"]`; como en `from_molblock_batch`, las
+    /// versiones registradas son las de RDKit.
+    #[cfg(feature = "rdkit")]
+    pub fn from_smiles_standardized(smiles: &str, options: &StandardizeOptions) -> Result<Self, DomainError> {
+        if smiles.trim().is_empty() {
+            return Err(DomainError::ValidationError("SMILES de entrada no puede estar vacío".to_string()));
+        }
+        let chem_molecule = engine()?.standardize(smiles, options)?;
+        Ok(Self::from_engine_molecule(&chem_molecule, smiles, Some(options))?.with_toolkit(rdkit_toolkit_versions()?))
+    }
+
+    /// Versión por lotes de `from_smiles_standardized` (errores por ítem,
+    /// como en `from_smiles_batch`).
+    #[cfg(feature = "rdkit")]
+    pub fn from_smiles_batch_standardized(smiles: &[&str],
+                                          options: &StandardizeOptions)
+                                          -> Result<Vec<Result<Self, DomainError>>, DomainError> {
+        let results = engine()?.standardize_batch(smiles, options)?;
+        Ok(Self::from_engine_batch(results,
+                                   smiles,
+                                   Some(options),
+                                   rdkit_toolkit_versions()?))
+    }
 
-        // Obtener molécula del motor
-        let chem_molecule = engine.get_molecule(smiles)
-                                  .map_err(|e| DomainError::ExternalError(format!("Error al procesar SMILES: {}", e)))?;
+    fn from_engine_batch(results: Vec<Result<chemengine::Molecule, chemengine::BatchItemError>>,
+                         smiles: &[&str],
+                         standardization: Option<&StandardizeOptions>,
+                         toolkit: &ToolkitVersions)
+                         -> Vec<Result<Self, DomainError>> {
+        results.into_iter()
+               .zip(smiles)
+               .enumerate()
+               .map(|(index, (item, original))| {
+                   if original.trim().is_empty() {
+                       return Err(DomainError::ValidationError(format!("SMILES #{index}: SMILES de entrada no puede estar vacío")));
+                   }
+                   match item {
+                       Ok(m) => Self::from_engine_molecule(&m, original, standardization).map(|m| m.with_toolkit(toolkit)),
+                       Err(e) => Err(e.into()),
+                   }
+               })
+               .collect()
+    }
 
+    /// Construye la molécula de dominio a partir de la respuesta del motor.
+    fn from_engine_molecule(chem_molecule: &chemengine::Molecule,
+                            original_smiles: &str,
+                            standardization: Option<&StandardizeOptions>)
+                            -> Result<Self, DomainError> {
         // Crear instancia con metadatos relevantes
-        Self::new(&chem_molecule.inchikey,
-                  &chem_molecule.smiles,
-                  &chem_molecule.inchi,
-                  serde_json::json!({
-                      "source": "created_from_smiles",
-                      "original_smiles": smiles,
-                      "generation_timestamp": Utc::now().to_rfc3339(),
-                  }))
+        let mut metadata = serde_json::json!({
+            "source": "created_from_smiles",
+            "original_smiles": original_smiles,
+        });
+        if let Some(options) = standardization {
+            metadata["standardization"] = standardization_provenance(options);
+        }
+        Self::new(&chem_molecule.inchikey, &chem_molecule.smiles, &chem_molecule.inchi, metadata)
+    }
+
+    /// Registra las versiones del toolkit en `metadata["toolkit"]`.
+    fn with_toolkit(mut self, toolkit: &ToolkitVersions) -> Self {
+        if let Some(metadata) = self.metadata.as_object_mut() {
+            metadata.insert("toolkit".to_string(), serde_json::json!(toolkit));
+        }
+        self
     }
 
     /// Obtiene el SMILES de la molécula
@@ -117,12 +262,131 @@ impl Molecule {
         &self.metadata
     }
 
+    /// Calcula descriptores RDKit de la molécula (ver
+    /// `chemengine::Descriptor`; `names` vacío = todos) y los expone como
+    /// `MolecularProperty`. Los descriptores fallidos no abortan la llamada:
+    /// quedan en `MolecularDescriptors::errors`.
+    #[cfg(feature = "rdkit")]
+    pub fn descriptors(&self, names: &[&str]) -> Result<MolecularDescriptors<'_>, DomainError> {
+        let engine = engine()?;
+        let descriptors = engine.descriptors(&self.smiles, names)?;
+        MolecularDescriptors::from_engine(self, &descriptors)
+    }
+
+    /// Calcula el fingerprint binario de la molécula (Morgan/ECFP, MACCS o
+    /// RDKit path según `kind`).
+    #[cfg(feature = "rdkit")]
+    pub fn fingerprint(&self, kind: &FingerprintKind) -> Result<Fingerprint, DomainError> {
+        let bits = engine()?.fingerprint(&self.smiles, kind)?;
+        Fingerprint::from_engine(kind.clone(), bits)
+    }
+
+    /// Genera `n` conformeros 3D (ETKDG + `force_field`) con semilla fija.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si `n` es 0 o `seed` supera
+    /// `ConformerSet::MAX_SEED`; `ExternalError` si RDKit no puede generar o
+    /// parametrizar la molécula y `EngineUnavailable` si el motor no está
+    /// disponible.
+    #[cfg(feature = "rdkit")]
+    pub fn embed_conformers(&self, n: usize, seed: u32, force_field: ForceField) -> Result<ConformerSet, DomainError> {
+        if n == 0 {
+            return Err(DomainError::ValidationError("Se necesita al menos un conformero".to_string()));
+        }
+        if seed > ConformerSet::MAX_SEED {
+            return Err(DomainError::ValidationError(format!("La semilla debe ser como mucho {}", ConformerSet::MAX_SEED)));
+        }
+        Ok(engine()?.embed_conformers(&self.smiles, n, seed, force_field)?)
+    }
+
+    /// Indica si la molécula contiene la subestructura descrita por `smarts`.
+    ///
+    /// # Errores
+    /// `DomainError::ExternalError` si el SMARTS es inválido y
+    /// `EngineUnavailable` si el motor no está disponible.
+    #[cfg(feature = "rdkit")]
+    pub fn matches_smarts(&self, smarts: &str) -> Result<bool, DomainError> {
+        Ok(engine()?.matches_smarts(&self.smiles, smarts)?)
+    }
+
+    /// Comprueba que InChIKey, InChI y SMILES describen el mismo compuesto:
+    /// el InChIKey recalculado desde el InChI y desde el SMILES debe
+    /// coincidir con el declarado. Los identificadores de
+    /// `RUST_IDENTIFIER_PREFIX` se verifican con el backend de Rust y los
+    /// InChI con RDKit, sea cual sea el backend activo.
+    ///
+    /// # Errores
+    /// `DomainError::IdentityMismatch` si no coinciden y `EngineUnavailable`
+    /// si hace falta RDKit y no está disponible.
+    pub fn verify_identity(&self) -> Result<(), DomainError> {
+        Self::verify_identities(std::slice::from_ref(self))?.pop()
+                                                            .unwrap_or_else(|| Err(incomplete_response()))
+    }
+
+    /// Versión por lotes de `verify_identity`: una llamada al backend por
+    /// tipo de identificador. La posición `i` del resultado corresponde a
+    /// `molecules[i]`; solo falla entero si el backend no está disponible o
+    /// no devuelve un resultado por molécula (`DomainError::ExternalError`).
+    pub fn verify_identities(molecules: &[Molecule]) -> Result<Vec<Result<(), DomainError>>, DomainError> {
+        let mut results: Vec<Result<(), DomainError>> = molecules.iter().map(|_| Ok(())).collect();
+        let (rust, inchi): (Vec<usize>, Vec<usize>) =
+            (0..molecules.len()).partition(|&i| molecules[i].inchi.starts_with(RUST_IDENTIFIER_PREFIX));
+        for indices in [rust, inchi].into_iter().filter(|group| !group.is_empty()) {
+            let backend = identity_backend(&molecules[indices[0]].inchi)?;
+            let identifiers: Vec<&str> = indices.iter().map(|&i| molecules[i].inchi.as_str()).collect();
+            let smiles: Vec<&str> = indices.iter().map(|&i| molecules[i].smiles.as_str()).collect();
+            let from_identifiers = backend.inchikeys_from_identifiers(&identifiers)?;
+            let from_smiles = backend.parse_smiles_batch(&smiles)?;
+            if from_identifiers.len() != indices.len() || from_smiles.len() != indices.len() {
+                return Err(incomplete_response());
+            }
+            for ((&i, by_identifier), by_smiles) in indices.iter().zip(from_identifiers).zip(from_smiles) {
+                let molecule = &molecules[i];
+                results[i] =
+                    molecule.compare_key("InChI", by_identifier.map_err(|e| e.message))
+                            .and_then(|()| {
+                                molecule.compare_key("SMILES", by_smiles.map(|m| m.inchikey).map_err(|e| e.message))
+                            });
+            }
+        }
+        Ok(results)
+    }
+
+    /// Compara el InChIKey recalculado desde `source` con el declarado.
+    fn compare_key(&self, source: &str, computed: Result<String, String>) -> Result<(), DomainError> {
+        let message = match computed {
+            Ok(key) if key == self.inchikey => return Ok(()),
+            Ok(key) => format!("el {source} corresponde a {key}"),
+            Err(e) => format!("el {source} no se puede interpretar: {e}"),
+        };
+        Err(DomainError::IdentityMismatch { inchikey: self.inchikey.clone(),
+                                            message })
+    }
+
     /// Compara si dos moléculas son la misma basándose en el InChIKey
     pub fn is_same(&self, other: &Molecule) -> bool {
         self.inchikey == other.inchikey
     }
 }
 
+// Igualdad basada en la estructura canónica (ignora metadata)
+impl PartialEq for Molecule {
+    fn eq(&self, other: &Self) -> bool {
+        self.inchikey == other.inchikey && self.inchi == other.inchi && self.smiles == other.smiles
+    }
+}
+
+impl Eq for Molecule {}
+
+// Hash coherente con PartialEq: solo la estructura canónica
+impl Hash for Molecule {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.inchikey.hash(state);
+        self.inchi.hash(state);
+        self.smiles.hash(state);
+    }
+}
+
 // Implementación de Display para formato legible
 impl fmt::Display for Molecule {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -131,3 +395,73 @@ impl fmt::Display for Molecule {
                self.smiles, self.inchi, self.inchikey)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MoleculeBackend, RustBackend};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_identity_ignores_metadata() -> Result<(), DomainError> {
+        let key = "LFQSCWFLJHTTHZ-UHFFFAOYSA-N";
+        let a = Molecule::from_parts(key, "CCO", "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3", json!({"source": "a"}))?;
+        let b = Molecule::from_parts(key, "CCO", "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3", json!({"source": "b"}))?;
+        assert_eq!(a, b);
+        assert_eq!(HashSet::from([a.clone(), b]).len(), 1);
+
+        let c = Molecule::from_parts(key, "OCC", "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3", json!({"source": "a"}))?;
+        assert_ne!(a, c);
+        Ok(())
+    }
+
+    #[test]
+    fn test_engine_metadata_is_deterministic() -> Result<(), DomainError> {
+        let raw = chemengine::Molecule { smiles: "CCO".to_string(),
+                                         inchi: "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3".to_string(),
+                                         inchikey: "LFQSCWFLJHTTHZ-UHFFFAOYSA-N".to_string(),
+                                         num_atoms: 3,
+                                         mol_weight: 46.07,
+                                         mol_formula: "C2H6O".to_string() };
+        let first = Molecule::from_engine_molecule(&raw, "OCC", None)?;
+        let second = Molecule::from_engine_molecule(&raw, "OCC", None)?;
+        assert_eq!(serde_json::to_vec(&first)?, serde_json::to_vec(&second)?);
+        assert_eq!(first.metadata(),
+                   &json!({"source": "created_from_smiles", "original_smiles": "OCC"}));
+        Ok(())
+    }
+
+    #[test]
+    fn test_strict_identity_check() -> Result<(), DomainError> {
+        let ethanol = RustBackend.parse_smiles("CCO")?;
+        let methanol = RustBackend.parse_smiles("CO")?;
+        let strict = |key: &str, smiles: &str, inchi: &str| {
+            Molecule::from_parts_checked(key, smiles, inchi, json!({}), IdentityCheck::Strict)
+        };
+        assert!(strict(&ethanol.inchikey, &ethanol.smiles, &ethanol.inchi).is_ok());
+        assert!(matches!(strict(&methanol.inchikey, &ethanol.smiles, &ethanol.inchi),
+                         Err(DomainError::IdentityMismatch { .. })));
+        assert!(matches!(strict(&ethanol.inchikey, &methanol.smiles, &ethanol.inchi),
+                         Err(DomainError::IdentityMismatch { .. })));
+        // Sin modo estricto solo se valida el formato
+        let lax = Molecule::from_parts_checked(&methanol.inchikey,
+                                               &ethanol.smiles,
+                                               &ethanol.inchi,
+                                               json!({}),
+                                               IdentityCheck::Format)?;
+        let results =
+            Molecule::verify_identities(&[lax.clone(), strict(&ethanol.inchikey, &ethanol.smiles, &ethanol.inchi)?])?;
+        assert!(results[0].as_ref().unwrap_err().is_permanent());
+        assert!(results[1].is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_molecules_record_toolkit() -> Result<(), DomainError> {
+        let molecule = Molecule::from_smiles("CCO")?;
+        assert_eq!(molecule.metadata()["toolkit"], json!(toolkit_versions()?));
+        assert_eq!(molecule.metadata()["toolkit"]["backend"], molecule_backend()?.name());
+        Ok(())
+    }
+}
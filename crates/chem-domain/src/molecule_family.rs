@@ -1,11 +1,23 @@
 // molecule_family.rs
-use crate::{DomainError, Molecule};
+use crate::{butina_clusters, nearest_indices, DomainError, Fingerprint, IdentityPolicy, Molecule, Similarity};
+#[cfg(feature = "rdkit")]
+use crate::{FilterCatalog, FingerprintKind, RGroups, StructuralAlert};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt;
 use uuid::Uuid;
 
+/// Versión del esquema del `family_hash`. Forma parte del hash junto con los
+/// InChIKeys ordenados, los parámetros de construcción y la política de
+/// identidad (Requerimientos §3.3).
+///
+/// La versión 0 (sin esquema) era el SHA-256 de los InChIKeys concatenados;
+/// `verify_integrity` la sigue aceptando para familias serializadas antes del
+/// cambio, que no tienen parámetros y usan la política `Full`. Las familias
+/// derivadas de ellas ya se calculan con la versión actual.
+pub const FAMILY_HASH_SCHEMA_VERSION: u32 = 1;
+
 /// Representa una colección inmutable de moléculas relacionadas con metadatos
 /// y verificación de integridad mediante hash. Ideal para agrupar moléculas
 /// con propiedades estructurales o funcionales similares.
@@ -16,6 +28,14 @@ pub struct MoleculeFamily {
     description: Option<String>,
     family_hash: String,
     provenance: serde_json::Value,
+    /// Parámetros de construcción (operación, semilla, fracciones…) que
+    /// entran en el `family_hash`. `{}` para familias construidas con `new`
+    /// o serializadas antes de registrarse los parámetros.
+    #[serde(default = "empty_object")]
+    parameters: serde_json::Value,
+    /// Criterio de duplicados y de pertenencia (ver `IdentityPolicy`).
+    #[serde(default)]
+    identity: IdentityPolicy,
     frozen: bool,
     molecules: Vec<Molecule>,
 }
@@ -33,39 +53,86 @@ impl MoleculeFamily {
     /// familia
     pub fn new<I>(molecules: I, provenance: serde_json::Value) -> Result<Self, DomainError>
         where I: IntoIterator<Item = Molecule>
+    {
+        Self::new_with_parameters(molecules, provenance, serde_json::json!({}))
+    }
+
+    /// Igual que `new`, registrando los parámetros de construcción que
+    /// entran en el `family_hash` (`H(ordered_inchikeys + parameters +
+    /// schema_version)`).
+    pub fn new_with_parameters<I>(molecules: I,
+                                  provenance: serde_json::Value,
+                                  parameters: serde_json::Value)
+                                  -> Result<Self, DomainError>
+        where I: IntoIterator<Item = Molecule>
+    {
+        Self::new_with_identity(molecules, provenance, parameters, IdentityPolicy::Full)
+    }
+
+    /// Igual que `new_with_parameters` con la política de identidad
+    /// `identity` para detectar duplicados. Una política distinta de `Full`
+    /// entra en el `family_hash`.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si dos moléculas tienen la misma clave
+    /// según `identity` o si la política no es aplicable a alguna.
+    pub fn new_with_identity<I>(molecules: I,
+                                provenance: serde_json::Value,
+                                parameters: serde_json::Value,
+                                identity: IdentityPolicy)
+                                -> Result<Self, DomainError>
+        where I: IntoIterator<Item = Molecule>
     {
         let molecules: Vec<Molecule> = molecules.into_iter().collect();
         // Validar que la familia no esté vacía
         if molecules.is_empty() {
             return Err(DomainError::ValidationError("Una familia molecular no puede estar vacía".to_string()));
         }
-        // Validar duplicados por InChIKey
-        let mut seen_inchikeys = HashSet::new();
+        // Validar duplicados según la política de identidad
+        let mut seen_keys = HashSet::new();
         for molecule in &molecules {
-            if !seen_inchikeys.insert(molecule.inchikey().to_owned()) {
+            if !seen_keys.insert(identity.key(molecule)?) {
                 return Err(DomainError::ValidationError(format!("Molécula duplicada en familia: {}", molecule.inchikey())));
             }
         }
-        // Generar hash basado en la secuencia de InChIKeys de las moléculas
-        let family_hash = Self::calculate_family_hash(&molecules);
+        // Generar hash basado en la secuencia de InChIKeys y los parámetros
+        let family_hash = Self::calculate_family_hash(&molecules, &parameters, &identity);
         Ok(MoleculeFamily { id: Uuid::new_v4(),
                             name: None,
                             description: None,
                             family_hash,
                             provenance,
+                            parameters,
+                            identity,
                             frozen: true, // Las familias son inmutables por defecto
                             molecules })
     }
 
-    /// Calcula el hash de la familia basado en los InChIKeys de las moléculas
-    fn calculate_family_hash(molecules: &[Molecule]) -> String {
-        let mut hasher = Sha256::new();
+    /// Calcula el hash de la familia: SHA-256 del JSON canónico (claves
+    /// ordenadas) con los InChIKeys en orden, los parámetros de construcción,
+    /// la política de identidad y la versión de esquema. `Full` no se
+    /// incluye, así que los hashes anteriores a las políticas siguen siendo
+    /// válidos.
+    fn calculate_family_hash(molecules: &[Molecule], parameters: &serde_json::Value, identity: &IdentityPolicy) -> String {
+        let inchikeys: Vec<&str> = molecules.iter().map(|m| m.inchikey()).collect();
+        let mut canonical = serde_json::json!({
+            "ordered_inchikeys": inchikeys,
+            "parameters": parameters,
+            "schema_version": FAMILY_HASH_SCHEMA_VERSION,
+        });
+        if *identity != IdentityPolicy::Full {
+            canonical["identity"] = serde_json::json!(identity);
+        }
+        format!("{:x}", Sha256::digest(canonical.to_string().as_bytes()))
+    }
 
-        // Incluir todos los InChIKeys en el hash para verificación de integridad
+    /// Hash de la versión 0 del esquema: SHA-256 de los InChIKeys
+    /// concatenados, sin parámetros ni política.
+    fn legacy_family_hash(molecules: &[Molecule]) -> String {
+        let mut hasher = Sha256::new();
         for molecule in molecules {
             hasher.update(molecule.inchikey().as_bytes());
         }
-
         format!("{:x}", hasher.finalize())
     }
 
@@ -89,10 +156,10 @@ impl MoleculeFamily {
     ///
     /// # Errores
     /// Retorna `DomainError::ValidationError` si la molécula ya existe en la
-    /// familia
+    /// familia (según su política de identidad)
     pub fn add_molecule(&self, molecule: Molecule) -> Result<Self, DomainError> {
         // Verificar si la molécula ya existe en la familia
-        if self.molecules.iter().any(|m| m.inchikey() == molecule.inchikey()) {
+        if self.contains_molecule(&molecule)? {
             return Err(DomainError::ValidationError(format!("Molécula ya existe en la familia: {}", molecule.inchikey())));
         }
 
@@ -101,25 +168,38 @@ impl MoleculeFamily {
         new_molecules.push(molecule);
 
         // Calcular nuevo hash
-        let family_hash = Self::calculate_family_hash(&new_molecules);
+        let family_hash = Self::calculate_family_hash(&new_molecules, &self.parameters, &self.identity);
 
         Ok(MoleculeFamily { id: Uuid::new_v4(),
                             name: self.name.clone(),
                             description: self.description.clone(),
                             family_hash,
                             provenance: self.provenance.clone(),
+                            parameters: self.parameters.clone(),
+                            identity: self.identity.clone(),
                             frozen: true,
                             molecules: new_molecules })
     }
 
     /// Elimina una molécula de la familia por su InChIKey, creando una nueva
-    /// instancia
+    /// instancia. Se compara según la política de identidad, como en
+    /// `contains`.
     ///
     /// # Argumentos
     /// * `inchikey` - InChIKey de la molécula a eliminar
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si la familia quedaría vacía o si la
+    /// política es `Layers` (un InChIKey no basta).
     pub fn remove_molecule(&self, inchikey: &str) -> Result<Self, DomainError> {
-        // Filtrar la molécula a eliminar
-        let new_molecules: Vec<Molecule> = self.molecules.iter().filter(|m| m.inchikey() != inchikey).cloned().collect();
+        // Filtrar la molécula a eliminar según la política de identidad
+        let key = self.identity.inchikey_key(inchikey)?;
+        let mut new_molecules = Vec::with_capacity(self.molecules.len());
+        for molecule in &self.molecules {
+            if self.identity.key(molecule)? != key {
+                new_molecules.push(molecule.clone());
+            }
+        }
 
         // Validar que la familia no quede vacía
         if new_molecules.is_empty() {
@@ -127,21 +207,30 @@ impl MoleculeFamily {
         }
 
         // Calcular nuevo hash
-        let family_hash = Self::calculate_family_hash(&new_molecules);
+        let family_hash = Self::calculate_family_hash(&new_molecules, &self.parameters, &self.identity);
 
         Ok(MoleculeFamily { id: Uuid::new_v4(),
                             name: self.name.clone(),
                             description: self.description.clone(),
                             family_hash,
                             provenance: self.provenance.clone(),
+                            parameters: self.parameters.clone(),
+                            identity: self.identity.clone(),
                             frozen: true,
                             molecules: new_molecules })
     }
 
-    /// Verifica la integridad de la familia recalculando y comparando el hash
+    /// Verifica la integridad de la familia recalculando y comparando el hash.
+    /// Acepta también el hash de la versión 0 del esquema (ver
+    /// `FAMILY_HASH_SCHEMA_VERSION`) si la familia no registra parámetros ni
+    /// otra política que `Full`.
     pub fn verify_integrity(&self) -> bool {
-        let calculated_hash = Self::calculate_family_hash(&self.molecules);
-        calculated_hash == self.family_hash
+        let calculated_hash = Self::calculate_family_hash(&self.molecules, &self.parameters, &self.identity);
+        if calculated_hash == self.family_hash {
+            return true;
+        }
+        let legacy = self.identity == IdentityPolicy::Full && self.parameters == empty_object();
+        legacy && Self::legacy_family_hash(&self.molecules) == self.family_hash
     }
 
     // Getters
@@ -160,9 +249,36 @@ impl MoleculeFamily {
         self.molecules.is_empty()
     }
 
-    /// Indica si la familia contiene una molécula con el InChIKey dado
+    /// Indica si la familia contiene el compuesto con el InChIKey dado según
+    /// su política de identidad (con `Connectivity` basta el primer bloque).
+    /// Con `Layers` el InChIKey no permite separar capas y se exige el
+    /// InChIKey exacto (ver `try_contains` y `contains_molecule`).
     pub fn contains(&self, inchikey: &str) -> bool {
-        self.molecules.iter().any(|m| m.inchikey() == inchikey)
+        match self.identity {
+            IdentityPolicy::Layers(_) => self.molecules.iter().any(|m| m.inchikey() == inchikey),
+            // `Full` y `Connectivity` se calculan desde el InChIKey: no fallan
+            _ => self.try_contains(inchikey).unwrap_or(false),
+        }
+    }
+
+    /// Como `contains`, pero aplicando siempre la política de identidad.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` con la política `Layers`, que necesita
+    /// el InChI: usar `contains_molecule`.
+    pub fn try_contains(&self, inchikey: &str) -> Result<bool, DomainError> {
+        Ok(self.identity_keys()?.contains(&self.identity.inchikey_key(inchikey)?))
+    }
+
+    /// Indica si la familia contiene `molecule` según su política de
+    /// identidad.
+    pub fn contains_molecule(&self, molecule: &Molecule) -> Result<bool, DomainError> {
+        Ok(self.identity_keys()?.contains(&self.identity.key(molecule)?))
+    }
+
+    /// Claves de identidad de las moléculas de la familia.
+    fn identity_keys(&self) -> Result<HashSet<String>, DomainError> {
+        self.molecules.iter().map(|m| self.identity.key(m)).collect()
     }
 
     /// Obtiene el hash único que identifica la composición de la familia
@@ -195,12 +311,353 @@ impl MoleculeFamily {
         &self.provenance
     }
 
+    /// Obtiene los parámetros de construcción incluidos en el hash
+    pub fn parameters(&self) -> &serde_json::Value {
+        &self.parameters
+    }
+
+    /// Obtiene la política de identidad de la familia
+    pub fn identity(&self) -> &IdentityPolicy {
+        &self.identity
+    }
+
+    /// Construye una familia derivada de `parents` por `operation`. Los
+    /// parámetros (con la operación) entran en el hash; la provenance
+    /// registra además los hashes de las familias de origen. La política de
+    /// identidad es la del primer padre.
+    fn derive(molecules: Vec<Molecule>,
+              operation: &str,
+              parents: &[&MoleculeFamily],
+              mut parameters: serde_json::Value)
+              -> Result<Self, DomainError> {
+        if molecules.is_empty() {
+            return Err(DomainError::ValidationError(format!("La operación '{operation}' produce una familia vacía")));
+        }
+        parameters["operation"] = serde_json::json!(operation);
+        let parent_hashes: Vec<&str> = parents.iter().map(|p| p.family_hash()).collect();
+        let provenance = serde_json::json!({
+            "operation": operation,
+            "parent_family_hashes": parent_hashes,
+            "parameters": parameters,
+        });
+        Self::new_with_identity(molecules, provenance, parameters, parents[0].identity.clone())
+    }
+
+    /// Claves de identidad de `other`, que debe compartir la política de
+    /// `self` para poder combinarse.
+    fn shared_keys(&self, other: &MoleculeFamily) -> Result<HashSet<String>, DomainError> {
+        if self.identity != other.identity {
+            return Err(DomainError::ValidationError(format!("Las familias usan políticas de identidad distintas ({:?} y {:?})",
+                                                            self.identity, other.identity)));
+        }
+        other.identity_keys()
+    }
+
+    /// Unión: moléculas de `self` en su orden, seguidas de las de `other` que
+    /// no estén en `self`. Las operaciones de conjuntos comparan por la
+    /// política de identidad, que debe ser la misma en ambas familias.
+    pub fn union(&self, other: &MoleculeFamily) -> Result<Self, DomainError> {
+        let keys = other.shared_keys(self)?;
+        let mut molecules = self.molecules.clone();
+        for molecule in &other.molecules {
+            if !keys.contains(&self.identity.key(molecule)?) {
+                molecules.push(molecule.clone());
+            }
+        }
+        Self::derive(molecules, "union", &[self, other], serde_json::json!({}))
+    }
+
+    /// Intersección: moléculas de `self` presentes en `other`, en el orden de
+    /// `self`. Error si no comparten ninguna.
+    pub fn intersection(&self, other: &MoleculeFamily) -> Result<Self, DomainError> {
+        let keys = self.shared_keys(other)?;
+        let molecules = self.select(|key| keys.contains(key))?;
+        Self::derive(molecules, "intersection", &[self, other], serde_json::json!({}))
+    }
+
+    /// Diferencia: moléculas de `self` ausentes en `other`, en el orden de
+    /// `self`. Error si no queda ninguna.
+    pub fn difference(&self, other: &MoleculeFamily) -> Result<Self, DomainError> {
+        let keys = self.shared_keys(other)?;
+        let molecules = self.select(|key| !keys.contains(key))?;
+        Self::derive(molecules, "difference", &[self, other], serde_json::json!({}))
+    }
+
+    /// Moléculas de `self` cuya clave de identidad cumple `keep`, en orden.
+    fn select(&self, keep: impl Fn(&String) -> bool) -> Result<Vec<Molecule>, DomainError> {
+        let mut selected = Vec::new();
+        for molecule in &self.molecules {
+            if keep(&self.identity.key(molecule)?) {
+                selected.push(molecule.clone());
+            }
+        }
+        Ok(selected)
+    }
+
+    /// Partición aleatoria reproducible en (train, test). La misma `seed`
+    /// produce siempre la misma partición; cada subconjunto conserva el
+    /// orden de la familia. `train_fraction` en (0, 1); ambos subconjuntos
+    /// tienen al menos una molécula.
+    pub fn split_random(&self, train_fraction: f64, seed: u64) -> Result<(Self, Self), DomainError> {
+        let n_train = self.train_size(train_fraction)?;
+        let mut order: Vec<usize> = (0..self.molecules.len()).collect();
+        let mut state = seed;
+        // Fisher–Yates con SplitMix64: determinista e independiente de la
+        // plataforma.
+        for i in (1..order.len()).rev() {
+            let j = (splitmix64(&mut state) % (i as u64 + 1)) as usize;
+            order.swap(i, j);
+        }
+        let mut in_train = vec![false; self.molecules.len()];
+        for &i in &order[..n_train] {
+            in_train[i] = true;
+        }
+        self.split_by_mask(&in_train,
+                           "random_split",
+                           serde_json::json!({ "seed": seed, "train_fraction": train_fraction }))
+    }
+
+    /// Partición por scaffold de Bemis–Murcko: moléculas con el mismo
+    /// scaffold quedan en el mismo subconjunto (ver `split_by_scaffold_keys`).
+    #[cfg(feature = "rdkit")]
+    pub fn split_by_scaffold(&self, train_fraction: f64) -> Result<(Self, Self), DomainError> {
+        let scaffolds = self.murcko_scaffolds()?;
+        self.split_by_scaffold_keys(&scaffolds, train_fraction)
+    }
+
+    /// Partición por grupos de scaffold ya calculados (alineados con
+    /// `molecules()`). Los grupos se asignan a train de mayor a menor tamaño
+    /// (empates por scaffold) mientras quepan en la fracción pedida; el resto
+    /// va a test. Los parámetros registran el hash de los scaffolds.
+    pub fn split_by_scaffold_keys(&self, scaffolds: &[String], train_fraction: f64) -> Result<(Self, Self), DomainError> {
+        if scaffolds.len() != self.molecules.len() {
+            return Err(DomainError::ValidationError(format!("Se esperaban {} scaffolds, se recibieron {}",
+                                                            self.molecules.len(),
+                                                            scaffolds.len())));
+        }
+        let n_train = self.train_size(train_fraction)?;
+        let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+        for (i, scaffold) in scaffolds.iter().enumerate() {
+            groups.entry(scaffold.as_str()).or_default().push(i);
+        }
+        let mut groups: Vec<(&str, Vec<usize>)> = groups.into_iter().collect();
+        groups.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
+        let mut in_train = vec![false; self.molecules.len()];
+        let mut assigned = 0;
+        for (_, members) in &groups {
+            if assigned + members.len() <= n_train {
+                assigned += members.len();
+                for &i in members {
+                    in_train[i] = true;
+                }
+            }
+        }
+        let scaffolds_hash = format!("{:x}", Sha256::digest(serde_json::json!(scaffolds).to_string().as_bytes()));
+        self.split_by_mask(&in_train,
+                           "scaffold_split",
+                           serde_json::json!({ "train_fraction": train_fraction, "scaffolds_hash": scaffolds_hash }))
+    }
+
+    /// Scaffolds de Bemis–Murcko (SMILES) en el orden de `molecules()`;
+    /// cadena vacía para moléculas acíclicas.
+    #[cfg(feature = "rdkit")]
+    pub fn murcko_scaffolds(&self) -> Result<Vec<String>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        crate::molecule::engine()?.murcko_scaffolds(&smiles)?
+                                  .into_iter()
+                                  .map(|item| item.map_err(DomainError::from))
+                                  .collect()
+    }
+
+    /// R-grupos de cada molécula respecto al primer núcleo de `cores` (SMILES
+    /// o SMARTS) en el que encaja, en el orden de `molecules()`.
+    #[cfg(feature = "rdkit")]
+    pub fn rgroup_decomposition(&self, cores: &[&str]) -> Result<Vec<RGroups>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        crate::molecule::engine()?.rgroup_decomposition(&smiles, cores)?
+                                  .into_iter()
+                                  .map(|item| item.map_err(DomainError::from))
+                                  .collect()
+    }
+
+    /// Bloques MOL (coordenadas 2D) en el orden de `molecules()`, p. ej.
+    /// para exportar la familia a SDF.
+    #[cfg(feature = "rdkit")]
+    pub fn molblocks(&self) -> Result<Vec<String>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        crate::molecule::engine()?.molblocks(&smiles)?
+                                  .into_iter()
+                                  .map(|item| item.map_err(DomainError::from))
+                                  .collect()
+    }
+
+    fn train_size(&self, train_fraction: f64) -> Result<usize, DomainError> {
+        if !(train_fraction > 0.0 && train_fraction < 1.0) {
+            return Err(DomainError::ValidationError(format!("train_fraction debe estar en (0, 1): {train_fraction}")));
+        }
+        if self.molecules.len() < 2 {
+            return Err(DomainError::ValidationError("Se necesitan al menos 2 moléculas para particionar".to_string()));
+        }
+        let n_train = (self.molecules.len() as f64 * train_fraction).round() as usize;
+        Ok(n_train.clamp(1, self.molecules.len() - 1))
+    }
+
+    fn split_by_mask(&self,
+                     in_train: &[bool],
+                     operation: &str,
+                     parameters: serde_json::Value)
+                     -> Result<(Self, Self), DomainError> {
+        let (train, test): (Vec<_>, Vec<_>) = self.molecules.iter().zip(in_train).partition(|(_, &t)| t);
+        let subset = |molecules: Vec<(&Molecule, &bool)>, name: &str| {
+            let mut params = parameters.clone();
+            params["subset"] = serde_json::json!(name);
+            Self::derive(molecules.into_iter().map(|(m, _)| m.clone()).collect(),
+                         operation,
+                         &[self],
+                         params)
+        };
+        Ok((subset(train, "train")?, subset(test, "test")?))
+    }
+
+    /// Nueva familia con las moléculas que contienen la subestructura
+    /// `smarts`, en el orden original, o `None` si ninguna coincide (una
+    /// familia no puede estar vacía). El patrón, su hash SHA-256 y el hash de
+    /// la familia de origen son parámetros de la operación (ver `derive`) y
+    /// por tanto entran en el `family_hash`.
+    ///
+    /// # Errores
+    /// `DomainError::ExternalError` si el SMARTS es inválido.
+    #[cfg(feature = "rdkit")]
+    pub fn filter_by_smarts(&self, smarts: &str) -> Result<Option<Self>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        let matches = crate::molecule::engine()?.matches_smarts_batch(&smiles, smarts)?;
+        let mut selected = Vec::new();
+        for (molecule, matched) in self.molecules.iter().zip(matches) {
+            if matched.map_err(DomainError::from)? {
+                selected.push(molecule.clone());
+            }
+        }
+        if selected.is_empty() {
+            return Ok(None);
+        }
+        Self::derive(selected,
+                     "filter_by_smarts",
+                     &[self],
+                     serde_json::json!({
+                         "smarts": smarts,
+                         "smarts_hash": Self::smarts_hash(smarts),
+                         "parent_family_hash": self.family_hash,
+                     })).map(Some)
+    }
+
+    /// Hash SHA-256 (hex) de un patrón SMARTS, tal como se registra en los
+    /// parámetros de `filter_by_smarts`.
+    pub fn smarts_hash(smarts: &str) -> String {
+        format!("{:x}", Sha256::digest(smarts.as_bytes()))
+    }
+
+    /// Alertas estructurales de `catalogs` para cada molécula, en el orden de
+    /// `molecules()`. Una lista vacía significa que la molécula está limpia.
+    #[cfg(feature = "rdkit")]
+    pub fn structural_alerts(&self, catalogs: &[FilterCatalog]) -> Result<Vec<Vec<StructuralAlert>>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        crate::molecule::engine()?.structural_alerts(&smiles, catalogs)?
+                                  .into_iter()
+                                  .map(|item| item.map_err(DomainError::from))
+                                  .collect()
+    }
+
+    /// Calcula los fingerprints de todas las moléculas en una sola llamada
+    /// al motor, en el orden de `molecules()`.
+    #[cfg(feature = "rdkit")]
+    pub fn fingerprints(&self, kind: &FingerprintKind) -> Result<Vec<Fingerprint>, DomainError> {
+        let smiles: Vec<&str> = self.molecules.iter().map(|m| m.smiles()).collect();
+        crate::molecule::engine()?.fingerprints(&smiles, kind)?
+                                  .into_iter()
+                                  .map(|item| {
+                                      let bits = item.map_err(DomainError::from)?;
+                                      Fingerprint::from_engine(kind.clone(), bits)
+                                  })
+                                  .collect()
+    }
+
+    /// Las `k` moléculas más similares a `query` (ECFP4 + Tanimoto), con su
+    /// similitud, de mayor a menor.
+    #[cfg(feature = "rdkit")]
+    pub fn nearest(&self, query: &Molecule, k: usize) -> Result<Vec<(&Molecule, f64)>, DomainError> {
+        self.nearest_with(query, k, &FingerprintKind::ecfp4(), Similarity::Tanimoto)
+    }
+
+    /// Igual que `nearest` con fingerprint y métrica explícitos.
+    #[cfg(feature = "rdkit")]
+    pub fn nearest_with(&self,
+                        query: &Molecule,
+                        k: usize,
+                        kind: &FingerprintKind,
+                        metric: Similarity)
+                        -> Result<Vec<(&Molecule, f64)>, DomainError> {
+        let query_fp = query.fingerprint(kind)?;
+        let fingerprints = self.fingerprints(kind)?;
+        self.nearest_by_fingerprint(&query_fp, &fingerprints, k, metric)
+    }
+
+    /// Búsqueda de vecinos sobre fingerprints ya calculados (alineados con
+    /// `molecules()`). Empates se resuelven por posición en la familia.
+    pub fn nearest_by_fingerprint(&self,
+                                  query: &Fingerprint,
+                                  fingerprints: &[Fingerprint],
+                                  k: usize,
+                                  metric: Similarity)
+                                  -> Result<Vec<(&Molecule, f64)>, DomainError> {
+        self.check_aligned(fingerprints)?;
+        Ok(nearest_indices(query, fingerprints, k, metric)?.into_iter()
+                                                           .map(|(i, s)| (&self.molecules[i], s))
+                                                           .collect())
+    }
+
+    /// Agrupa la familia con Butina sobre fingerprints ya calculados
+    /// (alineados con `molecules()`). Pensado para tareas batch: el coste es
+    /// cuadrático en el tamaño de la familia.
+    pub fn cluster_by_fingerprint(&self,
+                                  fingerprints: &[Fingerprint],
+                                  threshold: f64,
+                                  metric: Similarity)
+                                  -> Result<Vec<Vec<&Molecule>>, DomainError> {
+        self.check_aligned(fingerprints)?;
+        Ok(butina_clusters(fingerprints, threshold, metric)?.into_iter()
+                                                            .map(|c| c.into_iter().map(|i| &self.molecules[i]).collect())
+                                                            .collect())
+    }
+
+    fn check_aligned(&self, fingerprints: &[Fingerprint]) -> Result<(), DomainError> {
+        if fingerprints.len() != self.molecules.len() {
+            return Err(DomainError::ValidationError(format!("Se esperaban {} fingerprints, se recibieron {}",
+                                                            self.molecules.len(),
+                                                            fingerprints.len())));
+        }
+        Ok(())
+    }
+
     /// Compara si dos familias son equivalentes basándose en su hash
     pub fn is_equivalent(&self, other: &MoleculeFamily) -> bool {
         self.family_hash == other.family_hash
     }
 }
 
+fn empty_object() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// Generador SplitMix64 (Steele et al.); suficiente para barajar de forma
+/// reproducible sin depender de `rand`.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
 // Implementación de IntoIterator para referencia
 impl<'a> IntoIterator for &'a MoleculeFamily {
     type Item = &'a Molecule;
@@ -242,6 +699,7 @@ impl PartialEq for MoleculeFamily {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::FingerprintKind;
     use serde_json::json;
 
     #[test]
@@ -267,6 +725,172 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_nearest_by_fingerprint() -> Result<(), DomainError> {
+        let mols = ["AAAAAAAAAAAAAA-AAAAAAAAAA-N",
+                    "BBBBBBBBBBBBBB-BBBBBBBBBB-N",
+                    "CCCCCCCCCCCCCC-CCCCCCCCCC-N"];
+        let molecules = mols.iter()
+                            .map(|k| Molecule::from_parts(k, "C", "InChI=1S/CH4/h1H4", json!({})))
+                            .collect::<Result<Vec<_>, _>>()?;
+        let family = MoleculeFamily::new(molecules, json!({"source": "test"}))?;
+        let kind = FingerprintKind::ecfp4();
+        let fp = |bits: &[u32]| Fingerprint::from_on_bits(kind.clone(), 2048, bits.iter().copied());
+        let fps = vec![fp(&[1, 2])?, fp(&[1, 2, 3])?, fp(&[9])?];
+
+        let near = family.nearest_by_fingerprint(&fp(&[1, 2, 3])?, &fps, 2, Similarity::Tanimoto)?;
+        assert_eq!(near[0].0.inchikey(), mols[1]);
+        assert_eq!(near[1].0.inchikey(), mols[0]);
+        assert!(family.nearest_by_fingerprint(&fps[0], &fps[..2], 1, Similarity::Dice)
+                      .is_err());
+
+        let clusters = family.cluster_by_fingerprint(&fps, 0.6, Similarity::Tanimoto)?;
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(clusters[0].len(), 2);
+        Ok(())
+    }
+
+    fn family_of(keys: &[&str], provenance: serde_json::Value) -> Result<MoleculeFamily, DomainError> {
+        let molecules = keys.iter()
+                            .map(|k| {
+                                Molecule::from_parts(&format!("{}-{}-N", k.repeat(14), k.repeat(10)),
+                                                     "C",
+                                                     "InChI=1S/CH4/h1H4",
+                                                     json!({}))
+                            })
+                            .collect::<Result<Vec<_>, _>>()?;
+        MoleculeFamily::new(molecules, provenance)
+    }
+
+    fn initials(family: &MoleculeFamily) -> String {
+        family.molecules().iter().map(|m| &m.inchikey()[..1]).collect()
+    }
+
+    #[test]
+    fn test_family_hash_includes_parameters() -> Result<(), DomainError> {
+        let a = family_of(&["A", "B"], json!({"source": "x"}))?;
+        let b = family_of(&["A", "B"], json!({"source": "y"}))?;
+        assert_eq!(a.family_hash(), b.family_hash());
+        let c = MoleculeFamily::new_with_parameters(a.molecules().to_vec(), json!({}), json!({"cutoff": 0.5}))?;
+        assert_ne!(a.family_hash(), c.family_hash());
+        assert!(c.verify_integrity());
+        assert_eq!(c.remove_molecule(c.molecules()[0].inchikey())?.parameters(),
+                   &json!({"cutoff": 0.5}));
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_family_hash_is_still_verified() -> Result<(), DomainError> {
+        let family = family_of(&["A", "B"], json!({"source": "x"}))?;
+        let mut value = serde_json::to_value(&family).unwrap();
+        let object = value.as_object_mut().unwrap();
+        object.remove("parameters");
+        object.remove("identity");
+        object.insert("family_hash".into(),
+                      json!(MoleculeFamily::legacy_family_hash(family.molecules())));
+        let legacy: MoleculeFamily = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(legacy.parameters(), &json!({}));
+        assert!(legacy.verify_integrity());
+
+        // El hash heredado no vale si la familia registra parámetros.
+        value["parameters"] = json!({"cutoff": 0.5});
+        let tampered: MoleculeFamily = serde_json::from_value(value).unwrap();
+        assert!(!tampered.verify_integrity());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_operations() -> Result<(), DomainError> {
+        let a = family_of(&["A", "B", "C"], json!({}))?;
+        let b = family_of(&["C", "D", "B"], json!({}))?;
+
+        let union = a.union(&b)?;
+        assert_eq!(initials(&union), "ABCD");
+        assert_eq!(union.provenance()["parent_family_hashes"],
+                   json!([a.family_hash(), b.family_hash()]));
+        assert_eq!(union.parameters()["operation"], "union");
+        assert!(union.verify_integrity());
+        assert_eq!(initials(&a.intersection(&b)?), "BC");
+        assert_eq!(initials(&a.difference(&b)?), "A");
+        assert!(a.difference(&a).is_err());
+        // Mismas moléculas, distinta construcción: distinto hash.
+        assert_ne!(a.intersection(&a)?.family_hash(), a.family_hash());
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_policies() -> Result<(), DomainError> {
+        let molecule = |key: &str, inchi: &str| Molecule::from_parts(key, "C", inchi, json!({}));
+        let main = "InChI=1S/C3H7NO2/c1-2(4)3(5)6/h2H,4H2,1H3,(H,5,6)";
+        let l_ala = molecule("QNAYBMKLOCPYGJ-REOHCLBHSA-N", &format!("{main}/t2-/m0/s1"))?;
+        let d_ala = molecule("QNAYBMKLOCPYGJ-UWTATZPHSA-N", &format!("{main}/t2-/m1/s1"))?;
+        let ala_13c = molecule("QNAYBMKLOCPYGJ-OUBTZVSYSA-N", &format!("{main}/i1+1"))?;
+
+        let full = MoleculeFamily::new(vec![l_ala.clone(), d_ala.clone(), ala_13c.clone()], json!({}))?;
+        assert!(MoleculeFamily::new_with_identity(vec![l_ala.clone(), d_ala.clone()],
+                                                  json!({}),
+                                                  json!({}),
+                                                  IdentityPolicy::Connectivity).is_err());
+        let isotopes = IdentityPolicy::layers([crate::InchiLayer::Isotope]);
+        let family =
+            MoleculeFamily::new_with_identity(vec![l_ala.clone(), ala_13c.clone()], json!({}), json!({}), isotopes.clone())?;
+        assert!(family.verify_integrity());
+        assert!(family.contains_molecule(&d_ala)?);
+        // Con `Layers` un InChIKey suelto no basta
+        assert!(family.try_contains(d_ala.inchikey()).is_err());
+        assert!(!family.contains(d_ala.inchikey()));
+        assert!(family.contains(l_ala.inchikey()));
+        assert!(family.remove_molecule(l_ala.inchikey()).is_err());
+        assert!(family.add_molecule(d_ala.clone()).is_err());
+        assert!(family.union(&full).is_err());
+
+        // La política entra en el hash (salvo `Full`)
+        let same = MoleculeFamily::new(family.molecules().to_vec(), json!({}))?;
+        assert_ne!(same.family_hash(), family.family_hash());
+        let connectivity =
+            MoleculeFamily::new_with_identity(vec![l_ala.clone()], json!({}), json!({}), IdentityPolicy::Connectivity)?;
+        assert!(connectivity.contains(d_ala.inchikey()));
+        assert!(connectivity.try_contains(d_ala.inchikey())?);
+        let methane = molecule("VNWKTOKETHGBQD-UHFFFAOYSA-N", "InChI=1S/CH4/h1H4")?;
+        let removed = connectivity.add_molecule(methane)?.remove_molecule(d_ala.inchikey())?;
+        assert_eq!(removed.len(), 1);
+        assert!(!removed.contains(l_ala.inchikey()));
+        let other =
+            MoleculeFamily::new_with_identity(vec![d_ala, ala_13c], json!({}), json!({}), IdentityPolicy::Connectivity);
+        assert!(other.is_err());
+        let with_l = MoleculeFamily::new_with_identity(vec![l_ala], json!({}), json!({}), isotopes)?;
+        assert_eq!(family.intersection(&with_l)?.len(), 1);
+        assert_eq!(family.difference(&with_l)?.identity(), family.identity());
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_split_is_seeded() -> Result<(), DomainError> {
+        let family = family_of(&["A", "B", "C", "D", "E", "F", "G", "H", "I", "J"], json!({}))?;
+        let (train, test) = family.split_random(0.8, 42)?;
+        assert_eq!((train.len(), test.len()), (8, 2));
+        let (again, _) = family.split_random(0.8, 42)?;
+        assert_eq!(train.family_hash(), again.family_hash());
+        assert!(test.molecules().iter().all(|m| !train.contains(m.inchikey())));
+        assert_eq!(test.parameters()["subset"], "test");
+        assert_eq!(test.parameters()["seed"], 42);
+        let (other, _) = family.split_random(0.8, 7)?;
+        assert_ne!(train.family_hash(), other.family_hash());
+        assert!(family.split_random(1.0, 42).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scaffold_split_keeps_groups_together() -> Result<(), DomainError> {
+        let family = family_of(&["A", "B", "C", "D", "E"], json!({}))?;
+        let scaffolds: Vec<String> = ["x", "y", "x", "z", "x"].iter().map(|s| s.to_string()).collect();
+        let (train, test) = family.split_by_scaffold_keys(&scaffolds, 0.8)?;
+        assert_eq!(initials(&train), "ABCE");
+        assert_eq!(initials(&test), "D");
+        assert!(family.split_by_scaffold_keys(&scaffolds[..2], 0.8).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_molecule_family_empty() {
         let provenance = json!({"source": "test"});
@@ -0,0 +1,564 @@
+// owned_property.rs
+//
+// Variantes "owned" de `MolecularProperty` y `FamilyProperty`: referencian
+// la molécula por InChIKey y la familia por `family_hash` en lugar de tomar
+// prestado el objeto, de modo que pueden almacenarse, enviarse entre hilos,
+// deserializarse desde la base de datos o viajar dentro de artifacts.
+// Campos según Requerimientos §3.4 (units, provider/version, step_id,
+// quality, preferred, value_hash).
+use crate::{DomainError, FamilyProperty, MolecularProperty, Molecule, MoleculeFamily, Unit};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use uuid::Uuid;
+
+/// Proveniencia exacta de un valor: proveedor y versión que lo calcularon o
+/// midieron.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PropertyProvider {
+    pub name: String,
+    pub version: String,
+}
+
+impl PropertyProvider {
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Result<Self, DomainError> {
+        let provider = Self { name: name.into(),
+                              version: version.into() };
+        if provider.name.trim().is_empty() || provider.version.trim().is_empty() {
+            return Err(DomainError::ValidationError("El proveedor requiere nombre y versión".to_string()));
+        }
+        Ok(provider)
+    }
+}
+
+/// Hash de un valor de propiedad: JSON canónico (claves ordenadas) de
+/// sujeto (InChIKey o family_hash), tipo, valor, unidades, proveedor y
+/// metadatos. `step_id`, `quality` y `preferred` no forman parte del valor.
+fn compute_value_hash<V: Serialize, M: Serialize>(subject: &str,
+                                                  property_type: &str,
+                                                  value: &V,
+                                                  units: Option<&str>,
+                                                  provider: &PropertyProvider,
+                                                  metadata: &M)
+                                                  -> Result<String, DomainError> {
+    let canonical = serde_json::json!({
+        "subject": subject,
+        "property_type": property_type,
+        "value": serde_json::to_value(value)?,
+        "units": units,
+        "provider": provider,
+        "metadata": serde_json::to_value(metadata)?,
+    });
+    Ok(format!("{:x}", Sha256::digest(canonical.to_string().as_bytes())))
+}
+
+fn registered_symbol(units: Option<&str>) -> Result<Option<String>, DomainError> {
+    units.map(|u| Unit::parse(u).map(|unit| unit.symbol().to_string()))
+         .transpose()
+}
+
+fn validate_fields(subject: &str, subject_name: &str, property_type: &str) -> Result<(), DomainError> {
+    if subject.trim().is_empty() {
+        return Err(DomainError::ValidationError(format!("{subject_name} no puede estar vacío")));
+    }
+    if property_type.trim().is_empty() {
+        return Err(DomainError::ValidationError("El tipo de propiedad no puede estar vacío".to_string()));
+    }
+    Ok(())
+}
+
+/// Propiedad molecular sin préstamos: la molécula se referencia por
+/// InChIKey. Serializable e inmutable; los `with_*` crean una nueva
+/// instancia (solo `with_units` altera el `value_hash`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedMolecularProperty<TypeValue, TypeMetaData> {
+    id: Uuid,
+    molecule_inchikey: String,
+    property_type: String,
+    value: TypeValue,
+    units: Option<String>,
+    provider: PropertyProvider,
+    step_id: Option<String>,
+    quality: Option<String>,
+    preferred: bool,
+    value_hash: String,
+    metadata: TypeMetaData,
+}
+
+impl<TypeValue, TypeMetaData> OwnedMolecularProperty<TypeValue, TypeMetaData>
+    where TypeValue: Serialize + Clone,
+          TypeMetaData: Serialize + Clone
+{
+    /// Crea una propiedad para la molécula con InChIKey `molecule_inchikey`
+    /// (normalizado a mayúsculas), sin unidades, calidad ni step.
+    pub fn new(molecule_inchikey: &str,
+               property_type: &str,
+               value: TypeValue,
+               provider: PropertyProvider,
+               metadata: TypeMetaData)
+               -> Result<Self, DomainError> {
+        validate_fields(molecule_inchikey, "El InChIKey", property_type)?;
+        let mut property = Self { id: Uuid::new_v4(),
+                                  molecule_inchikey: molecule_inchikey.to_uppercase(),
+                                  property_type: property_type.to_string(),
+                                  value,
+                                  units: None,
+                                  provider,
+                                  step_id: None,
+                                  quality: None,
+                                  preferred: false,
+                                  value_hash: String::new(),
+                                  metadata };
+        property.value_hash = property.calculate_hash()?;
+        Ok(property)
+    }
+
+    fn calculate_hash(&self) -> Result<String, DomainError> {
+        compute_value_hash(&self.molecule_inchikey,
+                           &self.property_type,
+                           &self.value,
+                           self.units.as_deref(),
+                           &self.provider,
+                           &self.metadata)
+    }
+
+    /// Nueva instancia con las unidades indicadas (forman parte del hash).
+    /// Deben estar registradas en `units`; se guarda su símbolo (los alias
+    /// como `A^2` quedan como `Å²`).
+    pub fn with_units(&self, units: Option<&str>) -> Result<Self, DomainError> {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.units = registered_symbol(units)?;
+        property.value_hash = property.calculate_hash()?;
+        Ok(property)
+    }
+
+    /// Nueva instancia con el step que produjo el valor.
+    pub fn with_step_id(&self, step_id: Option<&str>) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.step_id = step_id.map(str::to_string);
+        property
+    }
+
+    /// Nueva instancia con calidad modificada
+    pub fn with_quality(&self, quality: Option<String>) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.quality = quality;
+        property
+    }
+
+    /// Nueva instancia con el flag 'preferred' modificado
+    pub fn with_preferred(&self, preferred: bool) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.preferred = preferred;
+        property
+    }
+
+    /// Reconstruye la variante prestada sobre `molecule`, que debe tener el
+    /// mismo InChIKey. El `value_hash` de `MolecularProperty` no incluye
+    /// unidades ni proveedor, por lo que puede diferir del de esta instancia.
+    pub fn resolve<'a>(&self,
+                       molecule: &'a Molecule)
+                       -> Result<MolecularProperty<'a, TypeValue, TypeMetaData>, DomainError> {
+        if molecule.inchikey() != self.molecule_inchikey {
+            return Err(DomainError::ValidationError(format!("La propiedad pertenece a {}, no a {}",
+                                                            self.molecule_inchikey,
+                                                            molecule.inchikey())));
+        }
+        MolecularProperty::new(molecule,
+                               &self.property_type,
+                               self.value.clone(),
+                               self.quality.clone(),
+                               self.preferred,
+                               self.metadata.clone())
+    }
+
+    /// Verifica la integridad recalculando el hash (útil tras deserializar).
+    pub fn verify_integrity(&self) -> Result<bool, DomainError> {
+        Ok(self.calculate_hash()? == self.value_hash)
+    }
+
+    /// Obtiene el ID único de la propiedad
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// InChIKey de la molécula asociada
+    pub fn molecule_inchikey(&self) -> &str {
+        &self.molecule_inchikey
+    }
+
+    /// Obtiene el tipo de propiedad (ej: "logP", "polar_surface_area")
+    pub fn property_type(&self) -> &str {
+        &self.property_type
+    }
+
+    /// Obtiene el valor de la propiedad
+    pub fn value(&self) -> &TypeValue {
+        &self.value
+    }
+
+    /// Unidades del valor, si aplica
+    pub fn units(&self) -> Option<&str> {
+        self.units.as_deref()
+    }
+
+    /// Proveedor y versión que produjeron el valor
+    pub fn provider(&self) -> &PropertyProvider {
+        &self.provider
+    }
+
+    /// Step que produjo el valor, si se conoce
+    pub fn step_id(&self) -> Option<&str> {
+        self.step_id.as_deref()
+    }
+
+    /// Obtiene la calidad de la propiedad si está disponible
+    pub fn quality(&self) -> Option<&String> {
+        self.quality.as_ref()
+    }
+
+    /// Indica si esta es la propiedad preferida entre varias del mismo tipo
+    pub fn preferred(&self) -> bool {
+        self.preferred
+    }
+
+    /// Obtiene el hash único que identifica este valor
+    pub fn value_hash(&self) -> &str {
+        &self.value_hash
+    }
+
+    /// Obtiene los metadatos específicos del tipo de propiedad
+    pub fn metadata(&self) -> &TypeMetaData {
+        &self.metadata
+    }
+}
+
+/// Propiedad de familia sin préstamos: la familia se referencia por
+/// `family_hash`. Mismas reglas que `OwnedMolecularProperty`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnedFamilyProperty<ValueType, TypeMeta> {
+    id: Uuid,
+    family_hash: String,
+    property_type: String,
+    value: ValueType,
+    units: Option<String>,
+    provider: PropertyProvider,
+    step_id: Option<String>,
+    quality: Option<String>,
+    preferred: bool,
+    value_hash: String,
+    metadata: TypeMeta,
+}
+
+impl<ValueType, TypeMeta> OwnedFamilyProperty<ValueType, TypeMeta>
+    where ValueType: Serialize + Clone,
+          TypeMeta: Serialize + Clone
+{
+    /// Crea una propiedad para la familia con hash `family_hash`, sin
+    /// unidades, calidad ni step.
+    pub fn new(family_hash: &str,
+               property_type: &str,
+               value: ValueType,
+               provider: PropertyProvider,
+               metadata: TypeMeta)
+               -> Result<Self, DomainError> {
+        validate_fields(family_hash, "El family_hash", property_type)?;
+        let mut property = Self { id: Uuid::new_v4(),
+                                  family_hash: family_hash.to_string(),
+                                  property_type: property_type.to_string(),
+                                  value,
+                                  units: None,
+                                  provider,
+                                  step_id: None,
+                                  quality: None,
+                                  preferred: false,
+                                  value_hash: String::new(),
+                                  metadata };
+        property.value_hash = property.calculate_hash()?;
+        Ok(property)
+    }
+
+    fn calculate_hash(&self) -> Result<String, DomainError> {
+        compute_value_hash(&self.family_hash,
+                           &self.property_type,
+                           &self.value,
+                           self.units.as_deref(),
+                           &self.provider,
+                           &self.metadata)
+    }
+
+    /// Nueva instancia con las unidades indicadas (forman parte del hash).
+    /// Deben estar registradas en `units`; se guarda su símbolo (los alias
+    /// como `A^2` quedan como `Å²`).
+    pub fn with_units(&self, units: Option<&str>) -> Result<Self, DomainError> {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.units = registered_symbol(units)?;
+        property.value_hash = property.calculate_hash()?;
+        Ok(property)
+    }
+
+    /// Nueva instancia con el step que produjo el valor.
+    pub fn with_step_id(&self, step_id: Option<&str>) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.step_id = step_id.map(str::to_string);
+        property
+    }
+
+    /// Nueva instancia con calidad modificada
+    pub fn with_quality(&self, quality: Option<String>) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.quality = quality;
+        property
+    }
+
+    /// Nueva instancia con el flag 'preferred' modificado
+    pub fn with_preferred(&self, preferred: bool) -> Self {
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.preferred = preferred;
+        property
+    }
+
+    /// Reconstruye la variante prestada sobre `family`, que debe tener el
+    /// mismo `family_hash`.
+    pub fn resolve<'a>(&self, family: &'a MoleculeFamily) -> Result<FamilyProperty<'a, ValueType, TypeMeta>, DomainError> {
+        if family.family_hash() != self.family_hash {
+            return Err(DomainError::ValidationError(format!("La propiedad pertenece a la familia {}, no a {}",
+                                                            self.family_hash,
+                                                            family.family_hash())));
+        }
+        FamilyProperty::new(family,
+                            &self.property_type,
+                            self.value.clone(),
+                            self.quality.clone(),
+                            self.preferred,
+                            self.metadata.clone())
+    }
+
+    /// Verifica la integridad recalculando el hash (útil tras deserializar).
+    pub fn verify_integrity(&self) -> Result<bool, DomainError> {
+        Ok(self.calculate_hash()? == self.value_hash)
+    }
+
+    /// Obtiene el ID único de la propiedad
+    pub fn id(&self) -> &Uuid {
+        &self.id
+    }
+
+    /// Hash de la familia asociada
+    pub fn family_hash(&self) -> &str {
+        &self.family_hash
+    }
+
+    /// Obtiene el tipo de propiedad
+    pub fn property_type(&self) -> &str {
+        &self.property_type
+    }
+
+    /// Obtiene el valor de la propiedad
+    pub fn value(&self) -> &ValueType {
+        &self.value
+    }
+
+    /// Unidades del valor, si aplica
+    pub fn units(&self) -> Option<&str> {
+        self.units.as_deref()
+    }
+
+    /// Proveedor y versión que produjeron el valor
+    pub fn provider(&self) -> &PropertyProvider {
+        &self.provider
+    }
+
+    /// Step que produjo el valor, si se conoce
+    pub fn step_id(&self) -> Option<&str> {
+        self.step_id.as_deref()
+    }
+
+    /// Obtiene la calidad de la propiedad si está disponible
+    pub fn quality(&self) -> Option<&String> {
+        self.quality.as_ref()
+    }
+
+    /// Indica si esta es la propiedad preferida entre varias del mismo tipo
+    pub fn preferred(&self) -> bool {
+        self.preferred
+    }
+
+    /// Obtiene el hash único que identifica este valor
+    pub fn value_hash(&self) -> &str {
+        &self.value_hash
+    }
+
+    /// Obtiene los metadatos específicos del tipo de propiedad
+    pub fn metadata(&self) -> &TypeMeta {
+        &self.metadata
+    }
+}
+
+impl<TypeMetaData> OwnedMolecularProperty<f64, TypeMetaData> where TypeMetaData: Serialize + Clone
+{
+    /// Nueva instancia con el valor expresado en la unidad canónica de su
+    /// dimensión (p. ej. kcal/mol → kJ/mol). Sin unidades se devuelve igual.
+    pub fn to_canonical_units(&self) -> Result<Self, DomainError> {
+        let Some(units) = &self.units else {
+            return Ok(self.clone());
+        };
+        let unit = Unit::parse(units)?;
+        let mut property = self.clone();
+        property.id = Uuid::new_v4();
+        property.value = unit.to_canonical(self.value);
+        property.units = Some(unit.canonical().symbol().to_string());
+        property.value_hash = property.calculate_hash()?;
+        Ok(property)
+    }
+}
+
+impl<'a, TypeValue, TypeMetaData> MolecularProperty<'a, TypeValue, TypeMetaData>
+    where TypeValue: Serialize + Clone,
+          TypeMetaData: Serialize + Clone
+{
+    /// Variante owned (referencia por InChIKey) con proveedor y unidades.
+    pub fn to_owned_property(&self,
+                             provider: PropertyProvider,
+                             units: Option<&str>)
+                             -> Result<OwnedMolecularProperty<TypeValue, TypeMetaData>, DomainError> {
+        let owned = OwnedMolecularProperty::new(self.molecule().inchikey(),
+                                                self.property_type(),
+                                                self.value().clone(),
+                                                provider,
+                                                self.metadata().clone())?;
+        Ok(owned.with_units(units)?
+                .with_quality(self.quality().cloned())
+                .with_preferred(self.preferred()))
+    }
+}
+
+impl<'a, ValueType, TypeMeta> FamilyProperty<'a, ValueType, TypeMeta>
+    where ValueType: Serialize + Clone,
+          TypeMeta: Serialize + Clone
+{
+    /// Variante owned (referencia por `family_hash`) con proveedor y
+    /// unidades.
+    pub fn to_owned_property(&self,
+                             provider: PropertyProvider,
+                             units: Option<&str>)
+                             -> Result<OwnedFamilyProperty<ValueType, TypeMeta>, DomainError> {
+        let owned = OwnedFamilyProperty::new(self.family().family_hash(),
+                                             self.property_type(),
+                                             self.value().clone(),
+                                             provider,
+                                             self.metadata().clone())?;
+        Ok(owned.with_units(units)?
+                .with_quality(self.quality().cloned())
+                .with_preferred(self.preferred()))
+    }
+}
+
+// Implementación de Display para formato legible
+impl<TypeValue, TypeMetaData> fmt::Display for OwnedMolecularProperty<TypeValue, TypeMetaData> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+               "OwnedMolecularProperty(molecule: {}, type: {}, provider: {}@{})",
+               self.molecule_inchikey, self.property_type, self.provider.name, self.provider.version)
+    }
+}
+
+impl<ValueType, TypeMeta> fmt::Display for OwnedFamilyProperty<ValueType, TypeMeta> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f,
+               "OwnedFamilyProperty(family: {}, type: {}, provider: {}@{})",
+               self.family_hash, self.property_type, self.provider.name, self.provider.version)
+    }
+}
+
+// Igualdad basada en el hash de valor
+impl<TypeValue, TypeMetaData> PartialEq for OwnedMolecularProperty<TypeValue, TypeMetaData> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value_hash == other.value_hash
+    }
+}
+
+impl<ValueType, TypeMeta> PartialEq for OwnedFamilyProperty<ValueType, TypeMeta> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value_hash == other.value_hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ethanol() -> Molecule {
+        Molecule::from_parts("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                             "CCO",
+                             "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
+                             json!({})).unwrap()
+    }
+
+    #[test]
+    fn test_owned_molecular_property_round_trip() -> Result<(), DomainError> {
+        let molecule = ethanol();
+        let borrowed = MolecularProperty::new(&molecule, "logP", -0.31, Some("high".to_string()), true, json!({}))?;
+        let owned = borrowed.to_owned_property(PropertyProvider::new("rdkit", "2024.03")?, None)?;
+        assert_eq!(owned.molecule_inchikey(), molecule.inchikey());
+        assert!(owned.preferred());
+
+        let bytes = serde_json::to_vec(&owned)?;
+        let back: OwnedMolecularProperty<f64, serde_json::Value> = serde_json::from_slice(&bytes)?;
+        assert_eq!(back, owned);
+        assert!(back.verify_integrity()?);
+        assert_eq!(back.resolve(&molecule)?.value(), &-0.31);
+
+        // Unidades y proveedor forman parte de la identidad del valor.
+        assert_ne!(owned.with_units(Some("log"))?, owned);
+        let other = OwnedMolecularProperty::new(molecule.inchikey(),
+                                                "logP",
+                                                -0.31,
+                                                PropertyProvider::new("rdkit", "2025.09")?,
+                                                json!({}))?;
+        assert_ne!(other.value_hash(), owned.value_hash());
+        // `step_id`/`quality` no alteran el valor.
+        assert_eq!(owned.with_step_id(Some("compute")).value_hash(), owned.value_hash());
+        assert!(owned.with_units(Some("parsecs")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_canonical_units_make_providers_comparable() -> Result<(), DomainError> {
+        let provider = PropertyProvider::new("xtb", "6.6")?;
+        let kcal = OwnedMolecularProperty::new("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                                               "hydration_energy",
+                                               -1.0,
+                                               provider.clone(),
+                                               ())?.with_units(Some("kcal/mol"))?;
+        let kj = OwnedMolecularProperty::new("LFQSCWFLJHTTHZ-UHFFFAOYSA-N", "hydration_energy", -4.184, provider, ())?
+            .with_units(Some("kJ mol-1"))?;
+        assert_ne!(kcal, kj);
+        assert_eq!(kcal.to_canonical_units()?, kj.to_canonical_units()?);
+        assert_eq!(kj.units(), Some("kJ/mol"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_owned_family_property_resolve() -> Result<(), DomainError> {
+        let family = MoleculeFamily::new(vec![ethanol()], json!({"source": "test"}))?;
+        let owned = OwnedFamilyProperty::new(family.family_hash(),
+                                             "average_logP",
+                                             2.5,
+                                             PropertyProvider::new("chemflow", "1")?,
+                                             json!({}))?;
+        let back: OwnedFamilyProperty<f64, serde_json::Value> = serde_json::from_str(&serde_json::to_string(&owned)?)?;
+        assert_eq!(back.resolve(&family)?.value(), &2.5);
+        assert!(OwnedFamilyProperty::new("", "x", 1, PropertyProvider::new("a", "1")?, ()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+// reaction.rs
+//! Transformaciones: familias nuevas derivadas de otras (Requerimientos §4,
+//! `TransformationProvider`). La primera implementación es la enumeración
+//! por SMARTS de reacción (acoplamiento de amidas, bibliotecas
+//! combinatorias a partir de listas de reactivos).
+
+#[cfg(feature = "rdkit")]
+use std::collections::HashSet;
+
+#[cfg(feature = "rdkit")]
+use chemengine::EnumerationLimits;
+#[cfg(feature = "rdkit")]
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "rdkit")]
+use crate::molecule::engine;
+#[cfg(feature = "rdkit")]
+use crate::{toolkit_versions, Molecule};
+use crate::{DomainError, MoleculeFamily};
+
+/// Proveedor de transformaciones: a partir de una o varias familias produce
+/// una familia nueva cuya provenance registra la operación.
+pub trait TransformationProvider {
+    /// Nombre estable del proveedor.
+    fn name(&self) -> &str;
+
+    /// Versión del proveedor.
+    fn version(&self) -> &str;
+
+    /// Comprueba los parámetros y el número de familias sin calcular nada.
+    fn validate(&self, inputs: &[&MoleculeFamily]) -> Result<(), DomainError>;
+
+    /// Aplica la transformación.
+    fn transform(&self, inputs: &[&MoleculeFamily]) -> Result<MoleculeFamily, DomainError>;
+}
+
+/// Enumeración de productos de un SMARTS de reacción con RDKit. Cada familia
+/// de entrada aporta los reactivos de una plantilla, en orden.
+#[cfg(feature = "rdkit")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReactionEnumerator {
+    reaction: String,
+    limits: EnumerationLimits,
+}
+
+/// Resultado de una enumeración. `reagents[i]` son los InChIKeys de los
+/// reactivos (uno por plantilla) del producto `family.molecules()[i]`.
+#[cfg(feature = "rdkit")]
+#[derive(Debug, Clone)]
+pub struct ReactionEnumeration {
+    pub family: MoleculeFamily,
+    pub reagents: Vec<Vec<String>>,
+    /// Productos descartados por repetir un InChIKey ya enumerado.
+    pub n_duplicates: usize,
+    /// Productos que no superaron la sanitización.
+    pub n_failed: usize,
+    /// Se alcanzó algún límite de `EnumerationLimits`.
+    pub truncated: bool,
+}
+
+#[cfg(feature = "rdkit")]
+impl ReactionEnumerator {
+    pub const NAME: &'static str = "rdkit_reaction";
+    pub const VERSION: &'static str = "1";
+
+    /// # Errores
+    /// `DomainError::ValidationError` si `reaction` no tiene la forma
+    /// `reactivos>>productos` o algún límite es 0.
+    pub fn new(reaction: impl Into<String>, limits: EnumerationLimits) -> Result<Self, DomainError> {
+        let reaction = reaction.into().trim().to_string();
+        if reaction.matches(">>").count() != 1 || reaction.starts_with(">>") || reaction.ends_with(">>") {
+            return Err(DomainError::ValidationError(format!("SMARTS de reacción inválido: '{reaction}'")));
+        }
+        if limits.max_products == 0 || limits.max_products_per_combination == 0 {
+            return Err(DomainError::ValidationError("Los límites de enumeración deben ser mayores que 0".to_string()));
+        }
+        Ok(Self { reaction, limits })
+    }
+
+    pub fn reaction(&self) -> &str {
+        &self.reaction
+    }
+
+    pub fn limits(&self) -> &EnumerationLimits {
+        &self.limits
+    }
+
+    /// Número de plantillas de reactivo del SMARTS (componentes separados por
+    /// `.` fuera de paréntesis).
+    pub fn n_reactants(&self) -> usize {
+        let reactants = self.reaction.split(">>").next().unwrap_or_default();
+        let mut depth = 0i32;
+        let mut count = 1;
+        for c in reactants.chars() {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                '.' if depth == 0 => count += 1,
+                _ => {}
+            }
+        }
+        count
+    }
+
+    /// Hash SHA-256 (hex) del SMARTS de reacción.
+    pub fn reaction_hash(&self) -> String {
+        format!("{:x}", Sha256::digest(self.reaction.as_bytes()))
+    }
+
+    /// Enumera los productos sobre todas las combinaciones de reactivos. El
+    /// orden es el de la enumeración (combinaciones en orden lexicográfico
+    /// de las familias); los productos se deduplican por InChIKey
+    /// conservando la primera aparición. Los parámetros (reacción, hashes de
+    /// las familias de reactivos y límites) entran en el `family_hash`.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si el número de familias no coincide
+    /// con las plantillas o no se obtiene ningún producto.
+    pub fn enumerate(&self, reagents: &[&MoleculeFamily]) -> Result<ReactionEnumeration, DomainError> {
+        self.validate(reagents)?;
+        let smiles: Vec<Vec<&str>> = reagents.iter()
+                                             .map(|f| f.molecules().iter().map(|m| m.smiles()).collect())
+                                             .collect();
+        let enumeration = engine()?.enumerate_reaction(&self.reaction, &smiles, &self.limits)?;
+        let product_smiles: Vec<&str> = enumeration.products.iter().map(|p| p.smiles.as_str()).collect();
+        let toolkit = serde_json::json!(toolkit_versions()?);
+
+        let mut seen = HashSet::new();
+        let mut molecules = Vec::new();
+        let mut origins = Vec::new();
+        let mut n_duplicates = 0;
+        let mut n_failed = enumeration.n_failed;
+        for (product, molecule) in enumeration.products.iter().zip(Molecule::from_smiles_batch(&product_smiles)?) {
+            let Ok(molecule) = molecule else {
+                n_failed += 1;
+                continue;
+            };
+            if !seen.insert(molecule.inchikey().to_string()) {
+                n_duplicates += 1;
+                continue;
+            }
+            let used: Vec<String> = product.reagents
+                                           .iter()
+                                           .zip(reagents)
+                                           .map(|(&i, family)| family.molecules()[i].inchikey().to_string())
+                                           .collect();
+            let metadata = serde_json::json!({
+                "source": "reaction_enumeration",
+                "reaction_hash": self.reaction_hash(),
+                "reagents": used,
+                "toolkit": toolkit,
+            });
+            molecules.push(Molecule::from_parts(molecule.inchikey(), molecule.smiles(), molecule.inchi(), metadata)?);
+            origins.push(used);
+        }
+        if molecules.is_empty() {
+            return Err(DomainError::ValidationError(format!("La reacción no produce ningún producto: {}", self.reaction)));
+        }
+
+        let reagent_hashes: Vec<&str> = reagents.iter().map(|f| f.family_hash()).collect();
+        let parameters = serde_json::json!({
+            "operation": "reaction_enumeration",
+            "reaction": self.reaction,
+            "reagent_family_hashes": reagent_hashes,
+            "limits": self.limits,
+        });
+        let provenance = serde_json::json!({
+            "operation": "reaction_enumeration",
+            "provider": { "name": Self::NAME, "version": Self::VERSION },
+            "reaction": self.reaction,
+            "reaction_hash": self.reaction_hash(),
+            "reagent_family_hashes": reagent_hashes,
+            "limits": self.limits,
+            "n_products": molecules.len(),
+            "n_duplicates": n_duplicates,
+            "n_failed": n_failed,
+            "truncated": enumeration.truncated,
+            "toolkit": toolkit,
+        });
+        Ok(ReactionEnumeration { family: MoleculeFamily::new_with_parameters(molecules, provenance, parameters)?,
+                                 reagents: origins,
+                                 n_duplicates,
+                                 n_failed,
+                                 truncated: enumeration.truncated })
+    }
+}
+
+#[cfg(feature = "rdkit")]
+impl TransformationProvider for ReactionEnumerator {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn version(&self) -> &str {
+        Self::VERSION
+    }
+
+    fn validate(&self, inputs: &[&MoleculeFamily]) -> Result<(), DomainError> {
+        let expected = self.n_reactants();
+        if inputs.len() != expected {
+            return Err(DomainError::ValidationError(format!("La reacción necesita {expected} familias de reactivos y se dieron {}",
+                                                            inputs.len())));
+        }
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &[&MoleculeFamily]) -> Result<MoleculeFamily, DomainError> {
+        Ok(self.enumerate(inputs)?.family)
+    }
+}
+
+#[cfg(all(test, feature = "rdkit"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reaction_validation() {
+        let amide =
+            ReactionEnumerator::new(" [C:1](=O)[OH].[N;!H0:2]>>[C:1](=O)[N:2] ", EnumerationLimits::default()).unwrap();
+        assert_eq!(amide.reaction(), "[C:1](=O)[OH].[N;!H0:2]>>[C:1](=O)[N:2]");
+        assert_eq!(amide.n_reactants(), 2);
+        assert_eq!(ReactionEnumerator::new("C(.C)>>CC", EnumerationLimits::default()).unwrap()
+                                                                                     .n_reactants(),
+                   1);
+
+        assert!(ReactionEnumerator::new("CCO", EnumerationLimits::default()).is_err());
+        assert!(ReactionEnumerator::new(">>CC", EnumerationLimits::default()).is_err());
+        let zero = EnumerationLimits { max_products: 0,
+                                       ..Default::default() };
+        assert!(ReactionEnumerator::new("CC>>CC", zero).is_err());
+
+        let family = MoleculeFamily::new(vec![Molecule::from_parts("LFQSCWFLJHTTHZ-UHFFFAOYSA-N",
+                                                                   "CCO",
+                                                                   "InChI=1S/C2H6O/c1-2-3/h3H,2H2,1H3",
+                                                                   serde_json::json!({})).unwrap()],
+                                         serde_json::json!({})).unwrap();
+        let err = amide.validate(&[&family]).unwrap_err();
+        assert!(err.to_string().contains("2 familias"));
+    }
+}
@@ -0,0 +1,432 @@
+// smiles.rs
+//! Lectura de SMILES en Rust puro (backend `rust`) sobre el crate `purr`.
+//!
+//! `purr` valida la sintaxis OpenSMILES completa (átomos orgánicos y entre
+//! corchetes, `*`, isótopos, carga, quiralidad, enlaces `- = # $ : / \`,
+//! ramas, cierres de anillo y componentes separados por `.`) y construye el
+//! grafo. Sobre ese grafo este módulo sólo calcula hidrógenos implícitos,
+//! fórmula, masa y número de átomos; el SMILES se conserva tal cual.
+//!
+//! Límites conocidos:
+//! - No hay canonicalización ni normalización: el SMILES y la clave son los de
+//!   la entrada tal como se escribió, así que `CCO` y `OCC` dan claves
+//!   distintas. La identidad canónica de una molécula sólo la da RDKit
+//!   (InChIKey).
+//! - La valencia sólo se comprueba contra los objetivos de OpenSMILES
+//!   (subconjunto orgánico y corchetes de B, C, N, O, P, S, As, Se); los
+//!   sistemas aromáticos no se kekulizan ni se validan.
+//! - La estereoquímica (`@`, `@@`, `/`, `\`) se conserva tal como se escribió,
+//!   sin interpretarse; las clases no tetraédricas (`@TB`, `@OH`…) se rechazan.
+//! - La masa es la media de cada elemento e ignora el isótopo; `*` no aporta
+//!   masa. Los identificadores no son InChI: ver `RUST_IDENTIFIER_PREFIX`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chemengine::{ItemErrorKind, SanitizationIssue};
+use purr::feature::{AtomKind, BracketSymbol, Configuration, Element};
+use purr::graph::{Atom, Builder};
+use purr::read::Trace;
+use purr::walk::Follower;
+use sha2::{Digest, Sha256};
+
+/// Prefijo del identificador que ocupa el lugar del InChI en las moléculas
+/// del backend `rust` (`chemflow-rs=1/<fórmula>/<SMILES de entrada>`).
+pub const RUST_IDENTIFIER_PREFIX: &str = "chemflow-rs=1/";
+
+/// Versión del backend `rust`. Entra en las versiones del toolkit
+/// (`ToolkitVersions::rust`), así que hay que incrementarla con cualquier
+/// cambio que altere la fórmula o el `identity_key` de alguna
+/// molécula; con ella cambian los fingerprints de los steps químicos.
+pub const RUST_BACKEND_VERSION: u32 = 3;
+
+/// Error de lectura con la posición (en bytes, desde 0) donde se detectó.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmilesError {
+    pub position: usize,
+    pub message: String,
+    /// Motivo si la sintaxis es correcta pero la molécula no supera la
+    /// comprobación de valencia, como lo reporta RDKit.
+    pub sanitization: Option<SanitizationIssue>,
+}
+
+impl SmilesError {
+    /// Tipo equivalente al de los errores por ítem del motor RDKit.
+    pub fn kind(&self) -> ItemErrorKind {
+        match self.sanitization {
+            Some(issue) => ItemErrorKind::Sanitization(issue),
+            None => ItemErrorKind::InvalidSmiles { position: Some(self.position) },
+        }
+    }
+}
+
+impl fmt::Display for SmilesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (posición {})", self.message, self.position)
+    }
+}
+
+impl std::error::Error for SmilesError {}
+
+fn error(position: usize, message: impl Into<String>) -> SmilesError {
+    SmilesError { position,
+                  message: message.into(),
+                  sanitization: None }
+}
+
+/// Masas atómicas medias (g/mol) de los elementos admitidos.
+static WEIGHTS: &[(&str, f64)] = &[("H", 1.008),
+                                   ("He", 4.003),
+                                   ("Li", 6.94),
+                                   ("Be", 9.012),
+                                   ("B", 10.81),
+                                   ("C", 12.011),
+                                   ("N", 14.007),
+                                   ("O", 15.999),
+                                   ("F", 18.998),
+                                   ("Ne", 20.180),
+                                   ("Na", 22.990),
+                                   ("Mg", 24.305),
+                                   ("Al", 26.982),
+                                   ("Si", 28.085),
+                                   ("P", 30.974),
+                                   ("S", 32.06),
+                                   ("Cl", 35.45),
+                                   ("Ar", 39.95),
+                                   ("K", 39.098),
+                                   ("Ca", 40.078),
+                                   ("Ti", 47.867),
+                                   ("V", 50.942),
+                                   ("Cr", 51.996),
+                                   ("Mn", 54.938),
+                                   ("Fe", 55.845),
+                                   ("Co", 58.933),
+                                   ("Ni", 58.693),
+                                   ("Cu", 63.546),
+                                   ("Zn", 65.38),
+                                   ("Ga", 69.723),
+                                   ("Ge", 72.630),
+                                   ("As", 74.922),
+                                   ("Se", 78.971),
+                                   ("Br", 79.904),
+                                   ("Kr", 83.798),
+                                   ("Rb", 85.468),
+                                   ("Sr", 87.62),
+                                   ("Zr", 91.224),
+                                   ("Mo", 95.95),
+                                   ("Ru", 101.07),
+                                   ("Rh", 102.91),
+                                   ("Pd", 106.42),
+                                   ("Ag", 107.87),
+                                   ("Cd", 112.41),
+                                   ("Sn", 118.71),
+                                   ("Sb", 121.76),
+                                   ("Te", 127.60),
+                                   ("I", 126.90),
+                                   ("Xe", 131.29),
+                                   ("Cs", 132.91),
+                                   ("Ba", 137.33),
+                                   ("Gd", 157.25),
+                                   ("W", 183.84),
+                                   ("Os", 190.23),
+                                   ("Ir", 192.22),
+                                   ("Pt", 195.08),
+                                   ("Au", 196.97),
+                                   ("Hg", 200.59),
+                                   ("Tl", 204.38),
+                                   ("Pb", 207.2),
+                                   ("Bi", 208.98)];
+
+fn weight(symbol: &str) -> Option<f64> {
+    WEIGHTS.iter().find(|(s, _)| *s == symbol).map(|(_, w)| *w)
+}
+
+/// Símbolo del elemento con mayúscula inicial (`*` para el comodín).
+fn symbol(kind: &AtomKind) -> String {
+    match kind {
+        AtomKind::Star => "*".to_string(),
+        AtomKind::Aliphatic(aliphatic) => aliphatic.to_string(),
+        AtomKind::Aromatic(aromatic) => {
+            let aliphatic: purr::feature::Aliphatic = aromatic.into();
+            aliphatic.to_string()
+        }
+        AtomKind::Bracket { symbol, .. } => match symbol {
+            BracketSymbol::Star => "*".to_string(),
+            BracketSymbol::Element(element) => element.to_string(),
+            BracketSymbol::Aromatic(aromatic) => {
+                let element: Element = aromatic.into();
+                element.to_string()
+            }
+        },
+    }
+}
+
+fn charge(kind: &AtomKind) -> i32 {
+    match kind {
+        AtomKind::Bracket { charge: Some(charge), .. } => {
+            let charge: i8 = charge.into();
+            i32::from(charge)
+        }
+        _ => 0,
+    }
+}
+
+/// `Follower` que pasa la lectura al `Builder` de `purr` descartando las
+/// clases de quiralidad no tetraédricas (que `purr` no sabe invertir) y
+/// recordando en qué átomo apareció la primera.
+struct Reader {
+    builder: Builder,
+    atoms: usize,
+    unsupported: Option<usize>,
+}
+
+impl Reader {
+    fn check(&mut self, mut kind: AtomKind) -> AtomKind {
+        if let AtomKind::Bracket { configuration, .. } = &mut kind {
+            if !matches!(configuration, None | Some(Configuration::TH1) | Some(Configuration::TH2)) {
+                self.unsupported.get_or_insert(self.atoms);
+                *configuration = None;
+            }
+        }
+        self.atoms += 1;
+        kind
+    }
+}
+
+impl Follower for Reader {
+    fn root(&mut self, root: AtomKind) {
+        let root = self.check(root);
+        self.builder.root(root);
+    }
+
+    fn extend(&mut self, bond_kind: purr::feature::BondKind, atom_kind: AtomKind) {
+        let atom_kind = self.check(atom_kind);
+        self.builder.extend(bond_kind, atom_kind);
+    }
+
+    fn join(&mut self, bond_kind: purr::feature::BondKind, rnum: purr::feature::Rnum) {
+        self.builder.join(bond_kind, rnum);
+    }
+
+    fn pop(&mut self, depth: usize) {
+        self.builder.pop(depth);
+    }
+}
+
+/// Resultado de leer un SMILES.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedSmiles {
+    smiles: String,
+    formula: String,
+    num_atoms: usize,
+    mol_weight: f64,
+}
+
+/// Lee `smiles` y calcula fórmula, masa y número de átomos.
+pub fn parse(smiles: &str) -> Result<ParsedSmiles, SmilesError> {
+    let mut reader = Reader { builder: Builder::new(),
+                              atoms: 0,
+                              unsupported: None };
+    let mut trace = Trace::new();
+    purr::read::read(smiles, &mut reader, Some(&mut trace)).map_err(|e| match e {
+                                                               purr::read::Error::EndOfLine => {
+                                                                   error(smiles.len(), "SMILES incompleto")
+                                                               }
+                                                               purr::read::Error::Character(position) => {
+                                                                   error(position, "Carácter inesperado")
+                                                               }
+                                                           })?;
+    let atom_position = |id: usize| trace.atom(id).map_or(0, |range| range.start);
+    if let Some(id) = reader.unsupported {
+        return Err(error(atom_position(id), "Quiralidad no tetraédrica no soportada"));
+    }
+    let atoms = reader.builder.build().map_err(|e| match e {
+                                           purr::graph::Error::Rnum(rid) => error(trace.rnum(rid)
+                                                                                       .map_or(0, |range| range.start),
+                                                                                  "Cierre de anillo sin pareja"),
+                                           purr::graph::Error::Join(sid, _) => {
+                                               error(atom_position(sid), "Cierre de anillo con enlaces incompatibles")
+                                           }
+                                       })?;
+
+    let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+    let mut net_charge = 0;
+    let mut mol_weight = 0.0;
+    let mut num_atoms = 0;
+    for (id, atom) in atoms.iter().enumerate() {
+        check_valence(atom).map_err(|message| SmilesError { position: atom_position(id),
+                                                            message,
+                                                            sanitization: Some(SanitizationIssue::Valence) })?;
+        let symbol = symbol(&atom.kind);
+        let hydrogens = u32::from(atom.suppressed_hydrogens());
+        if symbol != "*" {
+            mol_weight +=
+                weight(&symbol).ok_or_else(|| error(atom_position(id), format!("Elemento sin masa conocida: {symbol}")))?;
+        }
+        mol_weight += f64::from(hydrogens) * weight("H").unwrap_or_default();
+        if symbol != "H" {
+            num_atoms += 1;
+        }
+        *counts.entry(symbol).or_default() += 1;
+        if hydrogens > 0 {
+            *counts.entry("H".to_string()).or_default() += hydrogens;
+        }
+        net_charge += charge(&atom.kind);
+    }
+
+    Ok(ParsedSmiles { smiles: smiles.to_string(),
+                      formula: hill_formula(counts, net_charge),
+                      num_atoms,
+                      mol_weight })
+}
+
+/// Valencia explícita (enlaces más hidrógenos entre corchetes) frente al
+/// mayor objetivo de OpenSMILES del átomo; sin objetivos no se comprueba.
+fn check_valence(atom: &Atom) -> Result<(), String> {
+    let Some(&max) = atom.kind.targets().iter().max() else {
+        return Ok(());
+    };
+    let hcount = match &atom.kind {
+        AtomKind::Bracket { hcount: Some(hcount), .. } => {
+            let hcount: u8 = hcount.into();
+            u32::from(hcount)
+        }
+        _ => 0,
+    };
+    let valence = atom.bonds.iter().map(|b| u32::from(b.order())).sum::<u32>() + hcount;
+    if valence > u32::from(max) {
+        return Err(format!("Valencia {valence} excede el máximo {max} para {}", symbol(&atom.kind)));
+    }
+    Ok(())
+}
+
+/// Fórmula en orden de Hill, con la carga neta al final.
+fn hill_formula(mut counts: BTreeMap<String, u32>, charge: i32) -> String {
+    let mut formula = String::new();
+    let mut push = |symbol: &str, n: u32| {
+        formula.push_str(symbol);
+        if n > 1 {
+            formula.push_str(&n.to_string());
+        }
+    };
+    let star = counts.remove("*");
+    if let Some(c) = counts.remove("C") {
+        push("C", c);
+        if let Some(h) = counts.remove("H") {
+            push("H", h);
+        }
+    }
+    for (symbol, n) in counts {
+        push(&symbol, n);
+    }
+    if let Some(n) = star {
+        push("*", n);
+    }
+    match charge {
+        0 => {}
+        1 => formula.push('+'),
+        -1 => formula.push('-'),
+        c if c > 0 => formula.push_str(&format!("+{c}")),
+        c => formula.push_str(&format!("{c}")),
+    }
+    formula
+}
+
+impl ParsedSmiles {
+    /// SMILES tal como se leyó (no canónico).
+    pub fn smiles(&self) -> &str {
+        &self.smiles
+    }
+
+    /// Fórmula molecular en orden de Hill, con la carga neta al final.
+    pub fn formula(&self) -> &str {
+        &self.formula
+    }
+
+    /// Átomos distintos de hidrógeno (los `[H]` explícitos no cuentan).
+    pub fn num_atoms(&self) -> usize {
+        self.num_atoms
+    }
+
+    /// Masa molecular media (g/mol).
+    pub fn mol_weight(&self) -> f64 {
+        self.mol_weight
+    }
+
+    /// Clave con formato de InChIKey (14-10-1) derivada del SMILES de
+    /// entrada: el primer bloque sale de él sin marcas de
+    /// estereoquímica y el segundo de la forma completa. El indicador `N`
+    /// (no estándar) del segundo bloque la distingue de un InChIKey real.
+    /// Al no haber canonicalización, dos escrituras distintas de la misma
+    /// molécula dan claves distintas.
+    pub fn identity_key(&self) -> String {
+        let skeleton: String = self.smiles.chars().filter(|c| !matches!(c, '@' | '/' | '\\')).collect();
+        format!("{}-{}NA-N",
+                hash_letters(&format!("rs3:{skeleton}"), 14),
+                hash_letters(&format!("rs3:{}", self.smiles), 8))
+    }
+}
+
+/// Bloque de `len` letras mayúsculas derivado de un SHA-256.
+fn hash_letters(text: &str, len: usize) -> String {
+    Sha256::digest(text.as_bytes()).iter()
+                                   .take(len)
+                                   .map(|b| char::from(b'A' + b % 26))
+                                   .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hydrogens_formula_and_weight() {
+        let g = parse("CC(=O)[O-].[NH4+]").unwrap();
+        assert_eq!(g.formula(), "C2H7NO2");
+        assert_eq!(g.num_atoms(), 5);
+        let ethanol = parse("CCO").unwrap();
+        assert_eq!(ethanol.formula(), "C2H6O");
+        assert!((ethanol.mol_weight() - 46.069).abs() < 1e-3);
+        assert_eq!(parse("[Na+]").unwrap().formula(), "Na+");
+        assert_eq!(parse("c1ccccc1").unwrap().formula(), "C6H6");
+        assert_eq!(parse("c1cc[nH]c1").unwrap().formula(), "C4H5N");
+        assert_eq!(parse("[H]OC([H])([H])C").unwrap().formula(), "C2H6O");
+        assert_eq!(parse("[H]OC([H])([H])C").unwrap().num_atoms(), 3);
+        assert_eq!(parse("*CC").unwrap().formula(), "C2H5*");
+    }
+
+    #[test]
+    fn errors_report_position() {
+        assert_eq!(parse("CC(C").unwrap_err().position, 4);
+        assert_eq!(parse("C1CC").unwrap_err().position, 1);
+        assert_eq!(parse("CCX").unwrap_err().position, 2);
+        assert_eq!(parse("C[C@TB1](F)(Cl)(Br)I").unwrap_err().position, 1);
+        assert!(parse("").is_err());
+        assert!(parse("C=").is_err());
+        let err = parse("C(C)(C)(C)(C)C").unwrap_err();
+        assert_eq!((err.position, err.sanitization), (0, Some(SanitizationIssue::Valence)));
+        assert!(parse("C[N+](C)(C)C").is_ok());
+    }
+
+    #[test]
+    fn stereo_and_wildcards_are_kept_as_written() {
+        for smiles in ["F/C=C/F", "F/C=C\\F", "N[C@@H](C)C(=O)O", "*c1ccccc1"] {
+            assert_eq!(parse(smiles).unwrap().smiles(), smiles);
+        }
+        assert_eq!(parse("*c1ccccc1").unwrap().formula(), "C6H5*");
+        let trans = parse("F/C=C/F").unwrap().identity_key();
+        let cis = parse("F/C=C\\F").unwrap().identity_key();
+        assert_ne!(trans, cis);
+        assert_eq!(trans[..14], cis[..14]);
+    }
+
+    #[test]
+    fn identity_depends_on_how_the_smiles_is_written() {
+        let key = parse("CCO").unwrap().identity_key();
+        assert_eq!(key.len(), 27);
+        assert!(key.ends_with("NA-N"));
+        assert_eq!(parse("CCO").unwrap().identity_key(), key);
+        // Sin canonicalización: otra escritura, otra clave
+        assert_ne!(parse("OCC").unwrap().identity_key(), key);
+    }
+}
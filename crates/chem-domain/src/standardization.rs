@@ -0,0 +1,51 @@
+// standardization.rs
+use chemengine::StandardizeOptions;
+use sha2::{Digest, Sha256};
+
+/// Identificador del estandarizador registrado en la provenance.
+pub const STANDARDIZER: &str = "rdkit.rdMolStandardize";
+
+/// Versión del esquema de parámetros de estandarización. Cambiarla invalida
+/// los hashes previos de forma explícita.
+pub const STANDARDIZATION_SCHEMA_VERSION: u32 = 1;
+
+/// Registro reproducible de la estandarización aplicada a una molécula:
+/// estandarizador, opciones y hash de ambos.
+pub fn standardization_provenance(options: &StandardizeOptions) -> serde_json::Value {
+    serde_json::json!({
+        "standardizer": STANDARDIZER,
+        "schema_version": STANDARDIZATION_SCHEMA_VERSION,
+        "options": options,
+        "params_hash": standardization_params_hash(options),
+    })
+}
+
+/// Hash SHA-256 (hex) del JSON canónico de (estandarizador, versión de
+/// esquema, opciones). Dos moléculas con el mismo hash se estandarizaron de
+/// la misma forma.
+pub fn standardization_params_hash(options: &StandardizeOptions) -> String {
+    // `serde_json::Value` ordena las claves de los objetos, por lo que el
+    // JSON resultante es canónico.
+    let canonical = serde_json::json!({
+        "standardizer": STANDARDIZER,
+        "schema_version": STANDARDIZATION_SCHEMA_VERSION,
+        "options": options,
+    });
+    format!("{:x}", Sha256::digest(canonical.to_string().as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn params_hash_depends_only_on_options() {
+        let all = StandardizeOptions::default();
+        let minimal = StandardizeOptions::cleanup_only();
+        assert_eq!(standardization_params_hash(&all), standardization_params_hash(&all.clone()));
+        assert_ne!(standardization_params_hash(&all), standardization_params_hash(&minimal));
+        let prov = standardization_provenance(&minimal);
+        assert_eq!(prov["params_hash"], standardization_params_hash(&minimal));
+        assert_eq!(prov["options"]["uncharge"], false);
+    }
+}
@@ -0,0 +1,173 @@
+// toolkit.rs
+//! Versiones del toolkit químico con el que se calculan las moléculas.
+//!
+//! Los SMILES canónicos y los InChIKey pueden cambiar entre versiones de
+//! RDKit (o del backend en Rust), así que las versiones quedan en el
+//! metadata de las moléculas creadas por el backend y en los parámetros de
+//! los steps químicos, y con ello en sus fingerprints. Al reejecutar con
+//! parámetros registrados, `ToolkitVersions::check` detecta el cambio de
+//! entorno y lo describe componente a componente.
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "rdkit")]
+use crate::molecule::engine;
+use crate::smiles::RUST_BACKEND_VERSION;
+use crate::{molecule_backend, DomainError};
+#[cfg(feature = "rdkit")]
+use crate::{MoleculeBackend, RdkitBackend};
+
+/// Versiones del entorno químico activo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolkitVersions {
+    /// Backend molecular (`rdkit`, `rust`).
+    pub backend: String,
+    /// Versión de RDKit, si el motor está disponible.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rdkit: Option<String>,
+    /// Versión del intérprete de Python embebido.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub python: Option<String>,
+    /// Versión del backend en Rust (`RUST_BACKEND_VERSION`; solo
+    /// con el backend `rust`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust: Option<String>,
+}
+
+/// Un componente del entorno cuya versión cambió.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolkitDrift {
+    pub component: &'static str,
+    pub recorded: Option<String>,
+    pub current: Option<String>,
+}
+
+impl fmt::Display for ToolkitDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let show = |v: &Option<String>| v.clone().unwrap_or_else(|| "ausente".to_string());
+        write!(f, "{} {} → {}", self.component, show(&self.recorded), show(&self.current))
+    }
+}
+
+impl ToolkitVersions {
+    /// Diferencias entre estas versiones (registradas) y `current`.
+    pub fn drift(&self, current: &ToolkitVersions) -> Vec<ToolkitDrift> {
+        let components = [("backend", Some(&self.backend), Some(&current.backend)),
+                          ("rdkit", self.rdkit.as_ref(), current.rdkit.as_ref()),
+                          ("python", self.python.as_ref(), current.python.as_ref()),
+                          ("rust", self.rust.as_ref(), current.rust.as_ref())];
+        components.into_iter()
+                  .filter(|(_, recorded, current)| recorded != current)
+                  .map(|(component, recorded, current)| ToolkitDrift { component,
+                                                                       recorded: recorded.cloned(),
+                                                                       current: current.cloned() })
+                  .collect()
+    }
+
+    /// Falla con `DomainError::EnvironmentDrift` si `current` difiere.
+    pub fn check(&self, current: &ToolkitVersions) -> Result<(), DomainError> {
+        let drift = self.drift(current);
+        if drift.is_empty() {
+            return Ok(());
+        }
+        Err(DomainError::EnvironmentDrift(drift.iter()
+                                               .map(ToString::to_string)
+                                               .collect::<Vec<_>>()
+                                               .join(", ")))
+    }
+}
+
+static CURRENT: OnceLock<ToolkitVersions> = OnceLock::new();
+#[cfg(feature = "rdkit")]
+static RDKIT: OnceLock<ToolkitVersions> = OnceLock::new();
+
+/// Versiones del entorno actual (backend activo, RDKit y Python si el motor
+/// está disponible). Se calculan una vez por proceso.
+pub fn toolkit_versions() -> Result<&'static ToolkitVersions, DomainError> {
+    if let Some(current) = CURRENT.get() {
+        return Ok(current);
+    }
+    let backend = molecule_backend()?.name();
+    let engine_versions = engine_versions()?;
+    let versions = ToolkitVersions { backend: backend.to_string(),
+                                     rdkit: engine_versions.as_ref().map(|v| v.rdkit.clone()),
+                                     python: engine_versions.map(|v| v.python),
+                                     rust: (backend == "rust").then(|| RUST_BACKEND_VERSION.to_string()) };
+    Ok(CURRENT.get_or_init(|| versions))
+}
+
+/// Versiones de RDKit y Python si el motor está disponible.
+#[cfg(feature = "rdkit")]
+fn engine_versions() -> Result<Option<chemengine::ToolkitVersions>, DomainError> {
+    match engine() {
+        Ok(engine) => Ok(Some(engine.toolkit_versions()?)),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Sin la feature `rdkit` no hay motor.
+#[cfg(not(feature = "rdkit"))]
+fn engine_versions() -> Result<Option<chemengine::ToolkitVersions>, DomainError> {
+    Ok(None)
+}
+
+/// Versiones de lo que siempre calcula RDKit (bloques MOL, estandarización)
+/// con independencia del backend activo: backend `rdkit`, RDKit y Python.
+///
+/// # Errores
+/// `DomainError::EngineUnavailable` si el motor RDKit no está disponible.
+#[cfg(feature = "rdkit")]
+pub fn rdkit_toolkit_versions() -> Result<&'static ToolkitVersions, DomainError> {
+    if let Some(versions) = RDKIT.get() {
+        return Ok(versions);
+    }
+    let engine_versions = engine()?.toolkit_versions()?;
+    let versions = ToolkitVersions { backend: RdkitBackend.name().to_string(),
+                                     rdkit: Some(engine_versions.rdkit),
+                                     python: Some(engine_versions.python),
+                                     rust: None };
+    Ok(RDKIT.get_or_init(|| versions))
+}
+
+/// Comprueba `recorded` (si hay) contra el entorno actual.
+pub fn check_toolkit(recorded: Option<&ToolkitVersions>) -> Result<(), DomainError> {
+    match recorded {
+        Some(recorded) => recorded.check(toolkit_versions()?),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift_lists_changed_components() {
+        let recorded = ToolkitVersions { backend: "rdkit".to_string(),
+                                         rdkit: Some("2023.09.5".to_string()),
+                                         python: Some("3.11.4".to_string()),
+                                         rust: None };
+        assert!(recorded.check(&recorded.clone()).is_ok());
+
+        let current = ToolkitVersions { rdkit: Some("2024.03.1".to_string()),
+                                        ..recorded.clone() };
+        assert_eq!(recorded.drift(&current),
+                   vec![ToolkitDrift { component: "rdkit",
+                                       recorded: Some("2023.09.5".to_string()),
+                                       current: Some("2024.03.1".to_string()) }]);
+        let err = recorded.check(&current).unwrap_err();
+        assert_eq!(err.to_string(), "Cambio de entorno químico: rdkit 2023.09.5 → 2024.03.1");
+        assert!(!err.is_permanent() && !err.is_transient());
+
+        let rust = ToolkitVersions { backend: "rust".to_string(),
+                                     rdkit: None,
+                                     python: None,
+                                     rust: Some("1".to_string()) };
+        assert_eq!(recorded.drift(&rust).len(), 4);
+        let json = serde_json::to_value(&rust).unwrap();
+        assert_eq!(json, serde_json::json!({"backend": "rust", "rust": "1"}));
+    }
+}
@@ -0,0 +1,281 @@
+// units.rs
+//
+// Registro de unidades relevantes en química, con parseo (símbolos y
+// alias), conversión y verificación dimensional. Cada dimensión tiene una
+// unidad canónica; las políticas y agregaciones normalizan a ella antes de
+// comparar valores de distintos proveedores.
+use crate::DomainError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+/// Dimensión física de una unidad. Solo se convierten unidades de la misma
+/// dimensión.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dimension {
+    /// Cociente puro (fracción, porcentaje).
+    Dimensionless,
+    /// Escala logarítmica (logP, logS, pKa): no convertible a fracción.
+    Logarithmic,
+    /// Unidad arbitraria (`au`); solo comparable consigo misma.
+    Arbitrary,
+    Length,
+    Area,
+    Volume,
+    Mass,
+    MolarMass,
+    Amount,
+    /// Concentración molar (mol/L).
+    Concentration,
+    /// Concentración másica (g/L).
+    MassConcentration,
+    /// Energía por mol (kJ/mol).
+    MolarEnergy,
+    Temperature,
+    Time,
+    Pressure,
+}
+
+/// Unidad registrada. `canonical = value * factor + offset` (el offset solo
+/// se usa en temperaturas).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Unit {
+    symbol: &'static str,
+    aliases: &'static [&'static str],
+    dimension: Dimension,
+    factor: f64,
+    offset: f64,
+}
+
+const fn unit(symbol: &'static str, aliases: &'static [&'static str], dimension: Dimension, factor: f64) -> Unit {
+    Unit { symbol,
+           aliases,
+           dimension,
+           factor,
+           offset: 0.0 }
+}
+
+/// Registro de unidades. La primera de cada dimensión es la canónica.
+static UNITS: &[Unit] = &[// Adimensionales
+                          unit("1", &["", "unitless", "dimensionless"], Dimension::Dimensionless, 1.0),
+                          unit("%", &["percent"], Dimension::Dimensionless, 0.01),
+                          unit("log", &["log units", "log_units"], Dimension::Logarithmic, 1.0),
+                          unit("au", &["arbitrary"], Dimension::Arbitrary, 1.0),
+                          // Longitud
+                          unit("Å", &["A", "angstrom", "Angstrom"], Dimension::Length, 1.0),
+                          unit("nm", &[], Dimension::Length, 10.0),
+                          unit("pm", &[], Dimension::Length, 0.01),
+                          unit("m", &[], Dimension::Length, 1e10),
+                          unit("bohr", &["a0"], Dimension::Length, 0.529_177_210_903),
+                          // Área
+                          unit("Å²", &["A^2", "A2", "Å^2", "angstrom^2"], Dimension::Area, 1.0),
+                          unit("nm²", &["nm^2", "nm2"], Dimension::Area, 100.0),
+                          unit("pm²", &["pm^2", "pm2"], Dimension::Area, 1e-4),
+                          // Volumen
+                          unit("Å³", &["A^3", "A3", "Å^3", "angstrom^3"], Dimension::Volume, 1.0),
+                          unit("nm³", &["nm^3", "nm3"], Dimension::Volume, 1000.0),
+                          unit("L", &["l"], Dimension::Volume, 1e27),
+                          unit("mL", &["ml", "cm³", "cm^3", "cm3"], Dimension::Volume, 1e24),
+                          // Masa
+                          unit("g", &[], Dimension::Mass, 1.0),
+                          unit("mg", &[], Dimension::Mass, 1e-3),
+                          unit("µg", &["ug", "μg"], Dimension::Mass, 1e-6),
+                          unit("kg", &[], Dimension::Mass, 1e3),
+                          // Masa molar (1 Da ≡ 1 g/mol para masas moleculares)
+                          unit("g/mol", &["g mol-1", "g·mol⁻¹", "Da", "amu", "u"], Dimension::MolarMass, 1.0),
+                          unit("kg/mol", &[], Dimension::MolarMass, 1e3),
+                          unit("kDa", &[], Dimension::MolarMass, 1e3),
+                          // Cantidad de sustancia
+                          unit("mol", &[], Dimension::Amount, 1.0),
+                          unit("mmol", &[], Dimension::Amount, 1e-3),
+                          unit("µmol", &["umol", "μmol"], Dimension::Amount, 1e-6),
+                          // Concentración molar
+                          unit("mol/L", &["M", "mol/l", "mol L-1"], Dimension::Concentration, 1.0),
+                          unit("mM", &["mmol/L", "mmol/l"], Dimension::Concentration, 1e-3),
+                          unit("µM", &["uM", "μM", "umol/L"], Dimension::Concentration, 1e-6),
+                          unit("nM", &["nmol/L"], Dimension::Concentration, 1e-9),
+                          unit("pM", &["pmol/L"], Dimension::Concentration, 1e-12),
+                          // Concentración másica
+                          unit("g/L", &["g/l", "mg/mL", "mg/ml"], Dimension::MassConcentration, 1.0),
+                          unit("mg/L",
+                               &["mg/l", "µg/mL", "ug/mL", "ug/ml"],
+                               Dimension::MassConcentration,
+                               1e-3),
+                          unit("µg/L", &["ug/L", "ug/l", "ng/mL"], Dimension::MassConcentration, 1e-6),
+                          // Energía molar
+                          unit("kJ/mol", &["kJ mol-1", "kj/mol"], Dimension::MolarEnergy, 1.0),
+                          unit("J/mol", &["J mol-1"], Dimension::MolarEnergy, 1e-3),
+                          unit("kcal/mol", &["kcal mol-1", "kcal/mole"], Dimension::MolarEnergy, 4.184),
+                          unit("eV", &["ev"], Dimension::MolarEnergy, 96.485_332_12),
+                          unit("hartree", &["Eh", "Ha"], Dimension::MolarEnergy, 2_625.499_639),
+                          unit("cm⁻¹", &["cm-1", "cm^-1", "1/cm"], Dimension::MolarEnergy, 0.011_962_657),
+                          // Temperatura
+                          unit("K", &["kelvin"], Dimension::Temperature, 1.0),
+                          Unit { symbol: "°C",
+                                 aliases: &["C", "degC", "celsius"],
+                                 dimension: Dimension::Temperature,
+                                 factor: 1.0,
+                                 offset: 273.15 },
+                          Unit { symbol: "°F",
+                                 aliases: &["F", "degF", "fahrenheit"],
+                                 dimension: Dimension::Temperature,
+                                 factor: 5.0 / 9.0,
+                                 offset: 255.372_222_222_222_2 },
+                          // Tiempo
+                          unit("s", &["sec"], Dimension::Time, 1.0),
+                          unit("ms", &[], Dimension::Time, 1e-3),
+                          unit("min", &[], Dimension::Time, 60.0),
+                          unit("h", &["hr", "hour"], Dimension::Time, 3600.0),
+                          // Presión
+                          unit("Pa", &[], Dimension::Pressure, 1.0),
+                          unit("kPa", &[], Dimension::Pressure, 1e3),
+                          unit("bar", &[], Dimension::Pressure, 1e5),
+                          unit("atm", &[], Dimension::Pressure, 101_325.0),
+                          unit("Torr", &["mmHg", "torr"], Dimension::Pressure, 133.322_368_421)];
+
+impl Unit {
+    /// Interpreta un símbolo o alias registrado (se ignoran espacios
+    /// exteriores; los símbolos distinguen mayúsculas: `mM` ≠ `MM`).
+    pub fn parse(text: &str) -> Result<Unit, DomainError> {
+        let text = text.trim();
+        UNITS.iter()
+             .find(|u| u.symbol == text || u.aliases.contains(&text))
+             .copied()
+             .ok_or_else(|| DomainError::ValidationError(format!("Unidad desconocida: '{text}'")))
+    }
+
+    /// Unidad canónica de una dimensión.
+    pub fn canonical_for(dimension: Dimension) -> Unit {
+        *UNITS.iter()
+              .find(|u| u.dimension == dimension)
+              .expect("toda dimensión tiene unidad canónica")
+    }
+
+    /// Todas las unidades registradas, en orden de registro.
+    pub fn all() -> &'static [Unit] {
+        UNITS
+    }
+
+    /// Símbolo canónico de la unidad
+    pub fn symbol(&self) -> &'static str {
+        self.symbol
+    }
+
+    /// Dimensión física de la unidad
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// Unidad canónica de la misma dimensión
+    pub fn canonical(&self) -> Unit {
+        Self::canonical_for(self.dimension)
+    }
+
+    /// Indica si es la unidad canónica de su dimensión
+    pub fn is_canonical(&self) -> bool {
+        self.symbol == self.canonical().symbol
+    }
+
+    /// Convierte `value` a la unidad canónica de su dimensión.
+    pub fn to_canonical(&self, value: f64) -> f64 {
+        value * self.factor + self.offset
+    }
+
+    /// Convierte `value` (en unidad canónica) a esta unidad.
+    pub fn from_canonical(&self, value: f64) -> f64 {
+        (value - self.offset) / self.factor
+    }
+
+    /// Convierte `value` de esta unidad a `target`.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si las dimensiones no coinciden.
+    pub fn convert(&self, value: f64, target: &Unit) -> Result<f64, DomainError> {
+        check_same_dimension(self, target)?;
+        if self.symbol == target.symbol {
+            return Ok(value);
+        }
+        Ok(target.from_canonical(self.to_canonical(value)))
+    }
+}
+
+/// Verifica que dos unidades sean de la misma dimensión.
+pub fn check_same_dimension(a: &Unit, b: &Unit) -> Result<(), DomainError> {
+    if a.dimension != b.dimension {
+        return Err(DomainError::ValidationError(format!("Dimensiones incompatibles: {} ({:?}) vs {} ({:?})",
+                                                        a.symbol, a.dimension, b.symbol, b.dimension)));
+    }
+    Ok(())
+}
+
+/// Convierte `value` entre unidades dadas como texto.
+pub fn convert(value: f64, from: &str, to: &str) -> Result<f64, DomainError> {
+    Unit::parse(from)?.convert(value, &Unit::parse(to)?)
+}
+
+/// Normaliza `value` en `units` a la unidad canónica de su dimensión;
+/// devuelve el valor y el símbolo canónico.
+pub fn normalize(value: f64, units: &str) -> Result<(f64, &'static str), DomainError> {
+    let unit = Unit::parse(units)?;
+    Ok((unit.to_canonical(value), unit.canonical().symbol))
+}
+
+// Implementación de Display para formato legible
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol)
+    }
+}
+
+// Serializa como símbolo; se deserializa con `Unit::parse` (acepta alias).
+impl Serialize for Unit {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.symbol)
+    }
+}
+
+impl<'de> Deserialize<'de> for Unit {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Unit::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9 * b.abs().max(1.0)
+    }
+
+    #[test]
+    fn registry_is_consistent() {
+        let mut seen = std::collections::HashSet::new();
+        for u in Unit::all() {
+            assert!(seen.insert(u.symbol), "símbolo duplicado: {}", u.symbol);
+            for alias in u.aliases {
+                assert!(seen.insert(alias), "alias duplicado: {alias}");
+            }
+            assert_eq!(Unit::parse(u.symbol).unwrap(), *u);
+            assert!(close(u.from_canonical(u.to_canonical(3.5)), 3.5));
+        }
+    }
+
+    #[test]
+    fn conversions_and_dimensions() {
+        assert!(close(convert(1.0, "kcal/mol", "kJ/mol").unwrap(), 4.184));
+        assert!(close(convert(1.0, "nm^2", "Å²").unwrap(), 100.0));
+        assert!(close(convert(25.0, "°C", "K").unwrap(), 298.15));
+        assert!(close(convert(212.0, "°F", "°C").unwrap(), 100.0));
+        assert!(close(convert(250.0, "nM", "µM").unwrap(), 0.25));
+        assert_eq!(normalize(2.0, "kcal/mol").unwrap(), (8.368, "kJ/mol"));
+        assert!(convert(1.0, "kJ/mol", "Å²").is_err());
+        assert!(convert(1.0, "%", "log").is_err());
+        assert!(Unit::parse("furlong").is_err());
+
+        let unit: Unit = serde_json::from_str("\"A^2\"").unwrap();
+        assert_eq!(serde_json::to_string(&unit).unwrap(), "\"Å²\"");
+        assert!(unit.is_canonical());
+    }
+}
@@ -0,0 +1,50 @@
+//! Benchmark: `get_molecule` (una llamada FFI por SMILES) vs `get_molecules`
+//! (una llamada por lote).
+//!
+//! `cargo bench -p chemengine --bench get_molecules [-- <n>]` (por defecto
+//! n = 2000). Requiere Python con RDKit; si no está disponible, se omite.
+
+use std::time::Instant;
+
+use chemengine::ChemEngine;
+
+const SMILES: &[&str] = &["CCO",
+                          "c1ccccc1",
+                          "CC(=O)Oc1ccccc1C(=O)O",
+                          "CN1C=NC2=C1C(=O)N(C(=O)N2C)C",
+                          "CC(C)Cc1ccc(cc1)C(C)C(=O)O",
+                          "C1CCCCC1",
+                          "OC(=O)CCC(=O)O",
+                          "invalid-smiles"];
+
+fn main() {
+    let n: usize = std::env::args().skip(1).find_map(|a| a.parse().ok()).unwrap_or(2000);
+    let engine = match ChemEngine::init() {
+        Ok(e) => e,
+        Err(e) => {
+            eprintln!("Skipping benchmark: RDKit no disponible ({e})");
+            return;
+        }
+    };
+    let input: Vec<&str> = SMILES.iter().cycle().take(n).copied().collect();
+
+    let start = Instant::now();
+    let single_ok = input.iter().filter(|s| engine.get_molecule(s).is_ok()).count();
+    let single = start.elapsed();
+
+    let start = Instant::now();
+    let batch = engine.get_molecules(&input).expect("batch call");
+    let batched = start.elapsed();
+    let batch_ok = batch.iter().filter(|r| r.is_ok()).count();
+
+    assert_eq!(single_ok, batch_ok, "ambos caminos deben aceptar los mismos SMILES");
+    println!("n={n} ok={batch_ok}");
+    println!("get_molecule  (single): {:>10.2?} ({:.1} µs/mol)",
+             single,
+             single.as_secs_f64() * 1e6 / n as f64);
+    println!("get_molecules (batch) : {:>10.2?} ({:.1} µs/mol)",
+             batched,
+             batched.as_secs_f64() * 1e6 / n as f64);
+    println!("speedup: {:.2}x",
+             single.as_secs_f64() / batched.as_secs_f64().max(f64::EPSILON));
+}
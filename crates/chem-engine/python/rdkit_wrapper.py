@@ -1,11 +1,98 @@
-from rdkit import Chem
-from rdkit.Chem import Descriptors, inchi
+import contextlib
+import io
+import itertools
+import json
+import logging
+import platform
+import re
 
+from rdkit import Chem, rdBase
+from rdkit.Chem import AllChem, Crippen, Descriptors, MACCSkeys, QED, inchi, rdMolDescriptors
+from rdkit.Chem.FilterCatalog import FilterCatalog, FilterCatalogParams
+from rdkit.Chem import rdRGroupDecomposition
+from rdkit.Chem.MolStandardize import rdMolStandardize
+from rdkit.Chem.Scaffolds import MurckoScaffold
 
-def molecule_info(smiles: str) -> dict:
-    mol = Chem.MolFromSmiles(smiles)
+
+class SmilesParseError(ValueError):
+    """SMILES sintácticamente inválido. `position` (base 0) es la posición
+    que indica RDKit, si la indica."""
+
+    def __init__(self, message: str, position=None):
+        super().__init__(message)
+        self.position = position
+
+
+class SanitizationError(ValueError):
+    """El SMILES se lee pero la molécula no supera la sanitización.
+    `issue`: `valence`, `aromaticity` u `other`."""
+
+    def __init__(self, message: str, issue: str):
+        super().__init__(message)
+        self.issue = issue
+
+
+_SANITIZATION_ISSUES = {
+    "AtomValenceException": "valence",
+    "AtomKekulizeException": "aromaticity",
+    "KekulizeException": "aromaticity",
+}
+
+
+def toolkit_versions() -> dict:
+    """Versiones de RDKit y de Python: los SMILES canónicos y los InChIKey
+    pueden cambiar entre versiones de RDKit."""
+    return {"rdkit": rdBase.rdkitVersion, "python": platform.python_version()}
+
+
+@contextlib.contextmanager
+def _captured_rdkit_log():
+    """Envía el log de RDKit al logger `rdkit` de Python solo durante el
+    bloque y lo captura en un `StringIO`. Al salir se restaura la salida por
+    defecto de RDKit (flujos de C++) y el logger queda como estaba."""
+    stream = io.StringIO()
+    handler = logging.StreamHandler(stream)
+    logger = logging.getLogger("rdkit")
+    level, propagate = logger.level, logger.propagate
+    logger.addHandler(handler)
+    logger.setLevel(logging.DEBUG)
+    logger.propagate = False
+    rdBase.LogToPythonLogger()
+    try:
+        yield stream
+    finally:
+        rdBase.LogToCppStreams()
+        logger.setLevel(level)
+        logger.propagate = propagate
+        logger.removeHandler(handler)
+
+
+def _parse_error(smiles: str) -> SmilesParseError:
+    """Repite la lectura capturando el log de RDKit para extraer el motivo y
+    la posición ("check for mistakes around position N", base 1)."""
+    with _captured_rdkit_log() as stream:
+        Chem.MolFromSmiles(smiles, sanitize=False)
+    log = stream.getvalue()
+    match = re.search(r"position (\d+)", log)
+    position = int(match.group(1)) - 1 if match else None
+    reason = re.search(r"SMILES Parse Error: (.+?)(?: while parsing| for input|$)", log, re.MULTILINE)
+    return SmilesParseError(reason.group(1) if reason else "SMILES inválido", position)
+
+
+def _mol_from_smiles(smiles: str):
+    mol = Chem.MolFromSmiles(smiles, sanitize=False)
     if mol is None:
-        raise ValueError("SMILES inválido")
+        raise _parse_error(smiles)
+    try:
+        Chem.SanitizeMol(mol)
+    except Chem.rdchem.MolSanitizeException as e:
+        issue = _SANITIZATION_ISSUES.get(type(e).__name__, "other")
+        raise SanitizationError(str(e), issue) from None
+    return mol
+
+
+def molecule_info(smiles: str) -> dict:
+    mol = _mol_from_smiles(smiles)
 
     info = {
         "smiles": Chem.MolToSmiles(mol),
@@ -16,3 +103,344 @@ def molecule_info(smiles: str) -> dict:
         "mol_formula": Chem.rdMolDescriptors.CalcMolFormula(mol)
     }
     return info
+
+
+def _mol_from_molblock(molblock: str):
+    mol = Chem.MolFromMolBlock(molblock)
+    if mol is None:
+        raise ValueError("MOL block inválido")
+    return mol
+
+
+def molblock_info_batch(molblocks: list) -> str:
+    """`molecule_info` a partir de bloques MOL (registros de SDF/MOL)."""
+    return _batch(lambda molblock: molecule_info(Chem.MolToSmiles(_mol_from_molblock(molblock))), molblocks)
+
+
+def to_molblock_batch(smiles_list: list) -> str:
+    """Bloque MOL (con coordenadas 2D) para cada SMILES."""
+    def to_molblock(smiles: str) -> str:
+        mol = _mol_from_smiles(smiles)
+        AllChem.Compute2DCoords(mol)
+        return Chem.MolToMolBlock(mol)
+    return _batch(to_molblock, smiles_list)
+
+
+def inchikey_from_inchi_batch(inchis: list) -> str:
+    """InChIKey recalculado a partir de cada InChI (comprobación de identidad)."""
+    def to_inchikey(text: str) -> str:
+        key = inchi.InchiToInchiKey(text)
+        if not key:
+            raise ValueError("InChI inválido")
+        return key
+    return _batch(to_inchikey, inchis)
+
+
+def _batch(fn, smiles_list: list, *args) -> str:
+    """Aplica `fn` a cada SMILES en una única llamada FFI y devuelve un único
+    JSON. Cada ítem es `{"ok": valor}` o `{"error": mensaje, "kind": ...}`
+    (ver `_error_item`); un SMILES inválido no aborta el lote."""
+    out = []
+    for smiles in smiles_list:
+        try:
+            out.append({"ok": fn(smiles, *args)})
+        except Exception as e:  # noqa: BLE001 - se reporta por ítem
+            out.append(_error_item(e))
+    return json.dumps(out)
+
+
+def _error_item(e: Exception) -> dict:
+    """Error de un ítem con su tipo (`kind`) para que Rust lo tipifique."""
+    item = {"error": str(e) or type(e).__name__}
+    if isinstance(e, SmilesParseError):
+        item.update(kind="invalid_smiles", position=e.position)
+    elif isinstance(e, SanitizationError):
+        item.update(kind="sanitization", issue=e.issue)
+    return item
+
+
+def molecule_info_batch(smiles_list: list) -> str:
+    """Versión por lotes de `molecule_info`."""
+    return _batch(molecule_info, smiles_list)
+
+
+def _standardize_mol(mol, options: dict):
+    """Aplica los pasos de `rdMolStandardize` en orden fijo: limpieza,
+    desconexión de metales, fragmento mayor, neutralización y tautómero
+    canónico."""
+    mol = rdMolStandardize.Cleanup(mol)
+    if options.get("disconnect_metals"):
+        mol = rdMolStandardize.MetalDisconnector().Disconnect(mol)
+    if options.get("largest_fragment"):
+        mol = rdMolStandardize.LargestFragmentChooser(preferOrganic=True).choose(mol)
+    if options.get("uncharge"):
+        mol = rdMolStandardize.Uncharger().uncharge(mol)
+    if options.get("canonical_tautomer"):
+        mol = rdMolStandardize.TautomerEnumerator().Canonicalize(mol)
+    Chem.SanitizeMol(mol)
+    return mol
+
+
+def standardize(smiles: str, options_json: str) -> dict:
+    """Como `molecule_info`, pero sobre la estructura estandarizada."""
+    mol = _standardize_mol(_mol_from_smiles(smiles), json.loads(options_json))
+    return molecule_info(Chem.MolToSmiles(mol))
+
+
+def standardize_batch(smiles_list: list, options_json: str) -> str:
+    """Versión por lotes de `standardize`."""
+    return _batch(standardize, smiles_list, options_json)
+
+
+def _fingerprint_bitvect(mol, spec: dict):
+    kind = spec["kind"]
+    if kind == "morgan":
+        try:
+            from rdkit.Chem import rdFingerprintGenerator
+            gen = rdFingerprintGenerator.GetMorganGenerator(radius=int(spec["radius"]), fpSize=int(spec["n_bits"]))
+            return gen.GetFingerprint(mol)
+        except ImportError:
+            return AllChem.GetMorganFingerprintAsBitVect(mol, int(spec["radius"]), nBits=int(spec["n_bits"]))
+    if kind == "maccs":
+        return MACCSkeys.GenMACCSKeys(mol)
+    if kind == "rdkit_path":
+        return Chem.RDKFingerprint(mol,
+                                   minPath=int(spec["min_path"]),
+                                   maxPath=int(spec["max_path"]),
+                                   fpSize=int(spec["n_bits"]))
+    raise ValueError(f"fingerprint desconocido: {kind}")
+
+
+def fingerprint(smiles: str, spec_json: str) -> dict:
+    """Fingerprint como lista de bits encendidos (`on_bits`) y longitud."""
+    mol = _mol_from_smiles(smiles)
+    bv = _fingerprint_bitvect(mol, json.loads(spec_json))
+    return {"n_bits": bv.GetNumBits(), "on_bits": list(bv.GetOnBits())}
+
+
+def fingerprint_batch(smiles_list: list, spec_json: str) -> str:
+    """Versión por lotes de `fingerprint`."""
+    return _batch(fingerprint, smiles_list, spec_json)
+
+
+def _query_from_smarts(smarts: str):
+    query = Chem.MolFromSmarts(smarts)
+    if query is None:
+        raise ValueError(f"SMARTS inválido: {smarts}")
+    return query
+
+
+def matches_smarts(smiles: str, smarts: str) -> bool:
+    """Indica si la molécula contiene la subestructura `smarts`."""
+    return _mol_from_smiles(smiles).HasSubstructMatch(_query_from_smarts(smarts))
+
+
+def matches_smarts_batch(smiles_list: list, smarts: str) -> str:
+    """Versión por lotes de `matches_smarts`. Un SMARTS inválido aborta la
+    llamada completa; un SMILES inválido sólo su ítem."""
+    query = _query_from_smarts(smarts)
+    return _batch(lambda smiles: _mol_from_smiles(smiles).HasSubstructMatch(query), smiles_list)
+
+
+# Catálogos de alertas estructurales soportados: nombre estable -> catálogo RDKit.
+FILTER_CATALOGS = {
+    "pains_a": FilterCatalogParams.FilterCatalogs.PAINS_A,
+    "pains_b": FilterCatalogParams.FilterCatalogs.PAINS_B,
+    "pains_c": FilterCatalogParams.FilterCatalogs.PAINS_C,
+    "brenk": FilterCatalogParams.FilterCatalogs.BRENK,
+}
+_FILTER_CATALOG_CACHE = {}
+
+
+def _filter_catalog(name: str):
+    catalog = _FILTER_CATALOG_CACHE.get(name)
+    if catalog is None:
+        if name not in FILTER_CATALOGS:
+            raise ValueError(f"catálogo de filtros desconocido: {name}")
+        params = FilterCatalogParams()
+        params.AddCatalog(FILTER_CATALOGS[name])
+        catalog = FilterCatalog(params)
+        _FILTER_CATALOG_CACHE[name] = catalog
+    return catalog
+
+
+def structural_alerts(smiles: str, catalogs: list) -> list:
+    """Alertas de los catálogos pedidos, sin duplicados y ordenadas por
+    (catálogo, descripción) para que el resultado sea determinista."""
+    mol = _mol_from_smiles(smiles)
+    found = set()
+    for name in catalogs:
+        for entry in _filter_catalog(name).GetMatches(mol):
+            found.add((name, entry.GetDescription()))
+    return [{"catalog": c, "description": d} for c, d in sorted(found)]
+
+
+def structural_alerts_batch(smiles_list: list, catalogs: list) -> str:
+    """Versión por lotes de `structural_alerts`."""
+    for name in catalogs:
+        _filter_catalog(name)
+    return _batch(structural_alerts, smiles_list, catalogs)
+
+
+def murcko_scaffold(smiles: str) -> str:
+    """Scaffold de Bemis–Murcko (SMILES canónico). Cadena vacía si la
+    molécula es acíclica."""
+    return MurckoScaffold.MurckoScaffoldSmiles(mol=_mol_from_smiles(smiles))
+
+
+def murcko_scaffold_batch(smiles_list: list) -> str:
+    """Versión por lotes de `murcko_scaffold`."""
+    return _batch(murcko_scaffold, smiles_list)
+
+
+def _core_from_text(core: str):
+    mol = Chem.MolFromSmiles(core)
+    if mol is None:
+        mol = Chem.MolFromSmarts(core)
+    if mol is None:
+        raise ValueError(f"Núcleo inválido: {core}")
+    return mol
+
+
+def rgroup_decomposition_batch(smiles_list: list, cores: list) -> str:
+    """R-grupos de cada SMILES respecto a `cores` (SMILES o SMARTS) con
+    `RGroupDecompose`. Cada ítem es `{"ok": {"core", "rgroups"}}` (con `core`
+    nulo si no encaja en ningún núcleo) o un error como en `_batch`."""
+    core_mols = [_core_from_text(c) for c in cores]
+    out = []
+    parsed = []
+    for smiles in smiles_list:
+        try:
+            parsed.append((len(out), _mol_from_smiles(smiles)))
+            out.append({"ok": {"core": None, "rgroups": {}}})
+        except Exception as e:  # noqa: BLE001 - se reporta por ítem
+            out.append(_error_item(e))
+    if parsed and core_mols:
+        rows, unmatched = rdRGroupDecomposition.RGroupDecompose(core_mols, [mol for _, mol in parsed],
+                                                                asSmiles=True, asRows=True)
+        skipped = set(unmatched)
+        rows = iter(rows)
+        for k, (position, _) in enumerate(parsed):
+            if k in skipped:
+                continue
+            row = dict(next(rows))
+            core = row.pop("Core")
+            out[position] = {"ok": {"core": core, "rgroups": dict(sorted(row.items()))}}
+    return json.dumps(out)
+
+
+def _reaction_from_smarts(smarts: str):
+    try:
+        rxn = AllChem.ReactionFromSmarts(smarts)
+    except ValueError as e:
+        raise ValueError(f"SMARTS de reacción inválido: {smarts}") from e
+    if rxn is None or rxn.GetNumProductTemplates() == 0:
+        raise ValueError(f"SMARTS de reacción sin productos: {smarts}")
+    rxn.Initialize()
+    return rxn
+
+
+def enumerate_reaction(smarts: str, reagents: list, limits_json: str) -> str:
+    """Enumera los productos de la reacción sobre todas las combinaciones de
+    reactivos (una lista por plantilla), en orden lexicográfico de índices.
+    Por combinación los productos se deduplican por SMILES canónico y se
+    ordenan; los que no superan la sanitización cuentan en `n_failed`."""
+    rxn = _reaction_from_smarts(smarts)
+    if rxn.GetNumReactantTemplates() != len(reagents):
+        raise ValueError(f"La reacción tiene {rxn.GetNumReactantTemplates()} reactivos "
+                         f"y se dieron {len(reagents)} listas")
+    limits = json.loads(limits_json)
+    mols = [[_mol_from_smiles(s) for s in group] for group in reagents]
+    products = []
+    n_failed = 0
+    truncated = False
+    for combo in itertools.product(*[range(len(group)) for group in mols]):
+        found = set()
+        for outcome in rxn.RunReactants(tuple(mols[i][j] for i, j in enumerate(combo))):
+            for product in outcome:
+                try:
+                    Chem.SanitizeMol(product)
+                    found.add(Chem.MolToSmiles(product))
+                except Exception:  # noqa: BLE001 - producto químicamente inválido
+                    n_failed += 1
+        found = sorted(found)
+        if len(found) > limits["max_products_per_combination"]:
+            found = found[:limits["max_products_per_combination"]]
+            truncated = True
+        if len(products) + len(found) > limits["max_products"]:
+            found = found[:limits["max_products"] - len(products)]
+            truncated = True
+        products.extend({"smiles": s, "reagents": list(combo)} for s in found)
+        if len(products) >= limits["max_products"] and truncated:
+            break
+    return json.dumps({"products": products, "n_failed": n_failed, "truncated": truncated})
+
+
+def embed_conformers(smiles: str, n: int, seed: int, force_field: str) -> str:
+    """Genera `n` conformeros con ETKDGv3 (semilla fija, un solo hilo) y los
+    optimiza con MMFF94/UFF. Devuelve un `ConformerSet` en JSON."""
+    mol = Chem.AddHs(_mol_from_smiles(smiles))
+    params = AllChem.ETKDGv3()
+    params.randomSeed = seed
+    params.numThreads = 1
+    ids = list(AllChem.EmbedMultipleConfs(mol, numConfs=n, params=params))
+    if not ids:
+        raise ValueError("ETKDG no pudo generar conformeros")
+    if force_field == "mmff":
+        if not AllChem.MMFFHasAllMoleculeParams(mol):
+            raise ValueError("MMFF94 no tiene parámetros para la molécula")
+        results = AllChem.MMFFOptimizeMoleculeConfs(mol, numThreads=1)
+    elif force_field == "uff":
+        results = AllChem.UFFOptimizeMoleculeConfs(mol, numThreads=1)
+    elif force_field == "none":
+        results = [(0, None)] * len(ids)
+    else:
+        raise ValueError(f"Campo de fuerza desconocido: {force_field}")
+    conformers = []
+    for conf_id, (not_converged, energy) in zip(ids, results):
+        positions = mol.GetConformer(conf_id).GetPositions()
+        conformers.append({
+            "coordinates": [[round(float(x), 4) for x in p] for p in positions],
+            "energy": None if energy is None else round(float(energy), 6),
+            "converged": not_converged == 0,
+        })
+    return json.dumps({
+        "smiles": Chem.MolToSmiles(Chem.RemoveHs(mol)),
+        "atoms": [atom.GetSymbol() for atom in mol.GetAtoms()],
+        "force_field": force_field,
+        "seed": seed,
+        "conformers": conformers,
+    })
+
+
+# Descriptores soportados: nombre estable -> función RDKit.
+DESCRIPTORS = {
+    "logp": Crippen.MolLogP,
+    "tpsa": rdMolDescriptors.CalcTPSA,
+    "hbd": rdMolDescriptors.CalcNumHBD,
+    "hba": rdMolDescriptors.CalcNumHBA,
+    "rotatable_bonds": rdMolDescriptors.CalcNumRotatableBonds,
+    "ring_count": rdMolDescriptors.CalcNumRings,
+    "aromatic_ring_count": rdMolDescriptors.CalcNumAromaticRings,
+    "formal_charge": Chem.GetFormalCharge,
+    "fraction_csp3": rdMolDescriptors.CalcFractionCSP3,
+    "qed": QED.qed,
+}
+
+
+def descriptors(smiles: str, names: list) -> dict:
+    """Calcula los descriptores pedidos. Un fallo en un descriptor no aborta
+    el resto: se reporta en `errors`."""
+    mol = _mol_from_smiles(smiles)
+    values = {}
+    errors = {}
+    for name in names:
+        fn = DESCRIPTORS.get(name)
+        if fn is None:
+            errors[name] = "descriptor desconocido"
+            continue
+        try:
+            values[name] = float(fn(mol))
+        except Exception as e:  # noqa: BLE001 - se reporta por descriptor
+            errors[name] = f"{type(e).__name__}: {e}"
+    return {"smiles": Chem.MolToSmiles(mol), "values": values, "errors": errors}
@@ -0,0 +1,54 @@
+//! Conformeros 3D generados con ETKDG (RDKit) y optimizados con un campo de
+//! fuerza (MMFF94 o UFF).
+
+use serde::{Deserialize, Serialize};
+
+/// Campo de fuerza para optimizar los conformeros tras el embedding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForceField {
+    #[default]
+    Mmff,
+    Uff,
+    /// Sin optimización: geometrías ETKDG tal cual (sin energías).
+    None,
+}
+
+impl ForceField {
+    /// Nombre estable (el mismo que en serde).
+    pub fn name(&self) -> &'static str {
+        match self {
+            ForceField::Mmff => "mmff",
+            ForceField::Uff => "uff",
+            ForceField::None => "none",
+        }
+    }
+}
+
+/// Un conformero: coordenadas (Å) en el orden de `ConformerSet::atoms` y
+/// energía del campo de fuerza (kcal/mol) si se optimizó.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conformer {
+    pub coordinates: Vec<[f64; 3]>,
+    pub energy: Option<f64>,
+    /// `false` si la optimización agotó las iteraciones sin converger.
+    pub converged: bool,
+}
+
+/// Conformeros de una molécula (con hidrógenos explícitos). Con la misma
+/// semilla, SMILES y versión de RDKit el resultado es reproducible; las
+/// coordenadas se redondean a 1e-4 Å y las energías a 1e-6.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConformerSet {
+    pub smiles: String,
+    /// Símbolos de los átomos, hidrógenos incluidos.
+    pub atoms: Vec<String>,
+    pub force_field: ForceField,
+    pub seed: u32,
+    pub conformers: Vec<Conformer>,
+}
+
+impl ConformerSet {
+    /// Semilla máxima: RDKit la recibe como `int` de C (`randomSeed`).
+    pub const MAX_SEED: u32 = i32::MAX as u32;
+}
@@ -1,7 +1,18 @@
 use pyo3::ffi::c_str;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyModule};
-use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::conformers::{ConformerSet, ForceField};
+use crate::descriptors::{Descriptor, Descriptors, RawDescriptors};
+use crate::errors::ItemErrorKind;
+use crate::fingerprints::{FingerprintBits, FingerprintKind};
+use crate::reactions::{Enumeration, EnumerationLimits};
+use crate::records::{BatchItemError, Molecule, ToolkitVersions};
+use crate::scaffolds::RGroups;
+use crate::standardize::StandardizeOptions;
+use crate::substructure::{FilterCatalog, StructuralAlert};
 use std::ffi::CString;
 use std::sync::OnceLock;
 
@@ -24,16 +35,6 @@ fn get_module(py: Python<'_>) -> PyResult<Py<PyModule>> {
                                                          })
 }
 
-#[derive(Debug, Deserialize)]
-pub struct Molecule {
-    pub smiles: String,
-    pub inchi: String,
-    pub inchikey: String,
-    pub num_atoms: u32,
-    pub mol_weight: f64,
-    pub mol_formula: String,
-}
-
 pub fn get_molecule(smiles: &str) -> PyResult<Molecule> {
     Python::attach(|py| {
         let rdkit_py = get_module(py)?;
@@ -41,12 +42,319 @@ pub fn get_molecule(smiles: &str) -> PyResult<Molecule> {
         let binding = rdkit.getattr("molecule_info")?.call1((smiles,))?;
         let info = binding.downcast::<PyDict>()?;
         let json_str: String = py.import("json")?.call_method1("dumps", (info,))?.extract()?;
-        let molecule: Molecule = serde_json::from_str(&json_str).map_err(|e| {
-                                     PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Deserialization error: {}", e))
-                                 })?;
-        Ok(molecule)
+        deserialize(&json_str)
+    })
+}
+pub fn toolkit_versions() -> PyResult<ToolkitVersions> {
+    Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        let binding = rdkit.getattr("toolkit_versions")?.call0()?;
+        let info = binding.downcast::<PyDict>()?;
+        let json_str: String = py.import("json")?.call_method1("dumps", (info,))?.extract()?;
+        deserialize(&json_str)
+    })
+}
+
+/// Decodifica el JSON devuelto por el wrapper Python.
+fn deserialize<T: DeserializeOwned>(json: &str) -> PyResult<T> {
+    serde_json::from_str(json).map_err(|e| {
+                                  PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Deserialization error: {e}"))
+                              })
+}
+
+/// Serializa a JSON los parámetros que se envían al wrapper Python.
+fn serialize<T: Serialize>(value: &T) -> PyResult<String> {
+    serde_json::to_string(value).map_err(|e| {
+                                    PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Serialization error: {e}"))
+                                })
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawBatchItem<T> {
+    Ok {
+        ok: T,
+    },
+    Error {
+        error: String,
+        #[serde(default)]
+        kind: Option<String>,
+        #[serde(default)]
+        position: Option<usize>,
+        #[serde(default)]
+        issue: Option<String>,
+    },
+}
+
+/// Decodifica la respuesta JSON de una función `*_batch` del wrapper,
+/// preservando el orden de la entrada.
+fn parse_batch<T: DeserializeOwned>(json_str: &str, smiles: &[&str]) -> PyResult<Vec<Result<T, BatchItemError>>> {
+    let raw: Vec<RawBatchItem<T>> = deserialize(json_str)?;
+    if raw.len() != smiles.len() {
+        return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Batch size mismatch: {} != {}",
+                                                                           raw.len(),
+                                                                           smiles.len())));
+    }
+    Ok(raw.into_iter()
+          .enumerate()
+          .map(|(index, item)| match item {
+              RawBatchItem::Ok { ok } => Ok(ok),
+              RawBatchItem::Error { error,
+                                    kind,
+                                    position,
+                                    issue, } => {
+                  Err(BatchItemError { index,
+                                       smiles: smiles[index].to_string(),
+                                       message: error,
+                                       kind: ItemErrorKind::from_wire(kind.as_deref(), position, issue.as_deref()) })
+              }
+          })
+          .collect())
+}
+
+/// Procesa un lote de SMILES con una única llamada a Python (un solo
+/// `Python::attach` y un solo JSON de vuelta). El resultado conserva el orden
+/// de la entrada; los SMILES inválidos producen `Err` en su posición sin
+/// abortar el lote.
+pub fn get_molecules(smiles: &[&str]) -> PyResult<Vec<Result<Molecule, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("molecule_info_batch")?.call1((smiles.to_vec(),))?.extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Versión de `get_molecules` a partir de bloques MOL (registros de un
+/// SDF/MOL). En los errores por ítem, `smiles` lleva el bloque de origen.
+pub fn get_molecules_from_molblocks(molblocks: &[&str]) -> PyResult<Vec<Result<Molecule, BatchItemError>>> {
+    if molblocks.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("molblock_info_batch")?.call1((molblocks.to_vec(),))?.extract()
+    })?;
+    parse_batch(&json_str, molblocks)
+}
+
+/// InChIKey calculado a partir de cada InChI del lote. En los errores por
+/// ítem, `smiles` lleva el InChI de origen.
+pub fn inchikeys_from_inchi(inchis: &[&str]) -> PyResult<Vec<Result<String, BatchItemError>>> {
+    if inchis.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("inchikey_from_inchi_batch")?
+             .call1((inchis.to_vec(),))?
+             .extract()
+    })?;
+    parse_batch(&json_str, inchis)
+}
+
+/// Bloque MOL V2000 (coordenadas 2D) de cada SMILES del lote.
+pub fn molblocks(smiles: &[&str]) -> PyResult<Vec<Result<String, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("to_molblock_batch")?.call1((smiles.to_vec(),))?.extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Estandariza `smiles` con `rdMolStandardize` y devuelve los identificadores
+/// de la estructura resultante.
+pub fn standardize(smiles: &str, options: &StandardizeOptions) -> PyResult<Molecule> {
+    let options = serialize(options)?;
+    Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        let binding = rdkit.getattr("standardize")?.call1((smiles, options))?;
+        let info = binding.downcast::<PyDict>()?;
+        let json_str: String = py.import("json")?.call_method1("dumps", (info,))?.extract()?;
+        deserialize(&json_str)
+    })
+}
+
+/// Versión por lotes de `standardize` (una sola llamada a Python).
+pub fn standardize_batch(smiles: &[&str], options: &StandardizeOptions) -> PyResult<Vec<Result<Molecule, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let options = serialize(options)?;
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("standardize_batch")?
+             .call1((smiles.to_vec(), options))?
+             .extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Fingerprint de un SMILES como bits encendidos.
+pub fn fingerprint(smiles: &str, kind: &FingerprintKind) -> PyResult<FingerprintBits> {
+    let spec = serialize(kind)?;
+    Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        let binding = rdkit.getattr("fingerprint")?.call1((smiles, spec))?;
+        let info = binding.downcast::<PyDict>()?;
+        let json_str: String = py.import("json")?.call_method1("dumps", (info,))?.extract()?;
+        deserialize(&json_str)
+    })
+}
+
+/// Versión por lotes de `fingerprint` (una sola llamada a Python).
+pub fn fingerprints(smiles: &[&str], kind: &FingerprintKind) -> PyResult<Vec<Result<FingerprintBits, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let spec = serialize(kind)?;
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("fingerprint_batch")?.call1((smiles.to_vec(), spec))?.extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Indica si `smiles` contiene la subestructura `smarts`. Un SMARTS o
+/// SMILES inválido produce error.
+pub fn matches_smarts(smiles: &str, smarts: &str) -> PyResult<bool> {
+    Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("matches_smarts")?.call1((smiles, smarts))?.extract()
+    })
+}
+
+/// Versión por lotes de `matches_smarts` (una sola llamada a Python). El
+/// SMARTS se compila una vez; si es inválido falla la llamada completa.
+pub fn matches_smarts_batch(smiles: &[&str], smarts: &str) -> PyResult<Vec<Result<bool, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("matches_smarts_batch")?
+             .call1((smiles.to_vec(), smarts))?
+             .extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Alertas estructurales de los `catalogs` para cada SMILES del lote. Las
+/// alertas de cada ítem vienen ordenadas por (catálogo, descripción).
+pub fn structural_alerts(smiles: &[&str],
+                         catalogs: &[FilterCatalog])
+                         -> PyResult<Vec<Result<Vec<StructuralAlert>, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let names: Vec<&str> = catalogs.iter().map(FilterCatalog::name).collect();
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("structural_alerts_batch")?
+             .call1((smiles.to_vec(), names))?
+             .extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Scaffolds de Bemis–Murcko (SMILES canónico) de cada SMILES del lote.
+/// Las moléculas acíclicas tienen scaffold vacío.
+pub fn murcko_scaffolds(smiles: &[&str]) -> PyResult<Vec<Result<String, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("murcko_scaffold_batch")?.call1((smiles.to_vec(),))?.extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// R-grupos de cada SMILES del lote respecto a `cores` (SMILES o SMARTS).
+pub fn rgroup_decomposition(smiles: &[&str], cores: &[&str]) -> PyResult<Vec<Result<RGroups, BatchItemError>>> {
+    if smiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("rgroup_decomposition_batch")?
+             .call1((smiles.to_vec(), cores.to_vec()))?
+             .extract()
+    })?;
+    parse_batch(&json_str, smiles)
+}
+
+/// Enumera los productos de `smarts` sobre las listas de reactivos
+/// (`reagents[i]` para la plantilla `i`) dentro de `limits`.
+pub fn enumerate_reaction(smarts: &str, reagents: &[Vec<&str>], limits: &EnumerationLimits) -> PyResult<Enumeration> {
+    let limits = serialize(limits)?;
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("enumerate_reaction")?
+             .call1((smarts, reagents.to_vec(), limits))?
+             .extract()
+    })?;
+    deserialize(&json_str)
+}
+
+/// Genera `n` conformeros 3D (ETKDGv3 + `force_field`) con semilla fija
+/// (como mucho `ConformerSet::MAX_SEED`).
+pub fn embed_conformers(smiles: &str, n: usize, seed: u32, force_field: ForceField) -> PyResult<ConformerSet> {
+    let seed = i32::try_from(seed).map_err(|_| {
+                                      pyo3::exceptions::PyValueError::new_err(format!("Semilla fuera de rango para RDKit \
+                                                                                       (máx. {}): {seed}",
+                                                                                      ConformerSet::MAX_SEED))
+                                  })?;
+    let json_str: String = Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        rdkit.getattr("embed_conformers")?
+             .call1((smiles, n, seed, force_field.name()))?
+             .extract()
+    })?;
+    deserialize(&json_str)
+}
+
+/// Calcula descriptores RDKit para `smiles`. `names` vacío = todos los
+/// soportados (`Descriptor::ALL`). Los errores por descriptor (incluidos
+/// nombres desconocidos) quedan en `Descriptors::errors`.
+pub fn descriptors(smiles: &str, names: &[&str]) -> PyResult<Descriptors> {
+    let names: Vec<&str> = if names.is_empty() {
+        Descriptor::ALL.iter().map(Descriptor::name).collect()
+    } else {
+        names.to_vec()
+    };
+    Python::attach(|py| {
+        let rdkit_py = get_module(py)?;
+        let rdkit = rdkit_py.bind(py);
+        let binding = rdkit.getattr("descriptors")?.call1((smiles, names))?;
+        let info = binding.downcast::<PyDict>()?;
+        let json_str: String = py.import("json")?.call_method1("dumps", (info,))?.extract()?;
+        let raw: RawDescriptors = deserialize(&json_str)?;
+        Ok(Descriptors::from_raw(raw))
     })
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -71,4 +379,128 @@ mod tests {
         assert!((mol.mol_weight - 46.07).abs() < 0.1); // Peso molecular
                                                        // aproximado
     }
+    #[test]
+    fn test_toolkit_versions() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let versions = toolkit_versions().unwrap();
+        assert!(versions.rdkit.starts_with("20"));
+        assert_eq!(versions.python.split('.').count(), 3);
+    }
+    #[test]
+    fn test_get_molecules_batch() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let out = get_molecules(&["CCO", "not-a-smiles", "c1ccccc1"]).expect("Fallo en el lote");
+        assert_eq!(out.len(), 3);
+        assert_eq!(out[0].as_ref().unwrap().num_atoms, 3);
+        assert_eq!(out[1].as_ref().unwrap_err().index, 1);
+        assert!(matches!(out[1].as_ref().unwrap_err().kind, ItemErrorKind::InvalidSmiles { .. }));
+        assert_eq!(out[2].as_ref().unwrap().num_atoms, 6);
+        let bad = get_molecules(&["C(C)(C)(C)(C)C", "c1cccc1"]).expect("Fallo en el lote");
+        assert_eq!(bad[0].as_ref().unwrap_err().kind,
+                   ItemErrorKind::Sanitization(crate::SanitizationIssue::Valence));
+        assert_eq!(bad[1].as_ref().unwrap_err().kind,
+                   ItemErrorKind::Sanitization(crate::SanitizationIssue::Aromaticity));
+    }
+    #[test]
+    fn test_parse_batch_error_kinds() {
+        let json = r#"[{"ok": true}, {"error": "syntax error", "kind": "invalid_smiles", "position": 2}, {"error": "x"}]"#;
+        let out: Vec<Result<bool, BatchItemError>> = parse_batch(json, &["C", "CCX", "C"]).unwrap();
+        assert_eq!(out[0], Ok(true));
+        let err = out[1].as_ref().unwrap_err();
+        assert_eq!((err.index, err.kind), (1, ItemErrorKind::InvalidSmiles { position: Some(2) }));
+        assert_eq!(out[2].as_ref().unwrap_err().kind, ItemErrorKind::Other);
+    }
+    #[test]
+    fn test_descriptors() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let d = descriptors("CCO", &["hbd", "tpsa", "no_existe"]).expect("Fallo al calcular descriptores");
+        assert_eq!(d.hbd, Some(1));
+        assert!((d.tpsa.unwrap() - 20.23).abs() < 0.1);
+        assert!(d.errors.contains_key("no_existe"));
+        assert!(d.logp.is_none());
+    }
+    #[test]
+    fn test_smarts_and_alerts() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        assert!(matches_smarts("CC(=O)O", "C(=O)[OX2H1]").unwrap());
+        assert!(!matches_smarts("CCO", "C(=O)[OX2H1]").unwrap());
+        assert!(matches_smarts("CCO", "not a smarts ((").is_err());
+        let out = structural_alerts(&["Oc1ccccc1O", "CCO"], &[FilterCatalog::PainsA, FilterCatalog::Brenk]).unwrap();
+        assert!(!out[0].as_ref().unwrap().is_empty());
+        assert!(out[1].as_ref().unwrap().is_empty());
+    }
+    #[test]
+    fn test_standardize_salt_and_charge() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let acid = get_molecule("CC(=O)O").unwrap();
+        let salt = standardize("CC(=O)[O-].[Na+]", &StandardizeOptions::default()).unwrap();
+        assert_eq!(salt.inchikey, acid.inchikey);
+        let raw = standardize("CC(=O)[O-].[Na+]", &StandardizeOptions::cleanup_only()).unwrap();
+        assert_ne!(raw.inchikey, acid.inchikey);
+    }
+    #[test]
+    fn test_murcko_scaffolds() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let out = murcko_scaffolds(&["c1ccccc1CCO", "CCO", "xx"]).unwrap();
+        assert_eq!(out[0].as_deref().unwrap(), "c1ccccc1");
+        assert_eq!(out[1].as_deref().unwrap(), "");
+        assert!(out[2].is_err());
+    }
+    #[test]
+    fn test_rgroup_decomposition() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let out = rgroup_decomposition(&["c1ccccc1O", "c1ccccc1CC", "CCO", "xx"], &["c1ccccc1"]).unwrap();
+        let phenol = out[0].as_ref().unwrap();
+        assert!(phenol.is_matched());
+        assert_eq!(phenol.rgroups.len(), 1);
+        assert!(out[1].as_ref().unwrap().is_matched());
+        assert!(!out[2].as_ref().unwrap().is_matched());
+        assert!(out[3].is_err());
+    }
+    #[test]
+    fn test_inchikeys_from_inchi() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let ethanol = get_molecule("CCO").unwrap();
+        let out = inchikeys_from_inchi(&[&ethanol.inchi, "InChI=1S/no-es-inchi"]).unwrap();
+        assert_eq!(out[0].as_deref().unwrap(), ethanol.inchikey);
+        assert!(out[1].is_err());
+    }
+    #[test]
+    fn test_molblock_round_trip() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let blocks = molblocks(&["CCO"]).unwrap();
+        let block = blocks[0].as_deref().unwrap();
+        let back = get_molecules_from_molblocks(&[block, "no es un bloque"]).unwrap();
+        assert_eq!(back[0].as_ref().unwrap().inchikey, get_molecule("CCO").unwrap().inchikey);
+        assert!(back[1].is_err());
+    }
+    #[test]
+    fn test_enumerate_amide_coupling() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let amide = "[C:1](=O)[OH].[N;!H0:2]>>[C:1](=O)[N:2]";
+        let limits = EnumerationLimits::default();
+        let out = enumerate_reaction(amide, &[vec!["CC(=O)O", "OC(=O)c1ccccc1"], vec!["CN", "NCCO"]], &limits).unwrap();
+        assert_eq!(out.products.len(), 4);
+        assert_eq!(out.products[0].reagents, vec![0, 0]);
+        assert_eq!(out.products[0].smiles, "CNC(C)=O");
+        assert!(!out.truncated);
+
+        let limited = EnumerationLimits { max_products: 3,
+                                          ..limits };
+        let out = enumerate_reaction(amide, &[vec!["CC(=O)O", "OC(=O)c1ccccc1"], vec!["CN", "NCCO"]], &limited).unwrap();
+        assert_eq!(out.products.len(), 3);
+        assert!(out.truncated);
+        assert!(enumerate_reaction("no es una reacción", &[vec!["C"]], &limits).is_err());
+    }
+    #[test]
+    fn test_embed_conformers_is_seeded() {
+        init_python().expect("Fallo al inicializar Python/RDKit");
+        let a = embed_conformers("CCO", 3, 42, ForceField::Mmff).unwrap();
+        let b = embed_conformers("CCO", 3, 42, ForceField::Mmff).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.atoms.len(), 9);
+        assert_eq!(a.conformers[0].coordinates.len(), 9);
+        assert!(a.conformers.iter().all(|c| c.energy.is_some()));
+        assert!(embed_conformers("CCO", 1, ConformerSet::MAX_SEED + 1, ForceField::Mmff).is_err());
+    }
 }
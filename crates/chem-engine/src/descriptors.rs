@@ -0,0 +1,185 @@
+//! Descriptores moleculares calculados con RDKit (`Descriptors`,
+//! `rdMolDescriptors`, `Crippen`, `QED`).
+//!
+//! `ChemEngine::descriptors(smiles, names)` devuelve un `Descriptors` tipado:
+//! cada descriptor pedido queda con valor o con su error individual en
+//! `errors` (un descriptor fallido no invalida al resto). Un SMILES inválido
+//! sí es un error de la llamada completa.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Descriptores soportados. El nombre estable (`name`) es el usado por el
+/// wrapper Python y por las propiedades del dominio.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Descriptor {
+    LogP,
+    Tpsa,
+    Hbd,
+    Hba,
+    RotatableBonds,
+    RingCount,
+    AromaticRingCount,
+    FormalCharge,
+    FractionCsp3,
+    Qed,
+}
+
+impl Descriptor {
+    pub const ALL: [Descriptor; 10] = [Descriptor::LogP,
+                                       Descriptor::Tpsa,
+                                       Descriptor::Hbd,
+                                       Descriptor::Hba,
+                                       Descriptor::RotatableBonds,
+                                       Descriptor::RingCount,
+                                       Descriptor::AromaticRingCount,
+                                       Descriptor::FormalCharge,
+                                       Descriptor::FractionCsp3,
+                                       Descriptor::Qed];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Descriptor::LogP => "logp",
+            Descriptor::Tpsa => "tpsa",
+            Descriptor::Hbd => "hbd",
+            Descriptor::Hba => "hba",
+            Descriptor::RotatableBonds => "rotatable_bonds",
+            Descriptor::RingCount => "ring_count",
+            Descriptor::AromaticRingCount => "aromatic_ring_count",
+            Descriptor::FormalCharge => "formal_charge",
+            Descriptor::FractionCsp3 => "fraction_csp3",
+            Descriptor::Qed => "qed",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    /// Unidad del valor (`None` = adimensional o conteo).
+    pub fn unit(&self) -> Option<&'static str> {
+        match self {
+            Descriptor::Tpsa => Some("Å²"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Resultado tipado de `ChemEngine::descriptors`. Los campos no pedidos o
+/// fallidos quedan en `None`; los fallos (y nombres desconocidos) se
+/// registran en `errors` con el nombre pedido como clave.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Descriptors {
+    /// SMILES canónico de la molécula evaluada.
+    pub smiles: String,
+    pub logp: Option<f64>,
+    pub tpsa: Option<f64>,
+    pub hbd: Option<u32>,
+    pub hba: Option<u32>,
+    pub rotatable_bonds: Option<u32>,
+    pub ring_count: Option<u32>,
+    pub aromatic_ring_count: Option<u32>,
+    pub formal_charge: Option<i32>,
+    pub fraction_csp3: Option<f64>,
+    pub qed: Option<f64>,
+    pub errors: BTreeMap<String, String>,
+}
+
+impl Descriptors {
+    /// Valor numérico de un descriptor (conteos convertidos a `f64`).
+    pub fn get(&self, descriptor: Descriptor) -> Option<f64> {
+        match descriptor {
+            Descriptor::LogP => self.logp,
+            Descriptor::Tpsa => self.tpsa,
+            Descriptor::Hbd => self.hbd.map(f64::from),
+            Descriptor::Hba => self.hba.map(f64::from),
+            Descriptor::RotatableBonds => self.rotatable_bonds.map(f64::from),
+            Descriptor::RingCount => self.ring_count.map(f64::from),
+            Descriptor::AromaticRingCount => self.aromatic_ring_count.map(f64::from),
+            Descriptor::FormalCharge => self.formal_charge.map(f64::from),
+            Descriptor::FractionCsp3 => self.fraction_csp3,
+            Descriptor::Qed => self.qed,
+        }
+    }
+
+    /// Descriptores con valor, en orden estable.
+    pub fn values(&self) -> Vec<(Descriptor, f64)> {
+        Descriptor::ALL.into_iter()
+                       .filter_map(|d| self.get(d).map(|v| (d, v)))
+                       .collect()
+    }
+
+    #[cfg_attr(not(feature = "rdkit"), allow(dead_code))]
+    fn set(&mut self, descriptor: Descriptor, value: f64) {
+        match descriptor {
+            Descriptor::LogP => self.logp = Some(value),
+            Descriptor::Tpsa => self.tpsa = Some(value),
+            Descriptor::Hbd => self.hbd = Some(value as u32),
+            Descriptor::Hba => self.hba = Some(value as u32),
+            Descriptor::RotatableBonds => self.rotatable_bonds = Some(value as u32),
+            Descriptor::RingCount => self.ring_count = Some(value as u32),
+            Descriptor::AromaticRingCount => self.aromatic_ring_count = Some(value as u32),
+            Descriptor::FormalCharge => self.formal_charge = Some(value as i32),
+            Descriptor::FractionCsp3 => self.fraction_csp3 = Some(value),
+            Descriptor::Qed => self.qed = Some(value),
+        }
+    }
+
+    /// Construye el resultado tipado a partir de la respuesta cruda del
+    /// wrapper Python.
+    #[cfg_attr(not(feature = "rdkit"), allow(dead_code))]
+    pub(crate) fn from_raw(raw: RawDescriptors) -> Self {
+        let mut out = Descriptors { smiles: raw.smiles,
+                                    errors: raw.errors,
+                                    ..Default::default() };
+        for (name, value) in raw.values {
+            match Descriptor::from_name(&name) {
+                Some(d) => out.set(d, value),
+                None => {
+                    out.errors.insert(name, "descriptor desconocido".to_string());
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Respuesta cruda de `rdkit_wrapper.descriptors`.
+#[cfg_attr(not(feature = "rdkit"), allow(dead_code))]
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawDescriptors {
+    pub smiles: String,
+    pub values: BTreeMap<String, f64>,
+    pub errors: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_response_maps_to_typed_fields_and_errors() {
+        let raw: RawDescriptors =
+            serde_json::from_value(serde_json::json!({
+                                       "smiles": "CCO",
+                                       "values": {"logp": -0.0014, "hbd": 1.0, "formal_charge": 0.0},
+                                       "errors": {"qed": "ValueError: boom", "foo": "descriptor desconocido"}
+                                   })).unwrap();
+        let d = Descriptors::from_raw(raw);
+        assert_eq!(d.hbd, Some(1));
+        assert_eq!(d.formal_charge, Some(0));
+        assert_eq!(d.get(Descriptor::LogP), Some(-0.0014));
+        assert_eq!(d.qed, None);
+        assert_eq!(d.errors.len(), 2);
+        assert_eq!(d.values().len(), 3);
+        assert_eq!(Descriptor::from_name("rotatable_bonds"), Some(Descriptor::RotatableBonds));
+    }
+}
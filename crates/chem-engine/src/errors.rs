@@ -0,0 +1,184 @@
+//! Tipificación de las excepciones de Python/RDKit.
+//!
+//! El wrapper lanza `SmilesParseError` (con posición) y `SanitizationError`
+//! (con el motivo); aquí se traducen a variantes de `EngineError` junto con
+//! los fallos del entorno (módulo ausente, intérprete).
+
+#[cfg(feature = "rdkit")]
+use pyo3::exceptions::{PyImportError, PyMemoryError, PyRuntimeError, PySystemError};
+#[cfg(feature = "rdkit")]
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[cfg(feature = "rdkit")]
+use crate::EngineError;
+
+/// Motivo de un fallo de sanitización de RDKit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SanitizationIssue {
+    /// Valencia no permitida (`AtomValenceException`).
+    Valence,
+    /// Sistema aromático que no se puede kekulizar.
+    Aromaticity,
+    Other,
+}
+
+impl SanitizationIssue {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SanitizationIssue::Valence => "valence",
+            SanitizationIssue::Aromaticity => "aromaticity",
+            SanitizationIssue::Other => "other",
+        }
+    }
+
+    #[cfg_attr(not(feature = "rdkit"), allow(dead_code))]
+    fn from_name(name: &str) -> Self {
+        match name {
+            "valence" => SanitizationIssue::Valence,
+            "aromaticity" => SanitizationIssue::Aromaticity,
+            _ => SanitizationIssue::Other,
+        }
+    }
+}
+
+impl fmt::Display for SanitizationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Tipo de error de un ítem de lote (ver `BatchItemError`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ItemErrorKind {
+    /// SMILES sintácticamente inválido; `position` en bytes desde 0.
+    InvalidSmiles {
+        position: Option<usize>,
+    },
+    Sanitization(SanitizationIssue),
+    /// Cualquier otro fallo del cálculo (SMARTS inválido, embedding…).
+    #[default]
+    Other,
+}
+
+#[cfg_attr(not(feature = "rdkit"), allow(dead_code))]
+impl ItemErrorKind {
+    /// Reconstruye el tipo a partir de los campos `kind`/`position`/`issue`
+    /// de un ítem de error del wrapper.
+    pub(crate) fn from_wire(kind: Option<&str>, position: Option<usize>, issue: Option<&str>) -> Self {
+        match kind {
+            Some("invalid_smiles") => ItemErrorKind::InvalidSmiles { position },
+            Some("sanitization") => ItemErrorKind::Sanitization(SanitizationIssue::from_name(issue.unwrap_or_default())),
+            _ => ItemErrorKind::Other,
+        }
+    }
+}
+
+/// Traduce una excepción de Python. Con `smiles` (llamadas de una sola
+/// molécula) los errores químicos se tipifican; `ImportError` es
+/// `MissingModule`, `RuntimeError`/`SystemError`/`MemoryError` son
+/// `Interpreter` y el resto se envuelve con `fallback`.
+#[cfg(feature = "rdkit")]
+pub(crate) fn classify(err: PyErr, smiles: Option<&str>, fallback: fn(PyErr) -> EngineError) -> EngineError {
+    Python::attach(|py| {
+        let value = err.value(py);
+        let message = value.to_string();
+        let name = err.get_type(py).name().map(|n| n.to_string()).unwrap_or_default();
+        match (name.as_str(), smiles) {
+            ("SmilesParseError", Some(smiles)) => {
+                let position = value.getattr("position")
+                                    .and_then(|p| p.extract::<Option<usize>>())
+                                    .ok()
+                                    .flatten();
+                EngineError::InvalidSmiles { smiles: smiles.to_string(),
+                                             position,
+                                             message }
+            }
+            ("SanitizationError", Some(smiles)) => {
+                let issue = value.getattr("issue").and_then(|i| i.extract::<String>()).unwrap_or_default();
+                EngineError::Sanitization { smiles: smiles.to_string(),
+                                            issue: SanitizationIssue::from_name(&issue),
+                                            message }
+            }
+            _ if err.is_instance_of::<PyImportError>(py) => {
+                let module = value.getattr("name")
+                                  .and_then(|n| n.extract::<Option<String>>())
+                                  .ok()
+                                  .flatten()
+                                  .unwrap_or_default();
+                EngineError::MissingModule { module, message }
+            }
+            _ if err.is_instance_of::<PyRuntimeError>(py)
+                 || err.is_instance_of::<PySystemError>(py)
+                 || err.is_instance_of::<PyMemoryError>(py) =>
+            {
+                EngineError::Interpreter(err)
+            }
+            _ => fallback(err),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Las mismas clases de excepción que define el wrapper.
+    #[cfg(feature = "rdkit")]
+    const EXCEPTIONS: &str = concat!("class SmilesParseError(ValueError):\n",
+                                     "    def __init__(self, m, position=None):\n",
+                                     "        super().__init__(m)\n",
+                                     "        self.position = position\n",
+                                     "class SanitizationError(ValueError):\n",
+                                     "    def __init__(self, m, issue):\n",
+                                     "        super().__init__(m)\n",
+                                     "        self.issue = issue\n");
+
+    /// Ejecuta `raise_stmt` en Python y devuelve la excepción.
+    #[cfg(feature = "rdkit")]
+    fn py_error(raise_stmt: &str) -> PyErr {
+        Python::attach(|py| {
+            let code = std::ffi::CString::new(format!("{EXCEPTIONS}{raise_stmt}\n")).unwrap();
+            py.run(code.as_c_str(), None, None).unwrap_err()
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "rdkit")]
+    fn python_exceptions_are_typed() {
+        let err = classify(py_error("raise SmilesParseError('syntax error', 2)"),
+                           Some("CCX"),
+                           EngineError::GetMolecule);
+        assert!(matches!(&err, EngineError::InvalidSmiles { position: Some(2), smiles, .. } if smiles == "CCX"));
+        assert!(err.is_permanent() && !err.is_transient());
+
+        let err = classify(py_error("raise SanitizationError('Explicit valence', 'valence')"),
+                           Some("C(C)(C)(C)(C)C"),
+                           EngineError::GetMolecule);
+        assert!(matches!(err,
+                         EngineError::Sanitization { issue: SanitizationIssue::Valence,
+                                                     .. }));
+
+        let err = classify(py_error("import chemflow_missing_module"), None, EngineError::Init);
+        assert!(matches!(&err, EngineError::MissingModule { module, .. } if module == "chemflow_missing_module"));
+        assert!(!err.is_permanent() && !err.is_transient());
+
+        let err = classify(py_error("raise MemoryError()"), None, EngineError::GetMolecule);
+        assert!(err.is_transient());
+
+        // Sin SMILES (llamada por lotes) no se tipifica como error químico.
+        let err = classify(py_error("raise SmilesParseError('x')"), None, EngineError::GetMolecule);
+        assert!(matches!(err, EngineError::GetMolecule(_)));
+    }
+
+    #[test]
+    fn item_kinds_from_wire() {
+        assert_eq!(ItemErrorKind::from_wire(Some("invalid_smiles"), Some(3), None),
+                   ItemErrorKind::InvalidSmiles { position: Some(3) });
+        assert_eq!(ItemErrorKind::from_wire(Some("sanitization"), None, Some("aromaticity")),
+                   ItemErrorKind::Sanitization(SanitizationIssue::Aromaticity));
+        assert_eq!(ItemErrorKind::from_wire(None, None, None), ItemErrorKind::Other);
+    }
+}
@@ -0,0 +1,53 @@
+//! Especificación de fingerprints calculados con RDKit y su forma cruda
+//! (bits encendidos). La representación compacta y las métricas de
+//! similitud viven en `chem-domain` (`Fingerprint`).
+
+use serde::{Deserialize, Serialize};
+
+/// Tipo de fingerprint y sus parámetros. Serializado (JSON) se envía tal cual
+/// al wrapper Python, y forma parte de la identidad del fingerprint: dos
+/// fingerprints solo son comparables si su `FingerprintKind` coincide.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FingerprintKind {
+    /// Morgan/ECFP (`radius` 2 ≈ ECFP4, 3 ≈ ECFP6).
+    Morgan { radius: u32, n_bits: u32 },
+    /// Claves MACCS (167 bits, el bit 0 siempre apagado).
+    Maccs,
+    /// Fingerprint de caminos de RDKit (`Chem.RDKFingerprint`).
+    RdkitPath { min_path: u32, max_path: u32, n_bits: u32 },
+}
+
+impl FingerprintKind {
+    /// ECFP4: Morgan radio 2, 2048 bits.
+    pub fn ecfp4() -> Self {
+        FingerprintKind::Morgan { radius: 2, n_bits: 2048 }
+    }
+
+    /// ECFP6: Morgan radio 3, 2048 bits.
+    pub fn ecfp6() -> Self {
+        FingerprintKind::Morgan { radius: 3, n_bits: 2048 }
+    }
+
+    /// Fingerprint de caminos con los parámetros por defecto de RDKit.
+    pub fn rdkit_path() -> Self {
+        FingerprintKind::RdkitPath { min_path: 1,
+                                     max_path: 7,
+                                     n_bits: 2048 }
+    }
+
+    /// Longitud esperada del vector de bits.
+    pub fn n_bits(&self) -> u32 {
+        match self {
+            FingerprintKind::Morgan { n_bits, .. } | FingerprintKind::RdkitPath { n_bits, .. } => *n_bits,
+            FingerprintKind::Maccs => 167,
+        }
+    }
+}
+
+/// Fingerprint crudo devuelto por el motor.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FingerprintBits {
+    pub n_bits: u32,
+    pub on_bits: Vec<u32>,
+}
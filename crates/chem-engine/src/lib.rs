@@ -1,29 +1,219 @@
+//! Motor químico: tipos de intercambio con RDKit y, con la feature `rdkit`
+//! (por defecto), el intérprete Python embebido que los calcula.
+
+#[cfg(feature = "rdkit")]
 use pyo3::PyErr;
+#[cfg(feature = "rdkit")]
 use thiserror::Error;
+pub mod conformers;
+#[cfg(feature = "rdkit")]
 pub mod core;
-pub use core::Molecule;
+pub mod descriptors;
+pub mod errors;
+pub mod fingerprints;
+pub mod reactions;
+pub mod records;
+pub mod scaffolds;
+pub mod standardize;
+pub mod substructure;
+pub use conformers::{Conformer, ConformerSet, ForceField};
+pub use descriptors::{Descriptor, Descriptors};
+pub use errors::{ItemErrorKind, SanitizationIssue};
+pub use fingerprints::{FingerprintBits, FingerprintKind};
+pub use reactions::{Enumeration, EnumerationLimits, ReactionProduct};
+pub use records::{BatchItemError, Molecule, ToolkitVersions};
+pub use scaffolds::RGroups;
+pub use standardize::StandardizeOptions;
+pub use substructure::{FilterCatalog, StructuralAlert};
 
+#[cfg(feature = "rdkit")]
 #[derive(Debug, Error)]
 pub enum EngineError {
     #[error("Error inicializando Python/RDKit: {0}")]
     Init(PyErr),
     #[error("Error obteniendo molécula: {0}")]
     GetMolecule(PyErr),
+    #[error("Error calculando descriptores: {0}")]
+    Descriptors(PyErr),
+    #[error("Error calculando fingerprint: {0}")]
+    Fingerprint(PyErr),
+    #[error("Error en búsqueda por subestructura: {0}")]
+    Substructure(PyErr),
+    #[error("Error estandarizando estructura: {0}")]
+    Standardize(PyErr),
+    #[error("Error calculando scaffolds: {0}")]
+    Scaffold(PyErr),
+    #[error("Error generando bloque MOL: {0}")]
+    MolBlock(PyErr),
+    #[error("Error generando conformeros: {0}")]
+    Conformers(PyErr),
+    #[error("Error enumerando la reacción: {0}")]
+    Reaction(PyErr),
+    #[error("SMILES inválido '{smiles}'{}: {message}", position_suffix(.position))]
+    InvalidSmiles {
+        smiles: String,
+        position: Option<usize>,
+        message: String,
+    },
+    #[error("Sanitización fallida de '{smiles}' ({issue}): {message}")]
+    Sanitization {
+        smiles: String,
+        issue: SanitizationIssue,
+        message: String,
+    },
+    #[error("Módulo de Python no disponible '{module}': {message}")]
+    MissingModule { module: String, message: String },
+    #[error("Fallo del intérprete de Python: {0}")]
+    Interpreter(PyErr),
+}
+
+#[cfg(feature = "rdkit")]
+fn position_suffix(position: &Option<usize>) -> String {
+    position.map(|p| format!(" (posición {p})")).unwrap_or_default()
+}
+
+#[cfg(feature = "rdkit")]
+impl EngineError {
+    /// Error de la propia entrada química (SMILES inválido, sanitización):
+    /// reintentar no cambia el resultado.
+    pub fn is_permanent(&self) -> bool {
+        matches!(self, EngineError::InvalidSmiles { .. } | EngineError::Sanitization { .. })
+    }
+
+    /// Fallo del intérprete en tiempo de ejecución; puede resolverse
+    /// reintentando. Un módulo ausente no es transitorio: es del entorno.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, EngineError::Interpreter(_))
+    }
+
+    /// Adaptador para `map_err`: tipifica la excepción (ver
+    /// `errors::classify`) o la envuelve con `fallback`.
+    fn py<'a>(fallback: fn(PyErr) -> EngineError, smiles: Option<&'a str>) -> impl FnOnce(PyErr) -> EngineError + 'a {
+        move |err| errors::classify(err, smiles, fallback)
+    }
 }
 
+#[cfg(feature = "rdkit")]
 pub struct ChemEngine {
     _private: (),
 }
 
+#[cfg(feature = "rdkit")]
 impl ChemEngine {
     pub fn init() -> Result<Self, EngineError> {
-        core::init_python().map_err(EngineError::Init)?;
+        core::init_python().map_err(EngineError::py(EngineError::Init, None))?;
         Ok(Self { _private: () })
     }
+    /// Versiones de RDKit y Python del intérprete embebido.
+    pub fn toolkit_versions(&self) -> Result<ToolkitVersions, EngineError> {
+        core::toolkit_versions().map_err(EngineError::py(EngineError::Init, None))
+    }
     pub fn get_molecule(&self, smiles: &str) -> Result<Molecule, EngineError> {
-        let molecule = core::get_molecule(smiles).map_err(EngineError::GetMolecule)?;
+        let molecule = core::get_molecule(smiles).map_err(EngineError::py(EngineError::GetMolecule, Some(smiles)))?;
         Ok(molecule)
     }
+    /// Versión por lotes de `get_molecule`: una sola llamada a Python. Cada
+    /// posición del resultado corresponde a la misma posición de `smiles`;
+    /// los inválidos son `Err(BatchItemError)` con su índice y no abortan el
+    /// lote.
+    pub fn get_molecules(&self, smiles: &[&str]) -> Result<Vec<Result<Molecule, BatchItemError>>, EngineError> {
+        core::get_molecules(smiles).map_err(EngineError::py(EngineError::GetMolecule, None))
+    }
+    /// Versión de `get_molecules` a partir de bloques MOL (registros de un
+    /// SDF/MOL).
+    pub fn get_molecules_from_molblocks(&self,
+                                        molblocks: &[&str])
+                                        -> Result<Vec<Result<Molecule, BatchItemError>>, EngineError> {
+        core::get_molecules_from_molblocks(molblocks).map_err(EngineError::py(EngineError::GetMolecule, None))
+    }
+    /// InChIKey recalculado a partir de cada InChI (errores por ítem).
+    pub fn inchikeys_from_inchi(&self, inchis: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, EngineError> {
+        core::inchikeys_from_inchi(inchis).map_err(EngineError::py(EngineError::GetMolecule, None))
+    }
+    /// Bloques MOL (coordenadas 2D) por SMILES, p. ej. para exportar a SDF.
+    pub fn molblocks(&self, smiles: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, EngineError> {
+        core::molblocks(smiles).map_err(EngineError::py(EngineError::MolBlock, None))
+    }
+    /// Fingerprint RDKit (Morgan/ECFP, MACCS o caminos) como bits
+    /// encendidos.
+    pub fn fingerprint(&self, smiles: &str, kind: &FingerprintKind) -> Result<FingerprintBits, EngineError> {
+        core::fingerprint(smiles, kind).map_err(EngineError::py(EngineError::Fingerprint, Some(smiles)))
+    }
+    /// Versión por lotes de `fingerprint` (una sola llamada a Python; errores
+    /// por ítem con su índice).
+    pub fn fingerprints(&self,
+                        smiles: &[&str],
+                        kind: &FingerprintKind)
+                        -> Result<Vec<Result<FingerprintBits, BatchItemError>>, EngineError> {
+        core::fingerprints(smiles, kind).map_err(EngineError::py(EngineError::Fingerprint, None))
+    }
+    /// Indica si `smiles` contiene la subestructura `smarts`.
+    pub fn matches_smarts(&self, smiles: &str, smarts: &str) -> Result<bool, EngineError> {
+        core::matches_smarts(smiles, smarts).map_err(EngineError::py(EngineError::Substructure, Some(smiles)))
+    }
+    /// Versión por lotes de `matches_smarts`.
+    pub fn matches_smarts_batch(&self,
+                                smiles: &[&str],
+                                smarts: &str)
+                                -> Result<Vec<Result<bool, BatchItemError>>, EngineError> {
+        core::matches_smarts_batch(smiles, smarts).map_err(EngineError::py(EngineError::Substructure, None))
+    }
+    /// Alertas estructurales (PAINS/Brenk) por SMILES, en orden determinista.
+    pub fn structural_alerts(&self,
+                             smiles: &[&str],
+                             catalogs: &[FilterCatalog])
+                             -> Result<Vec<Result<Vec<StructuralAlert>, BatchItemError>>, EngineError> {
+        core::structural_alerts(smiles, catalogs).map_err(EngineError::py(EngineError::Substructure, None))
+    }
+    /// Estandariza la estructura (`rdMolStandardize`) y devuelve los
+    /// identificadores de la forma estandarizada.
+    pub fn standardize(&self, smiles: &str, options: &StandardizeOptions) -> Result<Molecule, EngineError> {
+        core::standardize(smiles, options).map_err(EngineError::py(EngineError::Standardize, Some(smiles)))
+    }
+    /// Versión por lotes de `standardize`.
+    pub fn standardize_batch(&self,
+                             smiles: &[&str],
+                             options: &StandardizeOptions)
+                             -> Result<Vec<Result<Molecule, BatchItemError>>, EngineError> {
+        core::standardize_batch(smiles, options).map_err(EngineError::py(EngineError::Standardize, None))
+    }
+    /// Scaffolds de Bemis–Murcko por SMILES (vacío si es acíclica).
+    pub fn murcko_scaffolds(&self, smiles: &[&str]) -> Result<Vec<Result<String, BatchItemError>>, EngineError> {
+        core::murcko_scaffolds(smiles).map_err(EngineError::py(EngineError::Scaffold, None))
+    }
+    /// Genera `n` conformeros 3D con ETKDG y los optimiza con
+    /// `force_field`. Con la misma `seed` el resultado es reproducible.
+    pub fn embed_conformers(&self,
+                            smiles: &str,
+                            n: usize,
+                            seed: u32,
+                            force_field: ForceField)
+                            -> Result<ConformerSet, EngineError> {
+        core::embed_conformers(smiles, n, seed, force_field).map_err(EngineError::py(EngineError::Conformers, Some(smiles)))
+    }
+    /// R-grupos de cada SMILES respecto al primer núcleo de `cores` (SMILES
+    /// o SMARTS) en el que encaja.
+    pub fn rgroup_decomposition(&self,
+                                smiles: &[&str],
+                                cores: &[&str])
+                                -> Result<Vec<Result<RGroups, BatchItemError>>, EngineError> {
+        core::rgroup_decomposition(smiles, cores).map_err(EngineError::py(EngineError::Scaffold, None))
+    }
+    /// Enumera los productos del SMARTS de reacción `smarts` sobre todas las
+    /// combinaciones de `reagents` (una lista por plantilla de reactivo), en
+    /// orden determinista y dentro de `limits`.
+    pub fn enumerate_reaction(&self,
+                              smarts: &str,
+                              reagents: &[Vec<&str>],
+                              limits: &EnumerationLimits)
+                              -> Result<Enumeration, EngineError> {
+        core::enumerate_reaction(smarts, reagents, limits).map_err(EngineError::py(EngineError::Reaction, None))
+    }
+    /// Calcula descriptores RDKit (logP, TPSA, HBD/HBA, enlaces rotables,
+    /// anillos, carga formal, fracción sp3, QED). `names` vacío = todos.
+    pub fn descriptors(&self, smiles: &str, names: &[&str]) -> Result<Descriptors, EngineError> {
+        core::descriptors(smiles, names).map_err(EngineError::py(EngineError::Descriptors, Some(smiles)))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,56 @@
+//! Enumeración de productos a partir de un SMARTS de reacción
+//! (`rdChemReactions`).
+
+use serde::{Deserialize, Serialize};
+
+/// Límites de una enumeración. Se envían tal cual (JSON) al wrapper Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct EnumerationLimits {
+    /// Número máximo de productos en total.
+    pub max_products: usize,
+    /// Productos distintos como máximo por combinación de reactivos (una
+    /// reacción puede actuar sobre varios sitios de la misma molécula).
+    pub max_products_per_combination: usize,
+}
+
+impl Default for EnumerationLimits {
+    fn default() -> Self {
+        Self { max_products: 1000,
+               max_products_per_combination: 10 }
+    }
+}
+
+/// Producto enumerado: SMILES canónico e índice del reactivo usado en cada
+/// lista (una por plantilla de reactivo).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReactionProduct {
+    pub smiles: String,
+    pub reagents: Vec<usize>,
+}
+
+/// Resultado de la enumeración. Las combinaciones se recorren en orden
+/// lexicográfico de índices y, dentro de cada una, los productos van
+/// ordenados por SMILES: el orden es determinista. `n_failed` cuenta los
+/// productos que no superaron la sanitización; `truncated` indica que se
+/// alcanzó algún límite.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Enumeration {
+    pub products: Vec<ReactionProduct>,
+    pub n_failed: usize,
+    pub truncated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enumeration_wire_format() {
+        let limits = serde_json::to_value(EnumerationLimits::default()).unwrap();
+        assert_eq!(limits,
+                   serde_json::json!({"max_products": 1000, "max_products_per_combination": 10}));
+        let raw = r#"{"products": [{"smiles": "CC(=O)NC", "reagents": [0, 1]}], "n_failed": 0, "truncated": false}"#;
+        let enumeration: Enumeration = serde_json::from_str(raw).unwrap();
+        assert_eq!(enumeration.products[0].reagents, vec![0, 1]);
+    }
+}
@@ -0,0 +1,35 @@
+//! Registros que devuelve el wrapper Python. No dependen del intérprete:
+//! el dominio los usa también sin la feature `rdkit`.
+
+use serde::Deserialize;
+
+use crate::errors::ItemErrorKind;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Molecule {
+    pub smiles: String,
+    pub inchi: String,
+    pub inchikey: String,
+    pub num_atoms: u32,
+    pub mol_weight: f64,
+    pub mol_formula: String,
+}
+
+/// Versiones del toolkit del intérprete embebido.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ToolkitVersions {
+    pub rdkit: String,
+    pub python: String,
+}
+
+/// Error de un ítem dentro de un lote (`get_molecules`). `index` es la
+/// posición en la entrada y `kind` distingue SMILES inválidos y fallos de
+/// sanitización del resto.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("SMILES #{index} ({smiles}): {message}")]
+pub struct BatchItemError {
+    pub index: usize,
+    pub smiles: String,
+    pub message: String,
+    pub kind: ItemErrorKind,
+}
@@ -0,0 +1,36 @@
+//! Descomposición en R-grupos (`rdRGroupDecomposition`) para análisis SAR.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// R-grupos de una molécula respecto al núcleo con el que encaja. `core` es
+/// `None` (y `rgroups` vacío) si no encaja en ninguno de los núcleos
+/// pedidos. Las claves de `rgroups` son las etiquetas de RDKit (`R1`, `R2`…)
+/// y los valores SMILES con los puntos de unión marcados.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RGroups {
+    pub core: Option<String>,
+    pub rgroups: BTreeMap<String, String>,
+}
+
+impl RGroups {
+    pub fn is_matched(&self) -> bool {
+        self.core.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgroups_wire_format() {
+        let raw = r#"{"core": "c1ccc([*:1])cc1", "rgroups": {"R1": "[H]O[*:1]"}}"#;
+        let rgroups: RGroups = serde_json::from_str(raw).unwrap();
+        assert!(rgroups.is_matched());
+        assert_eq!(rgroups.rgroups["R1"], "[H]O[*:1]");
+        let unmatched: RGroups = serde_json::from_str(r#"{"core": null, "rgroups": {}}"#).unwrap();
+        assert_eq!(unmatched, RGroups::default());
+    }
+}
@@ -0,0 +1,41 @@
+//! Opciones de estandarización estructural (`rdMolStandardize`).
+
+use serde::{Deserialize, Serialize};
+
+/// Pasos de estandarización a aplicar antes de calcular identificadores.
+/// Los pasos se ejecutan siempre en el mismo orden (limpieza, metales,
+/// fragmento mayor, neutralización, tautómero), de modo que las opciones
+/// determinan por completo el resultado para una versión de RDKit dada.
+///
+/// Serializado (JSON) se envía tal cual al wrapper Python.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct StandardizeOptions {
+    /// Rompe enlaces covalentes a metales (`MetalDisconnector`).
+    pub disconnect_metals: bool,
+    /// Conserva el fragmento orgánico mayor (elimina contraiones/sales).
+    pub largest_fragment: bool,
+    /// Neutraliza cargas cuando es posible (`Uncharger`).
+    pub uncharge: bool,
+    /// Tautómero canónico (`TautomerEnumerator::Canonicalize`).
+    pub canonical_tautomer: bool,
+}
+
+impl StandardizeOptions {
+    /// Solo la limpieza básica de RDKit, sin pasos opcionales.
+    pub fn cleanup_only() -> Self {
+        Self { disconnect_metals: false,
+               largest_fragment: false,
+               uncharge: false,
+               canonical_tautomer: false }
+    }
+}
+
+impl Default for StandardizeOptions {
+    /// Todos los pasos activos.
+    fn default() -> Self {
+        Self { disconnect_metals: true,
+               largest_fragment: true,
+               uncharge: true,
+               canonical_tautomer: true }
+    }
+}
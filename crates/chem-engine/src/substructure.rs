@@ -0,0 +1,60 @@
+//! Búsqueda por subestructura (SMARTS) y catálogos de alertas estructurales
+//! (PAINS/Brenk) de RDKit.
+
+use serde::{Deserialize, Serialize};
+
+/// Catálogo de alertas estructurales (`rdkit.Chem.FilterCatalog`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterCatalog {
+    PainsA,
+    PainsB,
+    PainsC,
+    Brenk,
+}
+
+impl FilterCatalog {
+    pub const ALL: [FilterCatalog; 4] = [FilterCatalog::PainsA,
+                                         FilterCatalog::PainsB,
+                                         FilterCatalog::PainsC,
+                                         FilterCatalog::Brenk];
+
+    /// Familias A, B y C de PAINS.
+    pub const PAINS: [FilterCatalog; 3] = [FilterCatalog::PainsA, FilterCatalog::PainsB, FilterCatalog::PainsC];
+
+    /// Nombre estable usado por el wrapper Python.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterCatalog::PainsA => "pains_a",
+            FilterCatalog::PainsB => "pains_b",
+            FilterCatalog::PainsC => "pains_c",
+            FilterCatalog::Brenk => "brenk",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| c.name() == name)
+    }
+}
+
+/// Alerta encontrada en una molécula: catálogo de origen y descripción de la
+/// entrada RDKit (p. ej. `"catechol_A(92)"`).
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StructuralAlert {
+    pub catalog: String,
+    pub description: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalog_names_round_trip() {
+        for c in FilterCatalog::ALL {
+            assert_eq!(FilterCatalog::from_name(c.name()), Some(c));
+            assert_eq!(serde_json::to_string(&c).unwrap(), format!("\"{}\"", c.name()));
+        }
+        assert_eq!(FilterCatalog::from_name("pains"), None);
+    }
+}
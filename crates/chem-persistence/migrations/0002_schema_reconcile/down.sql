@@ -0,0 +1,11 @@
+-- Irreversible: la reconciliación lleva cualquiera de los dos estados 0001 al
+-- esquema canónico y no registra cuál había. Deshacerla a ciegas (p.ej.
+-- borrar `workflow_branches` o la constraint unificada) podría destruir
+-- datos o dejar un esquema que no corresponde a ninguna migración. Se falla
+-- explícitamente para que `diesel migration revert` no informe un éxito
+-- falso; para volver atrás hay que restaurar un respaldo.
+DO $$
+BEGIN
+    RAISE EXCEPTION 'La migración 0002_schema_reconcile es irreversible: restaure un respaldo de la base';
+END
+$$;
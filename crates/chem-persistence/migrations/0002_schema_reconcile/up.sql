@@ -0,0 +1,46 @@
+-- Reconciliación de esquema.
+-- `0001_init` y `0001_event_log` comparten la versión Diesel "0001", por lo
+-- que solo una de ellas se aplica. Esta migración (idempotente) lleva
+-- cualquiera de los dos estados al esquema canónico que verifica
+-- `schema_check`.
+
+-- `0001_init` no crea la tabla de ramas.
+CREATE TABLE IF NOT EXISTS workflow_branches (
+    branch_id UUID PRIMARY KEY,
+    root_flow_id UUID NOT NULL,
+    parent_flow_id UUID NULL,
+    created_from_step_id TEXT NOT NULL,
+    divergence_params_hash TEXT NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    name TEXT NULL,
+    metadata JSONB NULL
+);
+CREATE INDEX IF NOT EXISTS ix_branches_root ON workflow_branches(root_flow_id);
+CREATE INDEX IF NOT EXISTS ix_branches_parent ON workflow_branches(parent_flow_id);
+
+-- `0001_event_log` define el CHECK de event_type como dos constraints de
+-- columna; se unifican en una única constraint nombrada.
+ALTER TABLE event_log
+    DROP CONSTRAINT IF EXISTS event_log_event_type_check;
+ALTER TABLE event_log
+    DROP CONSTRAINT IF EXISTS event_log_event_type_check1;
+ALTER TABLE event_log
+    DROP CONSTRAINT IF EXISTS event_log_event_type_in_check;
+ALTER TABLE event_log
+    ADD CONSTRAINT event_log_event_type_check
+    CHECK (
+        event_type = lower(event_type)
+        AND event_type IN (
+            'flowinitialized',
+            'stepstarted',
+            'stepfinished',
+            'stepfailed',
+            'stepsignal',
+            'propertypreferenceassigned',
+            'retryscheduled',
+            'branchcreated',
+            'userinteractionrequested',
+            'userinteractionprovided',
+            'flowcompleted'
+        )
+    );
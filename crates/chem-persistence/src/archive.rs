@@ -0,0 +1,633 @@
+//! Retención, archivado y compactación de flows antiguos.
+//!
+//! `event_log` es append-only y `workflow_step_artifacts.produced_in_seq`
+//! usa `ON DELETE RESTRICT`, por lo que la base crece sin límite. Este módulo
+//! permite:
+//! 1. Planificar (`FlowArchiver::plan`, dry-run): qué flows completados con
+//!    último evento anterior al corte (`RetentionPolicy::older_than_days`) se
+//!    archivarían, cuántos eventos/artifacts/errores implican y qué artifacts
+//!    se conservarían.
+//! 2. Ejecutar (`FlowArchiver::archive`): exportar cada flow a un `FlowBundle`
+//!    portable (JSON) mediante un `ArchiveSink` y, solo si la exportación fue
+//!    exitosa, borrarlo en una transacción.
+//!
+//! Reglas de seguridad:
+//! - Solo flows con `FlowCompleted`.
+//! - Un flow con ramas hijas aún presentes (`workflow_branches` cuyo
+//!   `branch_id` conserva eventos) queda bloqueado; se podrá archivar cuando
+//!   sus ramas se archiven (en la misma corrida si también son candidatas).
+//! - Un artifact producido por el flow y referenciado (`StepFinished.outputs`)
+//!   por otro flow vivo no se borra: se re-asigna `produced_in_seq` al primer
+//!   evento vivo que lo referencia. El bundle incluye igualmente su copia.
+//! - Si el flow recibió eventos entre la exportación y el borrado, se omite.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Text, Timestamptz, Uuid as SqlUuid};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::config::DbConfig;
+use crate::error::PersistenceError;
+use crate::pg::{ErrorRow, EventRow, PgPool};
+use crate::retry::{with_retry, RetryConfig, RetryMetrics, RetryMetricsSnapshot};
+use crate::schema::{event_log, step_execution_errors, workflow_branches, workflow_step_artifacts};
+use crate::tenant::TenantId;
+
+/// Identificador del formato de bundle portable.
+pub const BUNDLE_FORMAT: &str = "chemflow.flow-bundle";
+/// Versión del formato de bundle (incrementar ante cambios incompatibles).
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// Política de retención.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Antigüedad mínima (en días) del último evento del flow.
+    pub older_than_days: u32,
+    /// Límite opcional de flows a procesar por corrida.
+    pub max_flows: Option<usize>,
+}
+
+impl RetentionPolicy {
+    pub fn older_than_days(days: u32) -> Self {
+        Self { older_than_days: days,
+               max_flows: None }
+    }
+
+    /// Instante de corte: se archivan flows cuyo último evento es anterior.
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - Duration::days(i64::from(self.older_than_days))
+    }
+}
+
+/// Plan (o resultado) de archivado para un flow.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowRetentionPlan {
+    pub flow_id: Uuid,
+    pub last_event_at: DateTime<Utc>,
+    pub events: usize,
+    pub errors: usize,
+    /// Artifacts producidos por el flow que se borrarían.
+    pub artifacts_deleted: usize,
+    /// Artifacts producidos por el flow que se conservan por estar
+    /// referenciados desde flows vivos.
+    pub artifacts_retained: Vec<String>,
+}
+
+/// Flow candidato que no puede archivarse (aún).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockedFlow {
+    pub flow_id: Uuid,
+    pub reason: String,
+    pub live_branches: Vec<Uuid>,
+}
+
+/// Reporte de una corrida (dry-run o ejecución).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub tenant: Option<String>,
+    pub cutoff: DateTime<Utc>,
+    pub flows: Vec<FlowRetentionPlan>,
+    pub blocked: Vec<BlockedFlow>,
+}
+
+impl RetentionReport {
+    pub fn total_events(&self) -> usize {
+        self.flows.iter().map(|f| f.events).sum()
+    }
+
+    pub fn total_artifacts_deleted(&self) -> usize {
+        self.flows.iter().map(|f| f.artifacts_deleted).sum()
+    }
+}
+
+/// Fila de `workflow_step_artifacts` tal como se exporta.
+#[derive(Queryable, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactRow {
+    pub artifact_hash: String,
+    pub kind: String,
+    pub payload: Value,
+    pub metadata: Option<Value>,
+    pub produced_in_seq: i64,
+}
+
+/// Fila de `workflow_branches` tal como se exporta.
+#[derive(Queryable, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchRow {
+    pub branch_id: Uuid,
+    pub root_flow_id: Uuid,
+    pub parent_flow_id: Option<Uuid>,
+    pub created_from_step_id: String,
+    pub divergence_params_hash: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub name: Option<String>,
+    pub metadata: Option<Value>,
+}
+
+/// Exportación portable y autocontenida de un flow: eventos (orden `seq`),
+/// artifacts producidos o referenciados, errores y metadata de ramas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlowBundle {
+    pub format: String,
+    pub format_version: u32,
+    pub flow_id: Uuid,
+    pub tenant: Option<String>,
+    pub archived_at: DateTime<Utc>,
+    pub events: Vec<EventRow>,
+    pub artifacts: Vec<ArtifactRow>,
+    pub errors: Vec<ErrorRow>,
+    pub branches: Vec<BranchRow>,
+}
+
+impl FlowBundle {
+    /// Lee un bundle previamente escrito (p.ej. por `DirectorySink`).
+    pub fn read_from(path: &Path) -> Result<Self, PersistenceError> {
+        let bytes = fs::read(path).map_err(|e| PersistenceError::Unknown(format!("read bundle {}: {e}", path.display())))?;
+        let bundle: FlowBundle = serde_json::from_slice(&bytes).map_err(|e| {
+                                                                   PersistenceError::Unknown(format!("parse bundle {}: {e}",
+                                                                                                     path.display()))
+                                                               })?;
+        if bundle.format != BUNDLE_FORMAT || bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(PersistenceError::Unknown(format!("unsupported bundle format {} v{}",
+                                                         bundle.format, bundle.format_version)));
+        }
+        Ok(bundle)
+    }
+}
+
+/// Destino de los bundles archivados. El borrado del flow solo ocurre si
+/// `write_bundle` devuelve `Ok`.
+pub trait ArchiveSink {
+    fn write_bundle(&mut self, bundle: &FlowBundle) -> Result<(), PersistenceError>;
+}
+
+/// Escribe cada bundle como `<dir>/<flow_id>.json` (escritura atómica vía
+/// archivo temporal + rename).
+pub struct DirectorySink {
+    dir: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, PersistenceError> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|e| PersistenceError::Unknown(format!("create {}: {e}", dir.display())))?;
+        Ok(Self { dir })
+    }
+
+    pub fn bundle_path(&self, flow_id: Uuid) -> PathBuf {
+        self.dir.join(format!("{flow_id}.json"))
+    }
+}
+
+impl ArchiveSink for DirectorySink {
+    fn write_bundle(&mut self, bundle: &FlowBundle) -> Result<(), PersistenceError> {
+        let path = self.bundle_path(bundle.flow_id);
+        let tmp = path.with_extension("json.tmp");
+        let io_err = |e: std::io::Error| PersistenceError::Unknown(format!("write bundle {}: {e}", path.display()));
+        let bytes = serde_json::to_vec(bundle).map_err(|e| PersistenceError::Unknown(format!("serialize bundle: {e}")))?;
+        let mut file = fs::File::create(&tmp).map_err(io_err)?;
+        file.write_all(&bytes).map_err(io_err)?;
+        file.sync_all().map_err(io_err)?;
+        fs::rename(&tmp, &path).map_err(io_err)?;
+        Ok(())
+    }
+}
+
+#[derive(QueryableByName)]
+struct CandidateRow {
+    #[diesel(sql_type = SqlUuid)]
+    flow_id: Uuid,
+    #[diesel(sql_type = Timestamptz)]
+    last_event_at: DateTime<Utc>,
+}
+
+#[derive(QueryableByName)]
+struct LiveBranchRow {
+    #[diesel(sql_type = SqlUuid)]
+    parent: Uuid,
+    #[diesel(sql_type = SqlUuid)]
+    branch_id: Uuid,
+}
+
+#[derive(QueryableByName)]
+struct ProducedArtifactRow {
+    #[diesel(sql_type = Text)]
+    artifact_hash: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    live_seq: Option<i64>,
+}
+
+/// Ejecuta planificación y archivado sobre un pool (opcionalmente confinado a
+/// un tenant).
+pub struct FlowArchiver {
+    pool: PgPool,
+    tenant: Option<TenantId>,
+    retry: RetryConfig,
+    metrics: RetryMetrics,
+}
+
+impl FlowArchiver {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool,
+               tenant: None,
+               retry: RetryConfig::default(),
+               metrics: RetryMetrics::default() }
+    }
+
+    /// Crea el archivador con la política de reintentos de `cfg`
+    /// (`DATABASE_RETRY_*`).
+    pub fn from_config(pool: PgPool, cfg: &DbConfig) -> Self {
+        Self::new(pool).with_retry_config(cfg.retry.clone())
+    }
+
+    /// Confina el archivado al schema del tenant.
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Reemplaza la política de reintentos del borrado (p.ej.
+    /// `DbConfig::from_env().retry`).
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry.normalized();
+        self
+    }
+
+    /// Contadores acumulados de reintentos del borrado.
+    pub fn retry_metrics(&self) -> RetryMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Dry-run: calcula qué se archivaría sin modificar nada.
+    pub fn plan(&self, policy: &RetentionPolicy) -> Result<RetentionReport, PersistenceError> {
+        self.plan_at(policy, Utc::now())
+    }
+
+    /// Igual que `plan` con un "ahora" explícito (reproducible en tests).
+    pub fn plan_at(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<RetentionReport, PersistenceError> {
+        let cutoff = policy.cutoff(now);
+        let mut conn = self.connection()?;
+        conn.build_transaction().read_only().run(|tx| {
+                                                self.scope(tx)?;
+                                                let candidates = load_candidates(tx, cutoff)?;
+                                                let (ready, blocked) = order_candidates(tx, &candidates)?;
+                                                let mut flows = Vec::new();
+                                                for (flow_id, last_event_at) in
+                                                    ready.into_iter().take(policy.max_flows.unwrap_or(usize::MAX))
+                                                {
+                                                    flows.push(plan_flow(tx, flow_id, last_event_at)?);
+                                                }
+                                                Ok::<_, PersistenceError>(RetentionReport { dry_run: true,
+                                                                                            tenant:
+                                                                                                self.tenant
+                                                                                                    .as_ref()
+                                                                                                    .map(|t| t.to_string()),
+                                                                                            cutoff,
+                                                                                            flows,
+                                                                                            blocked })
+                                            })
+    }
+
+    /// Exporta y borra los flows del plan. Cada flow se procesa de forma
+    /// independiente: exportación → `sink.write_bundle` → borrado
+    /// transaccional. El reporte devuelto refleja lo efectivamente borrado.
+    pub fn archive(&self,
+                   policy: &RetentionPolicy,
+                   sink: &mut dyn ArchiveSink)
+                   -> Result<RetentionReport, PersistenceError> {
+        let mut report = self.plan(policy)?;
+        report.dry_run = false;
+        let mut done = Vec::new();
+        for planned in std::mem::take(&mut report.flows) {
+            let bundle = self.export(planned.flow_id)?;
+            sink.write_bundle(&bundle)?;
+            match self.delete_flow(&bundle, planned.last_event_at)? {
+                Ok(result) => done.push(result),
+                Err(reason) => report.blocked.push(BlockedFlow { flow_id: planned.flow_id,
+                                                                 reason,
+                                                                 live_branches: vec![] }),
+            }
+        }
+        report.flows = done;
+        info!("archive:done tenant={:?} flows={} events={} artifacts_deleted={}",
+              report.tenant,
+              report.flows.len(),
+              report.total_events(),
+              report.total_artifacts_deleted());
+        Ok(report)
+    }
+
+    /// Construye el bundle portable de un flow (lectura consistente).
+    pub fn export(&self, flow_id: Uuid) -> Result<FlowBundle, PersistenceError> {
+        let mut conn = self.connection()?;
+        conn.build_transaction().read_only().repeatable_read().run(|tx| {
+            self.scope(tx)?;
+            let events: Vec<EventRow> = event_log::table.filter(event_log::flow_id.eq(flow_id))
+                                                        .order(event_log::seq.asc())
+                                                        .load(tx)?;
+            let seqs: Vec<i64> = events.iter().map(|e| e.seq).collect();
+            let referenced: Vec<String> = events.iter().flat_map(|e| referenced_outputs(&e.payload)).collect();
+            let artifacts: Vec<ArtifactRow> =
+                workflow_step_artifacts::table.filter(workflow_step_artifacts::produced_in_seq.eq_any(&seqs)
+                                                                                            .or(workflow_step_artifacts::artifact_hash.eq_any(&referenced)))
+                                              .order(workflow_step_artifacts::artifact_hash.asc())
+                                              .load(tx)?;
+            let errors: Vec<ErrorRow> = step_execution_errors::table.filter(step_execution_errors::flow_id.eq(flow_id))
+                                                                    .order(step_execution_errors::id.asc())
+                                                                    .load(tx)?;
+            let branches: Vec<BranchRow> =
+                workflow_branches::table.filter(workflow_branches::branch_id.eq(flow_id)
+                                                                           .or(workflow_branches::parent_flow_id.eq(flow_id))
+                                                                           .or(workflow_branches::root_flow_id.eq(flow_id)))
+                                        .order(workflow_branches::branch_id.asc())
+                                        .load(tx)?;
+            Ok::<_, PersistenceError>(FlowBundle { format: BUNDLE_FORMAT.to_string(),
+                                                   format_version: BUNDLE_FORMAT_VERSION,
+                                                   flow_id,
+                                                   tenant: self.tenant.as_ref().map(|t| t.to_string()),
+                                                   archived_at: Utc::now(),
+                                                   events,
+                                                   artifacts,
+                                                   errors,
+                                                   branches })
+        })
+    }
+
+    /// Borra un flow ya exportado. Devuelve `Ok(Err(reason))` si el flow
+    /// cambió desde la exportación o aparecieron ramas vivas. La transacción
+    /// es SERIALIZABLE: los conflictos con escritores concurrentes (`40001`)
+    /// se reintentan con la política del archivador.
+    fn delete_flow(&self,
+                   bundle: &FlowBundle,
+                   last_event_at: DateTime<Utc>)
+                   -> Result<Result<FlowRetentionPlan, String>, PersistenceError> {
+        with_retry(&self.retry, &self.metrics, || self.delete_flow_once(bundle, last_event_at))
+    }
+
+    fn delete_flow_once(&self,
+                        bundle: &FlowBundle,
+                        last_event_at: DateTime<Utc>)
+                        -> Result<Result<FlowRetentionPlan, String>, PersistenceError> {
+        let flow_id = bundle.flow_id;
+        let mut conn = self.connection()?;
+        conn.build_transaction().serializable().run(|tx| {
+            self.scope(tx)?;
+            let current: i64 = event_log::table.filter(event_log::flow_id.eq(flow_id)).count().get_result(tx)?;
+            if current as usize != bundle.events.len() {
+                return Ok(Err(format!("flow changed since export ({} → {current} events)", bundle.events.len())));
+            }
+            if !live_branches(tx, &[flow_id])?.is_empty() {
+                return Ok(Err("live branches appeared since planning".to_string()));
+            }
+            let produced = load_produced_artifacts(tx, flow_id)?;
+            let mut retained = Vec::new();
+            let mut deleted = 0;
+            for a in &produced {
+                match a.live_seq {
+                    Some(seq) => {
+                        diesel::update(workflow_step_artifacts::table.find(&a.artifact_hash))
+                            .set(workflow_step_artifacts::produced_in_seq.eq(seq))
+                            .execute(tx)?;
+                        retained.push(a.artifact_hash.clone());
+                    }
+                    None => {
+                        deleted += diesel::delete(workflow_step_artifacts::table.find(&a.artifact_hash)).execute(tx)?;
+                    }
+                }
+            }
+            let errors = diesel::delete(step_execution_errors::table.filter(step_execution_errors::flow_id.eq(flow_id)))
+                .execute(tx)?;
+            diesel::delete(workflow_branches::table.filter(workflow_branches::branch_id.eq(flow_id))).execute(tx)?;
+            let events = diesel::delete(event_log::table.filter(event_log::flow_id.eq(flow_id))).execute(tx)?;
+            debug!("archive:deleted flow_id={flow_id} events={events} artifacts={deleted} retained={}", retained.len());
+            Ok::<_, PersistenceError>(Ok(FlowRetentionPlan { flow_id,
+                                                             last_event_at,
+                                                             events,
+                                                             errors,
+                                                             artifacts_deleted: deleted,
+                                                             artifacts_retained: retained }))
+        })
+    }
+
+    fn connection(&self) -> Result<r2d2::PooledConnection<diesel::r2d2::ConnectionManager<PgConnection>>, PersistenceError> {
+        self.pool
+            .get()
+            .map_err(|e| PersistenceError::TransientIo(format!("pool get for archive: {e}")))
+    }
+
+    fn scope(&self, conn: &mut PgConnection) -> Result<(), diesel::result::Error> {
+        if let Some(tenant) = &self.tenant {
+            diesel::sql_query(tenant.set_local_search_path_sql()).execute(conn)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hashes referenciados por un payload `StepFinished` (`outputs`).
+fn referenced_outputs(payload: &Value) -> Vec<String> {
+    payload.pointer("/StepFinished/outputs")
+           .and_then(Value::as_array)
+           .map(|arr| arr.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+           .unwrap_or_default()
+}
+
+/// Flows completados cuyo último evento es anterior al corte, ordenados por
+/// antigüedad.
+fn load_candidates(conn: &mut PgConnection, cutoff: DateTime<Utc>) -> Result<Vec<(Uuid, DateTime<Utc>)>, PersistenceError> {
+    let rows: Vec<CandidateRow> =
+        diesel::sql_query("SELECT flow_id, max(ts) AS last_event_at FROM event_log GROUP BY flow_id \
+                                                     HAVING bool_or(event_type = 'flowcompleted') AND max(ts) < $1 \
+                                                     ORDER BY last_event_at, flow_id").bind::<Timestamptz, _>(cutoff)
+                                                                                      .load(conn)?;
+    Ok(rows.into_iter().map(|r| (r.flow_id, r.last_event_at)).collect())
+}
+
+/// Ramas hijas (de cualquiera de `parents`) que aún conservan eventos.
+fn live_branches(conn: &mut PgConnection, parents: &[Uuid]) -> Result<Vec<(Uuid, Uuid)>, PersistenceError> {
+    let rows: Vec<LiveBranchRow> =
+        diesel::sql_query("SELECT COALESCE(b.parent_flow_id, b.root_flow_id) AS parent, b.branch_id FROM workflow_branches b \
+                           WHERE COALESCE(b.parent_flow_id, b.root_flow_id) = ANY($1) AND b.branch_id <> ALL($1) \
+                           AND EXISTS (SELECT 1 FROM event_log e WHERE e.flow_id = b.branch_id) \
+                           ORDER BY parent, b.branch_id").bind::<diesel::sql_types::Array<SqlUuid>, _>(parents)
+                                                         .load(conn)?;
+    Ok(rows.into_iter().map(|r| (r.parent, r.branch_id)).collect())
+}
+
+/// Separa candidatos archivables de bloqueados y ordena los archivables de
+/// modo que cada rama precede a su padre (el padre deja de tener ramas vivas
+/// una vez archivadas sus ramas).
+#[allow(clippy::type_complexity)]
+fn order_candidates(conn: &mut PgConnection,
+                    candidates: &[(Uuid, DateTime<Utc>)])
+                    -> Result<(Vec<(Uuid, DateTime<Utc>)>, Vec<BlockedFlow>), PersistenceError> {
+    let ids: Vec<Uuid> = candidates.iter().map(|(id, _)| *id).collect();
+    // Ramas vivas que NO son candidatas bloquean a su padre.
+    let mut children: BTreeMap<Uuid, Vec<Uuid>> = BTreeMap::new();
+    let all_branches: Vec<LiveBranchRow> =
+        diesel::sql_query("SELECT COALESCE(b.parent_flow_id, b.root_flow_id) AS parent, b.branch_id FROM workflow_branches b \
+                           WHERE COALESCE(b.parent_flow_id, b.root_flow_id) = ANY($1) AND b.branch_id <> COALESCE(b.parent_flow_id, b.root_flow_id) \
+                           AND EXISTS (SELECT 1 FROM event_log e WHERE e.flow_id = b.branch_id) \
+                           ORDER BY parent, b.branch_id").bind::<diesel::sql_types::Array<SqlUuid>, _>(&ids)
+                                                         .load(conn)?;
+    for b in all_branches {
+        children.entry(b.parent).or_default().push(b.branch_id);
+    }
+    Ok(resolve_order(candidates, &children))
+}
+
+/// Núcleo puro de `order_candidates`: bloqueo transitivo y orden
+/// ramas-antes-que-padres, estable respecto al orden de entrada.
+#[allow(clippy::type_complexity)]
+fn resolve_order(candidates: &[(Uuid, DateTime<Utc>)],
+                 children: &BTreeMap<Uuid, Vec<Uuid>>)
+                 -> (Vec<(Uuid, DateTime<Utc>)>, Vec<BlockedFlow>) {
+    let candidate_set: BTreeSet<Uuid> = candidates.iter().map(|(id, _)| *id).collect();
+    let mut blocked: BTreeMap<Uuid, Vec<Uuid>> = BTreeMap::new();
+    // Punto fijo: un flow queda bloqueado si alguna rama viva no es candidata
+    // o está bloqueada.
+    loop {
+        let mut changed = false;
+        for (id, _) in candidates {
+            if blocked.contains_key(id) {
+                continue;
+            }
+            let live: Vec<Uuid> = children.get(id)
+                                          .map(|c| {
+                                              c.iter()
+                                               .filter(|b| !candidate_set.contains(b) || blocked.contains_key(b))
+                                               .copied()
+                                               .collect()
+                                          })
+                                          .unwrap_or_default();
+            if !live.is_empty() {
+                blocked.insert(*id, live);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let mut ready = Vec::new();
+    let mut emitted: BTreeSet<Uuid> = BTreeSet::new();
+    let pending: Vec<(Uuid, DateTime<Utc>)> = candidates.iter()
+                                                        .filter(|(id, _)| !blocked.contains_key(id))
+                                                        .copied()
+                                                        .collect();
+    while emitted.len() < pending.len() {
+        let before = emitted.len();
+        for (id, ts) in &pending {
+            if emitted.contains(id) {
+                continue;
+            }
+            let waits = children.get(id).is_some_and(|c| c.iter().any(|b| !emitted.contains(b)));
+            if !waits {
+                emitted.insert(*id);
+                ready.push((*id, *ts));
+            }
+        }
+        if emitted.len() == before {
+            // Ciclo (no debería ocurrir): el borrado re-verifica ramas vivas.
+            ready.extend(pending.iter().filter(|(id, _)| !emitted.contains(id)).copied());
+            break;
+        }
+    }
+    let blocked = candidates.iter()
+                            .filter_map(|(id, _)| {
+                                blocked.get(id).map(|live| BlockedFlow { flow_id: *id,
+                                                                         reason: "has live branches".to_string(),
+                                                                         live_branches: live.clone() })
+                            })
+                            .collect();
+    (ready, blocked)
+}
+
+/// Artifacts producidos por el flow y, si los hay, el primer `seq` de otro
+/// flow que los referencia (destino de re-asignación).
+fn load_produced_artifacts(conn: &mut PgConnection, flow_id: Uuid) -> Result<Vec<ProducedArtifactRow>, PersistenceError> {
+    Ok(diesel::sql_query("SELECT a.artifact_hash, \
+                          (SELECT min(e.seq) FROM event_log e WHERE e.flow_id <> $1 AND e.event_type = 'stepfinished' \
+                           AND e.payload #> '{StepFinished,outputs}' @> jsonb_build_array(a.artifact_hash)) AS live_seq \
+                          FROM workflow_step_artifacts a JOIN event_log p ON p.seq = a.produced_in_seq \
+                          WHERE p.flow_id = $1 ORDER BY a.artifact_hash").bind::<SqlUuid, _>(flow_id)
+                                                                         .load(conn)?)
+}
+
+fn plan_flow(conn: &mut PgConnection,
+             flow_id: Uuid,
+             last_event_at: DateTime<Utc>)
+             -> Result<FlowRetentionPlan, PersistenceError> {
+    let events: i64 = event_log::table.filter(event_log::flow_id.eq(flow_id))
+                                      .count()
+                                      .get_result(conn)?;
+    let errors: i64 = step_execution_errors::table.filter(step_execution_errors::flow_id.eq(flow_id))
+                                                  .count()
+                                                  .get_result(conn)?;
+    let produced = load_produced_artifacts(conn, flow_id)?;
+    let artifacts_retained: Vec<String> = produced.iter()
+                                                  .filter(|a| a.live_seq.is_some())
+                                                  .map(|a| a.artifact_hash.clone())
+                                                  .collect();
+    Ok(FlowRetentionPlan { flow_id,
+                           last_event_at,
+                           events: events as usize,
+                           errors: errors as usize,
+                           artifacts_deleted: produced.len() - artifacts_retained.len(),
+                           artifacts_retained })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branches_are_archived_before_parents_and_live_ones_block() {
+        let t = Utc::now();
+        let (root, branch, other_root, live) =
+            (Uuid::from_u128(1), Uuid::from_u128(2), Uuid::from_u128(3), Uuid::from_u128(4));
+        let candidates = vec![(root, t), (branch, t), (other_root, t)];
+        let mut children = BTreeMap::new();
+        children.insert(root, vec![branch]);
+        children.insert(other_root, vec![live]);
+        let (ready, blocked) = resolve_order(&candidates, &children);
+        assert_eq!(ready.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![branch, root]);
+        assert_eq!(blocked.len(), 1);
+        assert_eq!(blocked[0].flow_id, other_root);
+        assert_eq!(blocked[0].live_branches, vec![live]);
+    }
+
+    #[test]
+    fn referenced_outputs_reads_step_finished_payload() {
+        let payload = serde_json::json!({"StepFinished": {"step_index": 0, "step_id": "s", "outputs": ["a", "b"],
+                                                           "fingerprint": "f", "outputs_payloads": null}});
+        assert_eq!(referenced_outputs(&payload), vec!["a", "b"]);
+        assert!(referenced_outputs(&serde_json::json!({"FlowCompleted": {"flow_fingerprint": "x"}})).is_empty());
+    }
+
+    #[test]
+    fn directory_sink_round_trips_bundle() {
+        let dir = std::env::temp_dir().join(format!("chemflow-archive-{}", Uuid::new_v4()));
+        let mut sink = DirectorySink::new(&dir).unwrap();
+        let bundle = FlowBundle { format: BUNDLE_FORMAT.to_string(),
+                                  format_version: BUNDLE_FORMAT_VERSION,
+                                  flow_id: Uuid::new_v4(),
+                                  tenant: None,
+                                  archived_at: Utc::now(),
+                                  events: vec![],
+                                  artifacts: vec![],
+                                  errors: vec![],
+                                  branches: vec![] };
+        sink.write_bundle(&bundle).unwrap();
+        let back = FlowBundle::read_from(&sink.bundle_path(bundle.flow_id)).unwrap();
+        assert_eq!(back, bundle);
+        fs::remove_dir_all(dir).ok();
+    }
+}
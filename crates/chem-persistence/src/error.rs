@@ -4,9 +4,11 @@
 //!
 //! Objetivo: encapsular detalles del driver y exponer un conjunto acotado de
 //! errores para el resto de la capa. Algunos errores son recuperables (p.ej.
-//! conflictos de serialización) y otros no.
+//! conflictos de serialización) y otros no; la decisión se toma a partir del
+//! SQLSTATE (ver `PersistenceError::sqlstate` y `retry::is_retryable`).
 
-use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::result::{DatabaseErrorInformation, DatabaseErrorKind, Error as DieselError};
+use log::{debug, warn};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -23,6 +25,16 @@ pub enum PersistenceError {
     SerializationConflict,
     #[error("transient IO / connection pool error: {0}")]
     TransientIo(String),
+    /// Error de base de datos sin variante semántica propia. `sqlstate` es el
+    /// código SQLSTATE cuando pudo determinarse.
+    #[error("database error (sqlstate={sqlstate:?}): {message}")]
+    Database { sqlstate: Option<String>, message: String },
+    #[error("invalid configuration: {0}")]
+    Config(String),
+    /// La base viva no coincide con el esquema esperado (ver
+    /// `schema_check`).
+    #[error("schema drift detected: {}", .0.iter().map(|d| d.to_string()).collect::<Vec<_>>().join("; "))]
+    SchemaDrift(Vec<crate::schema_check::SchemaDrift>),
     #[error("unknown database error: {0}")]
     Unknown(String),
 }
@@ -36,7 +48,8 @@ impl From<DieselError> for PersistenceError {
                 DatabaseErrorKind::CheckViolation => Self::CheckViolation(info.message().to_string()),
                 DatabaseErrorKind::ForeignKeyViolation => Self::ForeignKeyViolation(info.message().to_string()),
                 DatabaseErrorKind::SerializationFailure => Self::SerializationConflict,
-                other => Self::Unknown(format!("db error kind {:?}: {}", other, info.message())),
+                other => Self::Database { sqlstate: sqlstate_of(&other, info.as_ref()).map(str::to_string),
+                                          message: info.message().to_string() },
             },
             DieselError::DeserializationError(e) => Self::Unknown(format!("deser: {e}")),
             DieselError::SerializationError(e) => Self::Unknown(format!("ser: {e}")),
@@ -54,3 +67,114 @@ impl From<DieselError> for PersistenceError {
         }
     }
 }
+
+impl PersistenceError {
+    /// Código SQLSTATE asociado al error, si se conoce.
+    pub fn sqlstate(&self) -> Option<&str> {
+        match self {
+            Self::UniqueViolation(_) => Some("23505"),
+            Self::CheckViolation(_) => Some("23514"),
+            Self::ForeignKeyViolation(_) => Some("23503"),
+            Self::SerializationConflict => Some("40001"),
+            Self::Database { sqlstate, .. } => sqlstate.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// SQLSTATE de un error de base de datos de Diesel.
+///
+/// El backend Postgres de Diesel lee el SQLSTATE del `PGresult` (campo `C`)
+/// y lo traduce a `DatabaseErrorKind`, pero no expone el código: el
+/// `PGresult` queda dentro de un tipo privado del driver y
+/// `DatabaseErrorInformation` sólo ofrece mensaje, detalle, hint y
+/// tabla/columna/restricción. Por eso:
+///
+/// 1. Los `DatabaseErrorKind` que Diesel deriva del SQLSTATE se devuelven a su
+///    código (`sqlstate_of_kind`).
+/// 2. Sólo para `DatabaseErrorKind::Unknown`, y como último recurso, se
+///    reconoce el mensaje primario de Postgres (`sqlstate_from_message`). Cada
+///    uso queda en el log; si el servidor traduce sus mensajes (`lc_messages`
+///    distinto de inglés) la inferencia falla y el error se trata como no
+///    reintentable.
+fn sqlstate_of(kind: &DatabaseErrorKind, info: &dyn DatabaseErrorInformation) -> Option<&'static str> {
+    if let Some(code) = sqlstate_of_kind(kind) {
+        return Some(code);
+    }
+    if !matches!(kind, DatabaseErrorKind::Unknown) {
+        return None;
+    }
+    let code = sqlstate_from_message(info.message());
+    match code {
+        Some(code) => debug!("SQLSTATE {code} inferido del mensaje del servidor (Diesel no expone el código): {}",
+                             info.message()),
+        None => warn!("SQLSTATE desconocido (Diesel no expone el código y el mensaje no se reconoce): {}",
+                      info.message()),
+    }
+    code
+}
+
+/// Código SQLSTATE de los `DatabaseErrorKind` que Diesel obtiene del propio
+/// SQLSTATE. Diesel agrupa la clase `08` en `ClosedConnection`, así que se
+/// devuelve `08006` (connection_failure) como representante;
+/// `UnableToSendCommand` es un fallo del cliente sin SQLSTATE y se asimila a
+/// `08000` (connection_exception).
+fn sqlstate_of_kind(kind: &DatabaseErrorKind) -> Option<&'static str> {
+    match kind {
+        DatabaseErrorKind::UniqueViolation => Some("23505"),
+        DatabaseErrorKind::ForeignKeyViolation => Some("23503"),
+        DatabaseErrorKind::NotNullViolation => Some("23502"),
+        DatabaseErrorKind::CheckViolation => Some("23514"),
+        DatabaseErrorKind::SerializationFailure => Some("40001"),
+        DatabaseErrorKind::ReadOnlyTransaction => Some("25006"),
+        DatabaseErrorKind::ClosedConnection => Some("08006"),
+        DatabaseErrorKind::UnableToSendCommand => Some("08000"),
+        _ => None,
+    }
+}
+
+/// Último recurso: SQLSTATE de las condiciones transitorias (clases
+/// 40/53/55/57) a partir del mensaje primario de Postgres en inglés.
+fn sqlstate_from_message(message: &str) -> Option<&'static str> {
+    const KNOWN: &[(&str, &str)] = &[("deadlock detected", "40P01"),
+                                     ("could not serialize access", "40001"),
+                                     ("could not obtain lock", "55P03"),
+                                     ("sorry, too many clients already", "53300"),
+                                     ("remaining connection slots are reserved", "53300"),
+                                     ("terminating connection due to administrator command", "57P01"),
+                                     ("terminating connection because of crash of another server process", "57P02"),
+                                     ("the database system is starting up", "57P03"),
+                                     ("the database system is shutting down", "57P03"),
+                                     ("canceling statement due to statement timeout", "57014")];
+    let m = message.to_lowercase();
+    KNOWN.iter().find(|(needle, _)| m.contains(needle)).map(|(_, code)| *code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_error(kind: DatabaseErrorKind, message: &str) -> PersistenceError {
+        DieselError::DatabaseError(kind, Box::new(message.to_string())).into()
+    }
+
+    #[test]
+    fn sqlstate_comes_from_the_driver_kind() {
+        assert_eq!(db_error(DatabaseErrorKind::SerializationFailure, "x").sqlstate(),
+                   Some("40001"));
+        assert_eq!(db_error(DatabaseErrorKind::ClosedConnection, "x").sqlstate(), Some("08006"));
+        assert_eq!(db_error(DatabaseErrorKind::NotNullViolation, "x").sqlstate(), Some("23502"));
+        // El mensaje sólo se consulta cuando Diesel no pudo clasificar el
+        // error.
+        assert_eq!(db_error(DatabaseErrorKind::ReadOnlyTransaction, "deadlock detected").sqlstate(),
+                   Some("25006"));
+    }
+
+    #[test]
+    fn message_fallback_only_for_unknown_kind() {
+        assert_eq!(db_error(DatabaseErrorKind::Unknown, "deadlock detected").sqlstate(),
+                   Some("40P01"));
+        assert_eq!(db_error(DatabaseErrorKind::Unknown, "se detectó un interbloqueo").sqlstate(),
+                   None);
+    }
+}
@@ -7,17 +7,32 @@
 //! permitir iteración incremental sin romper contratos del core.
 //!
 //! Módulos:
+//! - `archive`: retención/archivado de flows antiguos a bundles portables.
 //! - `pg`: implementaciones sobre Postgres (append-only event_log y artifacts).
 //! - `migrations`: runner embebido de migraciones Diesel.
 //! - `config`: carga de configuración desde .env.
+//! - `retry`: política de reintentos configurable y métricas.
+//! - `tenant`: aislamiento multi-tenant por schema.
 //! - `schema`: tablas Diesel declaradas para compilar queries.
+//! - `schema_check`: verificación de drift entre la base y `schema`.
 
+pub mod archive;
 pub mod config;
 pub mod error;
 pub mod migrations;
 pub mod pg;
+pub mod retry;
 pub mod schema; // generado manualmente para F3
+pub mod schema_check;
+pub mod tenant;
 
-pub use config::init_dotenv;
+pub use archive::{ArchiveSink, DirectorySink, FlowArchiver, FlowBundle, RetentionPolicy, RetentionReport};
+pub use config::{init_dotenv, DbConfig, GssEncMode, TlsMode};
 pub use error::PersistenceError;
-pub use pg::{build_dev_pool_from_env, ConnectionProvider, ErrorRow, PgEventStore, PgFlowRepository, PgPool, PoolProvider};
+pub use pg::{
+    build_dev_pool_and_config_from_env, build_dev_pool_from_env, build_named_pools_from_env, build_pool_from_config,
+    list_tenants, migrate_tenant, ConnectionProvider, ErrorRow, PgEventStore, PgFlowRepository, PgPool, PoolProvider,
+};
+pub use retry::{RetryConfig, RetryMetricsSnapshot};
+pub use schema_check::{check_schema, verify_schema, verify_tenant_schema, SchemaDrift};
+pub use tenant::TenantId;
@@ -2,11 +2,14 @@
 //! En etapas iniciales solo expone función `run_pending_migrations`.
 //!
 //! Se espera que exista un directorio `migrations/` en este crate con las
-//! migraciones Diesel. Al inicializar el pool se ejecutan una vez.
+//! migraciones Diesel. Al inicializar el pool se ejecutan una vez (schema por
+//! defecto); los schemas de tenant se migran con `run_tenant_migrations`.
 
 use crate::error::PersistenceError;
+use crate::tenant::TenantId;
 use diesel::connection::SimpleConnection;
 use diesel::pg::PgConnection;
+use diesel::{Connection, RunQueryDsl};
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
 // Directorio esperado: `migrations/` en este crate.
@@ -20,3 +23,20 @@ pub fn run_pending_migrations(conn: &mut PgConnection) -> Result<(), Persistence
         .map(|_| ())
         .map_err(|e| PersistenceError::Unknown(format!("migration error: {e}")))
 }
+
+/// Crea (si no existe) el schema del tenant y aplica sobre él las migraciones
+/// pendientes.
+///
+/// Todo ocurre en una única transacción con `SET LOCAL search_path` al schema
+/// del tenant: las tablas (incluida `__diesel_schema_migrations`) se crean
+/// dentro del schema y la conexión no queda alterada al devolverse al pool.
+pub fn run_tenant_migrations(conn: &mut PgConnection, tenant: &TenantId) -> Result<(), PersistenceError> {
+    conn.batch_execute("CREATE EXTENSION IF NOT EXISTS pgcrypto;").ok();
+    conn.transaction::<_, PersistenceError, _>(|tx| {
+            diesel::sql_query(format!("CREATE SCHEMA IF NOT EXISTS {}", tenant.schema_name())).execute(tx)?;
+            diesel::sql_query(tenant.set_local_search_path_sql()).execute(tx)?;
+            tx.run_pending_migrations(MIGRATIONS)
+              .map(|_| ())
+              .map_err(|e| PersistenceError::Unknown(format!("migration error (tenant {tenant}): {e}")))
+        })
+}
@@ -15,25 +15,37 @@
 //! - Inserción opcional de artifacts de step dentro de la MISMA transacción del
 //!   evento `StepFinished` (atomicidad cuando el feature de artifacts está
 //!   activo; desactivable con feature `no-artifact-insert`).
-//! - Manejo básico de errores transitorios: reintento con backoff en `append` y
-//!   `list`.
+//! - Manejo de errores transitorios: reintento con backoff configurable
+//!   (`RetryConfig`) en `append` y `list`, clasificado por SQLSTATE y con
+//!   métricas expuestas vía `PgEventStore::retry_metrics`.
 //! - `PgFlowRepository`: delega el replay a la implementación InMemory para
 //!   asegurar paridad exacta.
+//! - Multi-tenant: `PgEventStore::with_tenant` + `migrate_tenant` aíslan cada
+//!   proyecto en su propio schema.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
 
 use chem_core::repo::FlowInstance;
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use diesel::r2d2::{self, ConnectionManager};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use uuid::Uuid;
 
-use chem_core::errors::{classify_error, ErrorClass};
+use chem_core::errors::{classify_error, CoreEngineError, ErrorClass};
 use chem_core::{EventStore, FlowDefinition, FlowEvent, FlowEventKind, FlowRepository, InMemoryFlowRepository};
 use log::{debug, error, warn};
+use once_cell::sync::Lazy;
 
+use crate::config::{load_named_configs, DbConfig};
 use crate::error::PersistenceError;
-use crate::migrations::run_pending_migrations;
+use crate::migrations::{run_pending_migrations, run_tenant_migrations};
+use crate::retry::{with_retry, RetryConfig, RetryMetrics, RetryMetricsSnapshot};
 use crate::schema::{event_log, step_execution_errors, workflow_step_artifacts};
+use crate::schema_check::verify_schema;
+use crate::tenant::{TenantId, TENANT_SCHEMA_PREFIX};
 
 /// Alias de tipo para el pool r2d2 de conexiones Postgres.
 ///
@@ -83,7 +95,7 @@ impl ConnectionProvider for PoolProvider {
 /// - `error_class`: clasificación del error.
 /// - `details`: JSONB con detalles.
 /// - `ts`: timestamp.
-#[derive(Queryable, Debug)]
+#[derive(Queryable, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ErrorRow {
     pub id: i64,
     pub flow_id: uuid::Uuid,
@@ -149,7 +161,7 @@ pub struct NewBranchRow {
 /// - `ts`: timestamp asignado por la base de datos (DEFAULT now()).
 /// - `event_type`: pista/constraint (minúsculas) del tipo de evento.
 /// - `payload`: JSONB con la representación completa del enum `FlowEventKind`.
-#[derive(Queryable, Debug)]
+#[derive(Queryable, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EventRow {
     pub seq: i64,
     pub flow_id: uuid::Uuid,
@@ -176,62 +188,6 @@ pub struct NewErrorRow<'a> {
     pub details: Option<&'a Value>,
 }
 
-/// Determina si un error es transitorio (recomendado reintentar con backoff).
-///
-/// Cubre:
-/// - Conflictos de serialización (deadlocks y nivel de aislamiento).
-/// - Errores de IO transitorios de pool/conexión.
-/// - Mensajes comunes de desconexión/timeout detectados por texto
-///   (best-effort).
-fn is_retryable(e: &PersistenceError) -> bool {
-    match e {
-        PersistenceError::SerializationConflict => true,
-        PersistenceError::TransientIo(_) => true,
-        // Algunos mensajes de error (dependen de driver/pg) pueden llegar como Unknown
-        // con texto. Hacemos best-effort string match sin acoplar a SQLSTATE.
-        PersistenceError::Unknown(msg) => {
-            let m = msg.to_lowercase();
-            m.contains("deadlock detected")
-            || m.contains("could not serialize access due to concurrent update")
-            || m.contains("terminating connection due to administrator command")
-            || m.contains("connection closed")
-            || m.contains("connection refused")
-            || m.contains("timeout")
-        }
-        _ => false,
-    }
-}
-
-/// Retry simple con backoff exponencial muy pequeño (hasta 3 intentos).
-///
-/// Política:
-/// - Intentos: 3.
-/// - Backoff: 15ms, 30ms, 45ms.
-/// - Logs: se emite `warn!` por intento.
-///
-/// Garantías:
-/// - No altera semántica de negocio; sólo repite la unidad de trabajo provista
-///   por `f`.
-fn with_retry<F, T>(mut f: F) -> Result<T, PersistenceError>
-    where F: FnMut() -> Result<T, PersistenceError>
-{
-    let mut attempts = 0;
-    loop {
-        match f() {
-            Err(e) if is_retryable(&e) && attempts < 3 => {
-                let delay_ms = 15 * ((attempts + 1) as u64);
-                warn!("retryable error (attempt {}): {:?} -> sleeping {}ms",
-                      attempts + 1,
-                      e,
-                      delay_ms);
-                std::thread::sleep(std::time::Duration::from_millis(delay_ms));
-                attempts += 1;
-            }
-            r => return r,
-        }
-    }
-}
-
 // SERIALIZACIÓN: guardamos el enum completo como JSON (payload), y además
 // persistimos `event_type` (minúsculas) para cumplir constraint y facilitar
 // ciertas consultas.
@@ -239,8 +195,22 @@ fn serialize_full_enum(kind: &FlowEventKind) -> Value {
     serde_json::to_value(kind).expect("serialize FlowEventKind")
 }
 
+/// Valores válidos de `event_log.event_type`: `event_type_for` aplicado a
+/// un valor de cada variante de `FlowEventKind`. Deben coincidir con el
+/// CHECK de la migración (ver `schema_check`).
+pub(crate) static EVENT_TYPES: Lazy<Vec<&'static str>> =
+    Lazy::new(|| flow_event_kinds().map(|kind| event_type_for(&kind)).collect());
+
+/// Un valor de cada variante de `FlowEventKind`, en orden de declaración.
+pub(crate) fn flow_event_kinds() -> impl Iterator<Item = FlowEventKind> {
+    std::iter::successors(next_kind(None), |kind| next_kind(Some(kind)))
+}
+
+/// Valores válidos de `step_execution_errors.error_class`.
+pub(crate) const ERROR_CLASSES: &[&str] = &["validation", "runtime", "transient", "permanent"];
+
 /// Mapea la variante del enum a un string en minúsculas, estable en el tiempo.
-fn event_type_for(kind: &FlowEventKind) -> &'static str {
+pub(crate) fn event_type_for(kind: &FlowEventKind) -> &'static str {
     match kind {
         FlowEventKind::FlowInitialized { .. } => "flowinitialized",
         FlowEventKind::StepStarted { .. } => "stepstarted",
@@ -256,6 +226,62 @@ fn event_type_for(kind: &FlowEventKind) -> &'static str {
     }
 }
 
+/// Recorre las variantes de `FlowEventKind`: devuelve un valor de la
+/// variante siguiente a `kind` (la primera con `None`; los campos no
+/// importan). El `match` es exhaustivo, así que una variante nueva no
+/// compila hasta enlazarla aquí y en `event_type_for`.
+fn next_kind(kind: Option<&FlowEventKind>) -> Option<FlowEventKind> {
+    let step_id = String::new;
+    match kind {
+        None => Some(FlowEventKind::FlowInitialized { definition_hash: String::new(),
+                                                      step_count: 0 }),
+        Some(FlowEventKind::FlowInitialized { .. }) => Some(FlowEventKind::StepStarted { step_index: 0,
+                                                                                         step_id: step_id() }),
+        Some(FlowEventKind::StepStarted { .. }) => Some(FlowEventKind::StepFinished { step_index: 0,
+                                                                                      step_id: step_id(),
+                                                                                      outputs: vec![],
+                                                                                      fingerprint: String::new(),
+                                                                                      outputs_payloads: None }),
+        Some(FlowEventKind::StepFinished { .. }) => Some(FlowEventKind::StepFailed { step_index: 0,
+                                                                                     step_id: step_id(),
+                                                                                     error:
+                                                                                         CoreEngineError::MissingInputs,
+                                                                                     fingerprint: String::new() }),
+        Some(FlowEventKind::StepFailed { .. }) => Some(FlowEventKind::StepSignal { step_index: 0,
+                                                                                   step_id: step_id(),
+                                                                                   signal: String::new(),
+                                                                                   data: Value::Null }),
+        Some(FlowEventKind::StepSignal { .. }) => {
+            Some(FlowEventKind::PropertyPreferenceAssigned { property_key: String::new(),
+                                                             policy_id: String::new(),
+                                                             params_hash: String::new(),
+                                                             rationale: Value::Null })
+        }
+        Some(FlowEventKind::PropertyPreferenceAssigned { .. }) => Some(FlowEventKind::RetryScheduled { step_id: step_id(),
+                                                                                                       retry_index: 1,
+                                                                                                       reason: None }),
+        Some(FlowEventKind::RetryScheduled { .. }) => Some(FlowEventKind::BranchCreated { branch_id: Uuid::nil(),
+                                                                                          parent_flow_id: Uuid::nil(),
+                                                                                          root_flow_id: Uuid::nil(),
+                                                                                          created_from_step_id: step_id(),
+                                                                                          divergence_params_hash: None }),
+        Some(FlowEventKind::BranchCreated { .. }) => Some(FlowEventKind::UserInteractionRequested { step_index: 0,
+                                                                                                    step_id: step_id(),
+                                                                                                    schema: None,
+                                                                                                    hint: None }),
+        Some(FlowEventKind::UserInteractionRequested { .. }) => {
+            Some(FlowEventKind::UserInteractionProvided { step_index: 0,
+                                                          step_id: step_id(),
+                                                          provided: Value::Null,
+                                                          decision_hash: None })
+        }
+        Some(FlowEventKind::UserInteractionProvided { .. }) => {
+            Some(FlowEventKind::FlowCompleted { flow_fingerprint: String::new() })
+        }
+        Some(FlowEventKind::FlowCompleted { .. }) => None,
+    }
+}
+
 /// Deserializa una `EventRow` a `FlowEvent`, utilizando el JSON completo del
 /// enum almacenado en `payload`. Si por alguna razón el JSON no es válido,
 /// devuelve `None`.
@@ -276,14 +302,72 @@ fn deserialize_full_enum(row: EventRow) -> Option<FlowEvent> {
 ///   en el mismo commit.
 /// - `list`: devolver todos los eventos de un flow ordenados por `seq` (replay
 ///   determinista).
+///
+/// Las operaciones se ejecutan bajo la política `RetryConfig` (por defecto
+/// `RetryConfig::default()`); los contadores de reintentos se consultan con
+/// `retry_metrics`.
+///
+/// Con `with_tenant` todas las lecturas y escrituras quedan confinadas al
+/// schema del tenant (ver módulo `tenant`).
 pub struct PgEventStore<P: ConnectionProvider> {
     pub provider: P,
+    retry: RetryConfig,
+    metrics: Arc<RetryMetrics>,
+    tenant: Option<TenantId>,
 }
 impl<P: ConnectionProvider> PgEventStore<P> {
     /// Crea un `PgEventStore` a partir de un `ConnectionProvider` (generalmente
     /// `PoolProvider`).
     pub fn new(provider: P) -> Self {
-        Self { provider }
+        Self { provider,
+               retry: RetryConfig::default(),
+               metrics: Arc::new(RetryMetrics::default()),
+               tenant: None }
+    }
+
+    /// Crea el store con la política de reintentos de `cfg`
+    /// (`DATABASE_RETRY_*`).
+    pub fn from_config(provider: P, cfg: &DbConfig) -> Self {
+        Self::new(provider).with_retry_config(cfg.retry.clone())
+    }
+
+    /// Confina el store al schema del tenant indicado. El schema debe haberse
+    /// migrado antes con `migrate_tenant`.
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = Some(tenant);
+        self
+    }
+
+    /// Tenant al que está confinado el store (`None` = schema por defecto).
+    pub fn tenant(&self) -> Option<&TenantId> {
+        self.tenant.as_ref()
+    }
+
+    /// Fija el `search_path` de la transacción en curso al schema del tenant
+    /// (no-op sin tenant). `SET LOCAL` se descarta al cerrar la transacción,
+    /// por lo que la conexión vuelve limpia al pool.
+    fn scope_to_tenant(&self, conn: &mut PgConnection) -> Result<(), diesel::result::Error> {
+        if let Some(tenant) = &self.tenant {
+            diesel::sql_query(tenant.set_local_search_path_sql()).execute(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Reemplaza la política de reintentos (p.ej.
+    /// `DbConfig::from_env().retry`).
+    pub fn with_retry_config(mut self, retry: RetryConfig) -> Self {
+        self.retry = retry.normalized();
+        self
+    }
+
+    /// Política de reintentos vigente.
+    pub fn retry_config(&self) -> &RetryConfig {
+        &self.retry
+    }
+
+    /// Contadores acumulados de reintentos desde la creación del store.
+    pub fn retry_metrics(&self) -> RetryMetricsSnapshot {
+        self.metrics.snapshot()
     }
 }
 
@@ -295,12 +379,12 @@ impl<P: ConnectionProvider> EventStore for PgEventStore<P> {
         // Transacción atómica: inserción de evento y (si aplica) artifacts.
         // - Si falla cualquiera de las inserciones, se revierte todo.
         // - Se usa retry/backoff para errores transitorios.
-        let inserted: (i64, DateTime<Utc>) =
-            with_retry(|| {
-                let mut conn = self.provider.connection()?;
-                conn.build_transaction()
+        let inserted: (i64, DateTime<Utc>) = with_retry(&self.retry, &self.metrics, || {
+                                                 let mut conn = self.provider.connection()?;
+                                                 conn.build_transaction()
                     .read_write()
                     .run(|tx_conn| {
+                        self.scope_to_tenant(tx_conn)?;
                         // Paso 1: insertar el evento
                         let (seq, ts): (i64, DateTime<Utc>) =
                             diesel::insert_into(event_log::table).values(NewEventRow { flow_id: &flow_id,
@@ -400,7 +484,7 @@ impl<P: ConnectionProvider> EventStore for PgEventStore<P> {
                         Ok::<(i64, DateTime<Utc>), diesel::result::Error>((seq, ts))
                     })
                     .map_err(PersistenceError::from)
-            }).expect("insert event (with artifacts)");
+                                             }).expect("insert event (with artifacts)");
 
         let ev = FlowEvent { seq: inserted.0 as u64,
                              flow_id,
@@ -414,11 +498,17 @@ impl<P: ConnectionProvider> EventStore for PgEventStore<P> {
     fn list(&self, flow_id: Uuid) -> Vec<FlowEvent> {
         debug!("list:start flow_id={flow_id}");
         // Lectura robusta con retry ante fallos transitorios.
-        let rows: Vec<EventRow> = with_retry(|| {
+        let rows: Vec<EventRow> = with_retry(&self.retry, &self.metrics, || {
                                       let mut conn = self.provider.connection()?;
-                                      let query = event_log::table.filter(event_log::flow_id.eq(flow_id))
-                                                                  .order(event_log::seq.asc());
-                                      query.load(&mut conn).map_err(PersistenceError::from)
+                                      conn.build_transaction()
+                                          .read_only()
+                                          .run(|tx_conn| {
+                                              self.scope_to_tenant(tx_conn)?;
+                                              event_log::table.filter(event_log::flow_id.eq(flow_id))
+                                                              .order(event_log::seq.asc())
+                                                              .load(tx_conn)
+                                          })
+                                          .map_err(PersistenceError::from)
                                   }).unwrap_or_else(|e| {
                                         error!("list:load error flow_id={flow_id} err={:?}", e);
                                         panic!("diesel load error: {e}");
@@ -433,12 +523,17 @@ impl<P: ConnectionProvider> PgEventStore<P> {
     /// Lista errores de ejecución para un flow_id, ordenados por ts.
     pub fn list_errors(&self, flow_id: Uuid) -> Vec<ErrorRow> {
         debug!("list_errors:start flow_id={flow_id}");
-        let rows: Vec<ErrorRow> = with_retry(|| {
+        let rows: Vec<ErrorRow> = with_retry(&self.retry, &self.metrics, || {
                                       let mut conn = self.provider.connection()?;
-                                      let query =
-                                          step_execution_errors::table.filter(step_execution_errors::flow_id.eq(flow_id))
-                                                                      .order(step_execution_errors::ts.asc());
-                                      query.load(&mut conn).map_err(PersistenceError::from)
+                                      conn.build_transaction()
+                                          .read_only()
+                                          .run(|tx_conn| {
+                                              self.scope_to_tenant(tx_conn)?;
+                                              step_execution_errors::table.filter(step_execution_errors::flow_id.eq(flow_id))
+                                                                          .order(step_execution_errors::ts.asc())
+                                                                          .load(tx_conn)
+                                          })
+                                          .map_err(PersistenceError::from)
                                   }).unwrap_or_else(|e| {
                                         error!("list_errors:load error flow_id={flow_id} err={:?}", e);
                                         vec![]
@@ -493,29 +588,50 @@ impl FlowRepository for PgFlowRepository {
 
 /// Construye un pool Postgres r2d2 a partir de URL.
 ///
-/// Comportamiento:
-/// - Valida y ajusta tamaños (si `min_size > max_size`, usa `min_size =
-///   max_size`).
-/// - Ejecuta migraciones inmediatamente tras el primer `get()`.
-/// - Devuelve `PersistenceError::TransientIo` ante errores del pool/manager.
+/// Atajo sobre `build_pool_from_config` con `DbConfig::new(url)` y los
+/// tamaños indicados (`0` se interpreta como `1`; si `min_size > max_size`,
+/// usa `min_size = max_size`).
 pub fn build_pool(database_url: &str, min_size: u32, max_size: u32) -> Result<PgPool, PersistenceError> {
     let validated_min = if min_size == 0 { 1 } else { min_size };
     let validated_max = if max_size == 0 { 1 } else { max_size };
     if validated_min > validated_max {
-        eprintln!("WARN: min_size > max_size ({} > {}), ajustando min=max",
-                  validated_min, validated_max);
+        warn!("min_size > max_size ({} > {}), ajustando min=max",
+              validated_min, validated_max);
     }
-    let final_min = validated_min.min(validated_max);
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool = r2d2::Pool::builder().min_idle(Some(final_min))
-                                    .max_size(validated_max)
+    let mut cfg = DbConfig::new(database_url);
+    cfg.min_connections = validated_min.min(validated_max);
+    cfg.max_connections = validated_max;
+    build_pool_from_config(&cfg)
+}
+
+/// Construye un pool Postgres r2d2 a partir de un `DbConfig` completo.
+///
+/// Comportamiento:
+/// - Valida la configuración (`DbConfig::validate`).
+/// - Aplica `connect_timeout` (libpq y checkout del pool) e `idle_timeout`.
+/// - Los parámetros de sesión (statement_timeout, search_path,
+///   application_name, sslmode, gssencmode) viajan en
+///   `DbConfig::connection_url`.
+/// - Ejecuta migraciones inmediatamente tras el primer `get()`.
+/// - Devuelve `PersistenceError::TransientIo` ante errores del pool/manager.
+pub fn build_pool_from_config(cfg: &DbConfig) -> Result<PgPool, PersistenceError> {
+    cfg.validate()?;
+    debug!("build_pool: {:?}", cfg);
+    let manager = ConnectionManager::<PgConnection>::new(cfg.connection_url());
+    let pool = r2d2::Pool::builder().min_idle(Some(cfg.min_connections))
+                                    .max_size(cfg.max_connections)
+                                    .connection_timeout(cfg.connect_timeout)
+                                    .idle_timeout(cfg.idle_timeout)
                                     .build(manager)
-                                    .map_err(|e| PersistenceError::TransientIo(format!("pool build: {e}")))?;
+                                    .map_err(|e| PersistenceError::TransientIo(format!("pool build [{}]: {e}", cfg.name)))?;
     // Ejecutar migraciones una sola vez al construir (primer connection checkout).
     {
         let mut conn = pool.get()
                            .map_err(|e| PersistenceError::TransientIo(format!("pool get for migrations: {e}")))?;
         run_pending_migrations(&mut conn)?;
+        if cfg.verify_schema {
+            verify_schema(&mut conn)?;
+        }
     }
     Ok(pool)
 }
@@ -525,10 +641,74 @@ pub fn build_pool_with_migrations(database_url: &str, min: u32, max: u32) -> Res
     build_pool(database_url, min, max)
 }
 
-/// Helper de desarrollo: carga `.env`, lee configuración (DATABASE_URL,
-/// tamaños) y construye un pool ya migrado.
+/// Helper de desarrollo: carga `.env`, lee configuración (`DbConfig`) y
+/// construye un pool ya migrado.
 pub fn build_dev_pool_from_env() -> Result<PgPool, PersistenceError> {
+    build_dev_pool_and_config_from_env().map(|(pool, _)| pool)
+}
+
+/// Igual que `build_dev_pool_from_env`, devolviendo también la
+/// configuración leída para aplicar el resto de ajustes del operador (p.ej.
+/// `PgEventStore::from_config`).
+pub fn build_dev_pool_and_config_from_env() -> Result<(PgPool, DbConfig), PersistenceError> {
     crate::config::init_dotenv();
-    let cfg = crate::config::DbConfig::from_env();
-    build_pool(&cfg.url, cfg.min_connections, cfg.max_connections)
+    let cfg = DbConfig::from_env()?;
+    Ok((build_pool_from_config(&cfg)?, cfg))
+}
+
+/// Construye un pool migrado por cada base declarada (ver
+/// `config::load_named_configs`), indexado por nombre lógico.
+pub fn build_named_pools_from_env() -> Result<BTreeMap<String, PgPool>, PersistenceError> {
+    load_named_configs()?.iter()
+                         .map(|(name, cfg)| build_pool_from_config(cfg).map(|p| (name.clone(), p)))
+                         .collect()
+}
+
+/// Crea y migra el schema de un tenant usando una conexión del pool.
+/// Idempotente: re-ejecutarlo solo aplica migraciones pendientes.
+pub fn migrate_tenant(pool: &PgPool, tenant: &TenantId) -> Result<(), PersistenceError> {
+    let mut conn = pool.get()
+                       .map_err(|e| PersistenceError::TransientIo(format!("pool get for tenant migrations: {e}")))?;
+    run_tenant_migrations(&mut conn, tenant)?;
+    debug!("migrate_tenant:done tenant={tenant} schema={}", tenant.schema_name());
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct SchemaNameRow {
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    schema_name: String,
+}
+
+/// Lista los tenants existentes (schemas `tenant_*`), ordenados por id.
+pub fn list_tenants(pool: &PgPool) -> Result<Vec<TenantId>, PersistenceError> {
+    let mut conn = pool.get()
+                       .map_err(|e| PersistenceError::TransientIo(format!("pool get for list_tenants: {e}")))?;
+    let rows: Vec<SchemaNameRow> =
+        diesel::sql_query("SELECT schema_name::text AS schema_name FROM information_schema.schemata \
+                           WHERE left(schema_name, length($1)) = $1 ORDER BY schema_name")
+            .bind::<diesel::sql_types::Text, _>(TENANT_SCHEMA_PREFIX)
+            .load(&mut conn)?;
+    Ok(rows.iter()
+           .filter_map(|r| TenantId::from_schema_name(&r.schema_name))
+           .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_from_config_applies_retry_policy() {
+        let mut cfg = DbConfig::new("postgres://localhost/unused");
+        cfg.retry = RetryConfig { max_attempts: 7,
+                                  base_delay_ms: 10,
+                                  max_delay_ms: 5,
+                                  jitter: 0.0 };
+        // Pool sin conexiones: `build_unchecked` no contacta con el servidor.
+        let pool = r2d2::Pool::builder().build_unchecked(ConnectionManager::<PgConnection>::new(cfg.connection_url()));
+        let store = PgEventStore::from_config(PoolProvider { pool }, &cfg);
+        assert_eq!(store.retry_config(), &cfg.retry.clone().normalized());
+        assert_eq!(store.retry_config().max_attempts, 7);
+    }
 }
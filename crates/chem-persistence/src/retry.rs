@@ -0,0 +1,291 @@
+//! Política de reintentos (retry/backoff) para operaciones sobre Postgres.
+//!
+//! Objetivo:
+//! - Reemplazar la política fija (3 reintentos, 15/30/45 ms) por una
+//!   configurable por despliegue (`RetryConfig`), cargable desde entorno vía
+//!   `DbConfig::from_env`.
+//! - Clasificar la reintentabilidad a partir de códigos SQLSTATE (ver
+//!   `PersistenceError::sqlstate`) en lugar de comparar mensajes.
+//! - Exponer métricas acumuladas (`RetryMetrics`) para que operadores puedan
+//!   ajustar la política observando reintentos reales.
+//!
+//! Variables soportadas (todas opcionales; para bases nombradas se usa el
+//! prefijo `<NOMBRE>_DATABASE_RETRY_*`, ver `crate::config`):
+//! - `DATABASE_RETRY_MAX_ATTEMPTS` (por defecto: 4 = 1 intento + 3 reintentos)
+//! - `DATABASE_RETRY_BASE_DELAY_MS` (por defecto: 15)
+//! - `DATABASE_RETRY_MAX_DELAY_MS` (por defecto: 1000)
+//! - `DATABASE_RETRY_JITTER` (fracción 0.0..=1.0, por defecto: 0.0)
+
+use std::collections::hash_map::RandomState;
+use std::env;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::config::parse_var;
+use crate::error::PersistenceError;
+
+/// Configuración de la política de reintentos con backoff exponencial.
+///
+/// El retardo antes del reintento `n` (1-based) es
+/// `min(base_delay_ms * 2^(n-1), max_delay_ms)`, perturbado por `jitter`
+/// (fracción simétrica: con `jitter = 0.2` el retardo varía ±20%).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Número total de ejecuciones permitidas (incluye el intento inicial).
+    /// Un valor de 1 desactiva los reintentos.
+    pub max_attempts: u32,
+    /// Retardo base (ms) del primer reintento.
+    pub base_delay_ms: u64,
+    /// Tope superior (ms) del retardo entre intentos.
+    pub max_delay_ms: u64,
+    /// Fracción de jitter aleatorio aplicada al retardo (0.0 = determinista).
+    pub jitter: f64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self { max_attempts: 4,
+               base_delay_ms: 15,
+               max_delay_ms: 1_000,
+               jitter: 0.0 }
+    }
+}
+
+impl RetryConfig {
+    /// Lee la configuración desde variables de entorno (`DATABASE_RETRY_*`),
+    /// usando los valores por defecto para las ausentes.
+    pub fn from_env() -> Result<Self, PersistenceError> {
+        Self::from_lookup("DATABASE", |k| env::var(k).ok())
+    }
+
+    /// Lee `<prefix>_RETRY_*` mediante `lookup`. Valores no parseables se
+    /// reportan como `PersistenceError::Config`.
+    pub fn from_lookup<F>(prefix: &str, lookup: F) -> Result<Self, PersistenceError>
+        where F: Fn(&str) -> Option<String>
+    {
+        let d = Self::default();
+        let key = |suffix: &str| format!("{prefix}_RETRY_{suffix}");
+        Ok(Self { max_attempts: parse_var(&lookup, &key("MAX_ATTEMPTS"))?.unwrap_or(d.max_attempts),
+                  base_delay_ms: parse_var(&lookup, &key("BASE_DELAY_MS"))?.unwrap_or(d.base_delay_ms),
+                  max_delay_ms: parse_var(&lookup, &key("MAX_DELAY_MS"))?.unwrap_or(d.max_delay_ms),
+                  jitter: parse_var(&lookup, &key("JITTER"))?.unwrap_or(d.jitter) }.normalized())
+    }
+
+    /// Ajusta valores fuera de rango: al menos un intento, `max_delay_ms >=
+    /// base_delay_ms` y `jitter` acotado a `[0, 1]`.
+    pub fn normalized(mut self) -> Self {
+        self.max_attempts = self.max_attempts.max(1);
+        self.max_delay_ms = self.max_delay_ms.max(self.base_delay_ms);
+        self.jitter = if self.jitter.is_finite() {
+            self.jitter.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self
+    }
+
+    /// Retardo (sin jitter) previo al reintento `retry` (1-based).
+    pub fn backoff_ms(&self, retry: u32) -> u64 {
+        let exp = retry.saturating_sub(1).min(32);
+        self.base_delay_ms.saturating_mul(1u64 << exp).min(self.max_delay_ms)
+    }
+
+    /// Retardo efectivo previo al reintento `retry`, aplicando jitter.
+    fn delay_for(&self, retry: u32) -> Duration {
+        let base = self.backoff_ms(retry);
+        if self.jitter <= 0.0 || base == 0 {
+            return Duration::from_millis(base);
+        }
+        // Valor pseudoaleatorio en [-1, 1] sin dependencias externas.
+        let r = RandomState::new().build_hasher().finish();
+        let unit = (r as f64 / u64::MAX as f64) * 2.0 - 1.0;
+        let jittered = base as f64 * (1.0 + self.jitter * unit);
+        Duration::from_millis(jittered.max(0.0).round() as u64)
+    }
+}
+
+/// Determina si un SQLSTATE corresponde a una condición transitoria.
+///
+/// Se consideran reintentables:
+/// - `40001` serialization_failure y `40P01` deadlock_detected.
+/// - Clase `08` (connection exception).
+/// - `53300` too_many_connections y `55P03` lock_not_available.
+/// - `57P01` admin_shutdown, `57P02` crash_shutdown, `57P03`
+///   cannot_connect_now.
+pub fn is_retryable_sqlstate(code: &str) -> bool {
+    matches!(code, "40001" | "40P01" | "53300" | "55P03" | "57P01" | "57P02" | "57P03") || code.starts_with("08")
+}
+
+/// Determina si un error de persistencia es transitorio (recomendado
+/// reintentar con backoff).
+///
+/// - Errores con SQLSTATE se clasifican con `is_retryable_sqlstate`.
+/// - Errores de pool/conexión sin SQLSTATE (`TransientIo`) son reintentables.
+pub fn is_retryable(e: &PersistenceError) -> bool {
+    match e {
+        PersistenceError::TransientIo(_) => true,
+        other => other.sqlstate().is_some_and(is_retryable_sqlstate),
+    }
+}
+
+/// Contadores acumulados de la política de reintentos.
+///
+/// Se comparten (vía `Arc`) entre las operaciones de un `PgEventStore`; usar
+/// `snapshot` para leerlos.
+#[derive(Debug, Default)]
+pub struct RetryMetrics {
+    operations: AtomicU64,
+    retries: AtomicU64,
+    recovered: AtomicU64,
+    exhausted: AtomicU64,
+    non_retryable: AtomicU64,
+    backoff_ms: AtomicU64,
+}
+
+/// Vista inmutable de `RetryMetrics` en un instante dado.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetryMetricsSnapshot {
+    /// Operaciones ejecutadas a través de la política.
+    pub operations: u64,
+    /// Reintentos realizados (no incluye el intento inicial).
+    pub retries: u64,
+    /// Operaciones que tuvieron éxito tras al menos un reintento.
+    pub recovered: u64,
+    /// Operaciones que agotaron `max_attempts` con error reintentable.
+    pub exhausted: u64,
+    /// Operaciones que fallaron con un error no reintentable.
+    pub non_retryable: u64,
+    /// Tiempo total (ms) dormido en backoff.
+    pub backoff_ms: u64,
+}
+
+impl RetryMetrics {
+    pub fn snapshot(&self) -> RetryMetricsSnapshot {
+        RetryMetricsSnapshot { operations: self.operations.load(Ordering::Relaxed),
+                               retries: self.retries.load(Ordering::Relaxed),
+                               recovered: self.recovered.load(Ordering::Relaxed),
+                               exhausted: self.exhausted.load(Ordering::Relaxed),
+                               non_retryable: self.non_retryable.load(Ordering::Relaxed),
+                               backoff_ms: self.backoff_ms.load(Ordering::Relaxed) }
+    }
+}
+
+/// Ejecuta `f` aplicando la política `cfg` y registrando en `metrics`.
+///
+/// Garantías:
+/// - No altera semántica de negocio; sólo repite la unidad de trabajo provista
+///   por `f` ante errores reintentables.
+/// - Logs: se emite `warn!` por reintento (incluye SQLSTATE si se conoce).
+pub fn with_retry<F, T>(cfg: &RetryConfig, metrics: &RetryMetrics, mut f: F) -> Result<T, PersistenceError>
+    where F: FnMut() -> Result<T, PersistenceError>
+{
+    metrics.operations.fetch_add(1, Ordering::Relaxed);
+    let mut attempt: u32 = 1;
+    loop {
+        match f() {
+            Ok(v) => {
+                if attempt > 1 {
+                    metrics.recovered.fetch_add(1, Ordering::Relaxed);
+                }
+                return Ok(v);
+            }
+            Err(e) if is_retryable(&e) => {
+                if attempt >= cfg.max_attempts {
+                    metrics.exhausted.fetch_add(1, Ordering::Relaxed);
+                    return Err(e);
+                }
+                let delay = cfg.delay_for(attempt);
+                warn!("retryable error (attempt {}/{}, sqlstate={:?}): {:?} -> sleeping {}ms",
+                      attempt,
+                      cfg.max_attempts,
+                      e.sqlstate(),
+                      e,
+                      delay.as_millis());
+                metrics.retries.fetch_add(1, Ordering::Relaxed);
+                metrics.backoff_ms.fetch_add(delay.as_millis() as u64, Ordering::Relaxed);
+                std::thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(e) => {
+                metrics.non_retryable.fetch_add(1, Ordering::Relaxed);
+                return Err(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db_error(code: &str) -> PersistenceError {
+        PersistenceError::Database { sqlstate: Some(code.to_string()),
+                                     message: "test".into() }
+    }
+
+    #[test]
+    fn sqlstate_classification() {
+        assert!(is_retryable(&PersistenceError::SerializationConflict));
+        assert!(is_retryable(&db_error("40P01")));
+        assert!(is_retryable(&db_error("08006")));
+        assert!(is_retryable(&PersistenceError::TransientIo("pool".into())));
+        assert!(!is_retryable(&PersistenceError::UniqueViolation("dup".into())));
+        assert!(!is_retryable(&db_error("42P01")));
+        assert!(!is_retryable(&PersistenceError::Unknown("timeout".into())));
+    }
+
+    #[test]
+    fn backoff_is_exponential_and_capped() {
+        let cfg = RetryConfig { max_attempts: 10,
+                                base_delay_ms: 10,
+                                max_delay_ms: 50,
+                                jitter: 0.0 };
+        let delays: Vec<u64> = (1..=5).map(|n| cfg.backoff_ms(n)).collect();
+        assert_eq!(delays, vec![10, 20, 40, 50, 50]);
+    }
+
+    #[test]
+    fn retries_until_exhausted_and_records_metrics() {
+        let cfg = RetryConfig { max_attempts: 3,
+                                base_delay_ms: 0,
+                                max_delay_ms: 0,
+                                jitter: 0.0 };
+        let metrics = RetryMetrics::default();
+        let mut calls = 0;
+        let res: Result<(), _> = with_retry(&cfg, &metrics, || {
+            calls += 1;
+            Err(db_error("40P01"))
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 3);
+        let snap = metrics.snapshot();
+        assert_eq!((snap.operations, snap.retries, snap.exhausted), (1, 2, 1));
+
+        let mut calls = 0;
+        let res = with_retry(&cfg, &metrics, || {
+            calls += 1;
+            if calls < 2 {
+                Err(PersistenceError::SerializationConflict)
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(res.unwrap(), 2);
+        assert_eq!(metrics.snapshot().recovered, 1);
+    }
+
+    #[test]
+    fn normalized_clamps_out_of_range_values() {
+        let cfg = RetryConfig { max_attempts: 0,
+                                base_delay_ms: 100,
+                                max_delay_ms: 10,
+                                jitter: 3.0 }.normalized();
+        assert_eq!(cfg.max_attempts, 1);
+        assert_eq!(cfg.max_delay_ms, 100);
+        assert_eq!(cfg.jitter, 1.0);
+    }
+}
@@ -0,0 +1,499 @@
+//! Verificación de drift entre la base viva y el esquema esperado.
+//!
+//! `schema.rs` se mantiene a mano y las migraciones `0001_init` y
+//! `0001_event_log` comparten versión Diesel (`0001`): Diesel indexa las
+//! migraciones pendientes por versión, por lo que solo una de las dos llega a
+//! aplicarse (`0002_schema_reconcile` lleva cualquiera de ambos estados al
+//! canónico). Esta rutina detecta cualquier divergencia restante comparando
+//! `information_schema` con:
+//! - las declaraciones `diesel::table!` (nombre, tipo SQL y nulabilidad de cada
+//!   columna, obtenidos de los propios tipos Diesel);
+//! - las restricciones de las que depende el código: PKs, el CHECK de
+//!   `event_log.event_type` (debe listar exactamente las variantes de
+//!   `FlowEventKind`), el CHECK de `step_execution_errors.error_class` y la FK
+//!   `produced_in_seq → event_log(seq) ON DELETE RESTRICT`.
+//!
+//! Se ejecuta al construir el pool si `DbConfig::verify_schema` está activo,
+//! o bajo demanda (`chem-cli verify-schema`). Las consultas usan
+//! `current_schema()`, por lo que respetan el `search_path` (tenants).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use diesel::expression::Expression;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::sql_types::{self, Text};
+use diesel::Column;
+use serde::{Deserialize, Serialize};
+
+use crate::error::PersistenceError;
+use crate::pg::{ERROR_CLASSES, EVENT_TYPES};
+use crate::schema::{event_log, step_execution_errors, workflow_branches, workflow_step_artifacts};
+use crate::tenant::TenantId;
+
+/// Divergencia concreta entre la base y el esquema esperado.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SchemaDrift {
+    MissingTable {
+        table: String,
+    },
+    MissingColumn {
+        table: String,
+        column: String,
+    },
+    /// Columna presente en la base pero no declarada en `schema.rs`.
+    UndeclaredColumn {
+        table: String,
+        column: String,
+    },
+    TypeMismatch {
+        table: String,
+        column: String,
+        expected: String,
+        found: String,
+    },
+    NullabilityMismatch {
+        table: String,
+        column: String,
+        expected_nullable: bool,
+    },
+    PrimaryKeyMismatch {
+        table: String,
+        expected: Vec<String>,
+        found: Vec<String>,
+    },
+    /// Ningún CHECK con lista de valores sobre la columna.
+    MissingCheck {
+        table: String,
+        column: String,
+    },
+    /// La lista de valores permitidos por el CHECK difiere de la esperada.
+    CheckValuesMismatch {
+        table: String,
+        column: String,
+        missing: Vec<String>,
+        unexpected: Vec<String>,
+    },
+    MissingForeignKey {
+        table: String,
+        column: String,
+        references: String,
+        on_delete: String,
+    },
+}
+
+impl fmt::Display for SchemaDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingTable { table } => write!(f, "missing table {table}"),
+            Self::MissingColumn { table, column } => write!(f, "missing column {table}.{column}"),
+            Self::UndeclaredColumn { table, column } => write!(f, "undeclared column {table}.{column}"),
+            Self::TypeMismatch { table,
+                                 column,
+                                 expected,
+                                 found, } => {
+                write!(f, "{table}.{column}: expected type {expected}, found {found}")
+            }
+            Self::NullabilityMismatch { table,
+                                        column,
+                                        expected_nullable, } => {
+                write!(f,
+                       "{table}.{column}: expected {}",
+                       if *expected_nullable { "NULL" } else { "NOT NULL" })
+            }
+            Self::PrimaryKeyMismatch { table, expected, found } => {
+                write!(f, "{table}: expected primary key {expected:?}, found {found:?}")
+            }
+            Self::MissingCheck { table, column } => write!(f, "{table}.{column}: missing CHECK value list"),
+            Self::CheckValuesMismatch { table,
+                                        column,
+                                        missing,
+                                        unexpected, } => {
+                write!(f, "{table}.{column}: CHECK missing {missing:?}, unexpected {unexpected:?}")
+            }
+            Self::MissingForeignKey { table,
+                                      column,
+                                      references,
+                                      on_delete, } => {
+                write!(f, "{table}.{column}: missing FK to {references} ON DELETE {on_delete}")
+            }
+        }
+    }
+}
+
+/// Nombre de tipo (`information_schema.columns.data_type`) de un tipo SQL
+/// Diesel.
+trait PgDataType {
+    const DATA_TYPE: &'static str;
+    const NULLABLE: bool = false;
+}
+
+macro_rules! pg_data_type {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(impl PgDataType for $ty { const DATA_TYPE: &'static str = $name; })*
+    };
+}
+
+pg_data_type! {
+    sql_types::BigInt => "bigint",
+    sql_types::Integer => "integer",
+    sql_types::Text => "text",
+    sql_types::Uuid => "uuid",
+    sql_types::Jsonb => "jsonb",
+    sql_types::Timestamptz => "timestamp with time zone",
+}
+
+impl<T: PgDataType + sql_types::SqlType> PgDataType for sql_types::Nullable<T> {
+    const DATA_TYPE: &'static str = T::DATA_TYPE;
+    const NULLABLE: bool = true;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ColumnSpec {
+    name: &'static str,
+    data_type: &'static str,
+    nullable: bool,
+}
+
+/// Especificación de una columna derivada de su declaración Diesel.
+fn col<C>() -> ColumnSpec
+    where C: Column + Expression,
+          <C as Expression>::SqlType: PgDataType
+{
+    ColumnSpec { name: C::NAME,
+                 data_type: <C as Expression>::SqlType::DATA_TYPE,
+                 nullable: <C as Expression>::SqlType::NULLABLE }
+}
+
+struct TableSpec {
+    name: &'static str,
+    primary_key: &'static [&'static str],
+    columns: Vec<ColumnSpec>,
+}
+
+/// Tablas esperadas, construidas a partir de `crate::schema`.
+fn expected_tables() -> Vec<TableSpec> {
+    vec![TableSpec { name: "event_log",
+                     primary_key: &["seq"],
+                     columns: vec![col::<event_log::seq>(),
+                                   col::<event_log::flow_id>(),
+                                   col::<event_log::ts>(),
+                                   col::<event_log::event_type>(),
+                                   col::<event_log::payload>()] },
+         TableSpec { name: "workflow_step_artifacts",
+                     primary_key: &["artifact_hash"],
+                     columns: vec![col::<workflow_step_artifacts::artifact_hash>(),
+                                   col::<workflow_step_artifacts::kind>(),
+                                   col::<workflow_step_artifacts::payload>(),
+                                   col::<workflow_step_artifacts::metadata>(),
+                                   col::<workflow_step_artifacts::produced_in_seq>()] },
+         TableSpec { name: "step_execution_errors",
+                     primary_key: &["id"],
+                     columns: vec![col::<step_execution_errors::id>(),
+                                   col::<step_execution_errors::flow_id>(),
+                                   col::<step_execution_errors::step_id>(),
+                                   col::<step_execution_errors::attempt_number>(),
+                                   col::<step_execution_errors::error_class>(),
+                                   col::<step_execution_errors::details>(),
+                                   col::<step_execution_errors::ts>()] },
+         TableSpec { name: "workflow_branches",
+                     primary_key: &["branch_id"],
+                     columns: vec![col::<workflow_branches::branch_id>(),
+                                   col::<workflow_branches::root_flow_id>(),
+                                   col::<workflow_branches::parent_flow_id>(),
+                                   col::<workflow_branches::created_from_step_id>(),
+                                   col::<workflow_branches::divergence_params_hash>(),
+                                   col::<workflow_branches::created_at>(),
+                                   col::<workflow_branches::name>(),
+                                   col::<workflow_branches::metadata>()] },]
+}
+
+/// CHECKs con lista de valores esperados: (tabla, columna, valores).
+fn expected_checks() -> Vec<(&'static str, &'static str, &'static [&'static str])> {
+    vec![("event_log", "event_type", EVENT_TYPES.as_slice()),
+         ("step_execution_errors", "error_class", ERROR_CLASSES)]
+}
+
+#[derive(QueryableByName)]
+struct ColumnRow {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    data_type: String,
+    #[diesel(sql_type = Text)]
+    is_nullable: String,
+}
+
+#[derive(QueryableByName)]
+struct KeyRow {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+}
+
+#[derive(QueryableByName)]
+struct CheckRow {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    check_clause: String,
+}
+
+#[derive(QueryableByName)]
+struct ForeignKeyRow {
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    ref_table: String,
+    #[diesel(sql_type = Text)]
+    ref_column: String,
+    #[diesel(sql_type = Text)]
+    delete_rule: String,
+}
+
+/// Devuelve todas las divergencias del schema actual (`current_schema()`).
+/// Lista vacía = sin drift.
+pub fn check_schema(conn: &mut PgConnection) -> Result<Vec<SchemaDrift>, PersistenceError> {
+    let tables = expected_tables();
+    let names: Vec<&str> = tables.iter().map(|t| t.name).collect();
+
+    let columns: Vec<ColumnRow> =
+        diesel::sql_query("SELECT table_name::text, column_name::text, data_type::text, is_nullable::text \
+                           FROM information_schema.columns WHERE table_schema = current_schema() \
+                           AND table_name = ANY($1) ORDER BY table_name, ordinal_position")
+            .bind::<sql_types::Array<Text>, _>(&names)
+            .load(conn)?;
+    let keys: Vec<KeyRow> = diesel::sql_query(
+                                              "SELECT tc.table_name::text, k.column_name::text \
+                           FROM information_schema.table_constraints tc \
+                           JOIN information_schema.key_column_usage k \
+                             ON k.constraint_schema = tc.constraint_schema AND k.constraint_name = tc.constraint_name \
+                           WHERE tc.constraint_type = 'PRIMARY KEY' AND tc.table_schema = current_schema() \
+                           AND tc.table_name = ANY($1) ORDER BY tc.table_name, k.ordinal_position",
+    ).bind::<sql_types::Array<Text>, _>(&names)
+                            .load(conn)?;
+    let checks: Vec<CheckRow> = diesel::sql_query(
+                                                  "SELECT u.table_name::text, u.column_name::text, cc.check_clause::text \
+                           FROM information_schema.check_constraints cc \
+                           JOIN information_schema.constraint_column_usage u \
+                             ON u.constraint_schema = cc.constraint_schema AND u.constraint_name = cc.constraint_name \
+                           WHERE u.table_schema = current_schema() AND u.table_name = ANY($1)",
+    ).bind::<sql_types::Array<Text>, _>(&names)
+                                .load(conn)?;
+    let fks: Vec<ForeignKeyRow> =
+        diesel::sql_query("SELECT k.column_name::text, u.table_name::text AS ref_table, u.column_name::text AS ref_column, \
+                           rc.delete_rule::text \
+                           FROM information_schema.referential_constraints rc \
+                           JOIN information_schema.key_column_usage k \
+                             ON k.constraint_schema = rc.constraint_schema AND k.constraint_name = rc.constraint_name \
+                           JOIN information_schema.constraint_column_usage u \
+                             ON u.constraint_schema = rc.unique_constraint_schema \
+                            AND u.constraint_name = rc.unique_constraint_name \
+                           WHERE k.table_schema = current_schema() AND k.table_name = 'workflow_step_artifacts'")
+            .load(conn)?;
+
+    let mut live: BTreeMap<String, Vec<ColumnRow>> = BTreeMap::new();
+    for c in columns {
+        live.entry(c.table_name.clone()).or_default().push(c);
+    }
+    let mut pks: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for k in keys {
+        pks.entry(k.table_name).or_default().push(k.column_name);
+    }
+    let mut check_values: BTreeMap<(String, String), Option<BTreeSet<String>>> = BTreeMap::new();
+    for c in checks {
+        let literals = quoted_literals(&c.check_clause);
+        let entry = check_values.entry((c.table_name, c.column_name)).or_insert(None);
+        if !literals.is_empty() {
+            entry.get_or_insert_with(BTreeSet::new).extend(literals);
+        }
+    }
+
+    let mut drifts = Vec::new();
+    for table in &tables {
+        let Some(found) = live.get(table.name) else {
+            drifts.push(SchemaDrift::MissingTable { table: table.name.to_string() });
+            continue;
+        };
+        drifts.extend(compare_columns(table, found));
+        let found_pk = pks.get(table.name).cloned().unwrap_or_default();
+        if found_pk != table.primary_key {
+            drifts.push(SchemaDrift::PrimaryKeyMismatch { table: table.name.to_string(),
+                                                          expected: table.primary_key
+                                                                         .iter()
+                                                                         .map(|c| c.to_string())
+                                                                         .collect(),
+                                                          found: found_pk });
+        }
+    }
+    for (table, column, expected) in expected_checks() {
+        if !live.contains_key(table) {
+            continue;
+        }
+        let found = check_values.get(&(table.to_string(), column.to_string())).cloned().flatten();
+        drifts.extend(compare_check(table, column, expected, found));
+    }
+    if live.contains_key("workflow_step_artifacts")
+       && !fks.iter().any(|fk| {
+                         fk.column_name == "produced_in_seq"
+                         && fk.ref_table == "event_log"
+                         && fk.ref_column == "seq"
+                         && fk.delete_rule == "RESTRICT"
+                     })
+    {
+        drifts.push(SchemaDrift::MissingForeignKey { table: "workflow_step_artifacts".into(),
+                                                     column: "produced_in_seq".into(),
+                                                     references: "event_log(seq)".into(),
+                                                     on_delete: "RESTRICT".into() });
+    }
+    Ok(drifts)
+}
+
+/// Como `check_schema`, pero devuelve `PersistenceError::SchemaDrift` si hay
+/// divergencias.
+pub fn verify_schema(conn: &mut PgConnection) -> Result<(), PersistenceError> {
+    let drifts = check_schema(conn)?;
+    if drifts.is_empty() {
+        Ok(())
+    } else {
+        Err(PersistenceError::SchemaDrift(drifts))
+    }
+}
+
+/// `verify_schema` sobre el schema de un tenant.
+pub fn verify_tenant_schema(conn: &mut PgConnection, tenant: &TenantId) -> Result<(), PersistenceError> {
+    conn.build_transaction().read_only().run(|tx| {
+                                            diesel::sql_query(tenant.set_local_search_path_sql()).execute(tx)?;
+                                            verify_schema(tx)
+                                        })
+}
+
+fn compare_columns(table: &TableSpec, found: &[ColumnRow]) -> Vec<SchemaDrift> {
+    let mut drifts = Vec::new();
+    for spec in &table.columns {
+        match found.iter().find(|c| c.column_name == spec.name) {
+            None => drifts.push(SchemaDrift::MissingColumn { table: table.name.to_string(),
+                                                             column: spec.name.to_string() }),
+            Some(c) => {
+                if c.data_type != spec.data_type {
+                    drifts.push(SchemaDrift::TypeMismatch { table: table.name.to_string(),
+                                                            column: spec.name.to_string(),
+                                                            expected: spec.data_type.to_string(),
+                                                            found: c.data_type.clone() });
+                }
+                if (c.is_nullable == "YES") != spec.nullable {
+                    drifts.push(SchemaDrift::NullabilityMismatch { table: table.name.to_string(),
+                                                                   column: spec.name.to_string(),
+                                                                   expected_nullable: spec.nullable });
+                }
+            }
+        }
+    }
+    for c in found {
+        if !table.columns.iter().any(|s| s.name == c.column_name) {
+            drifts.push(SchemaDrift::UndeclaredColumn { table: table.name.to_string(),
+                                                        column: c.column_name.clone() });
+        }
+    }
+    drifts
+}
+
+fn compare_check(table: &str, column: &str, expected: &[&str], found: Option<BTreeSet<String>>) -> Option<SchemaDrift> {
+    let Some(found) = found else {
+        return Some(SchemaDrift::MissingCheck { table: table.to_string(),
+                                                column: column.to_string() });
+    };
+    let expected: BTreeSet<String> = expected.iter().map(|s| s.to_string()).collect();
+    let missing: Vec<String> = expected.difference(&found).cloned().collect();
+    let unexpected: Vec<String> = found.difference(&expected).cloned().collect();
+    if missing.is_empty() && unexpected.is_empty() {
+        None
+    } else {
+        Some(SchemaDrift::CheckValuesMismatch { table: table.to_string(),
+                                                column: column.to_string(),
+                                                missing,
+                                                unexpected })
+    }
+}
+
+/// Literales `'...'` de una cláusula CHECK tal como la reporta Postgres
+/// (`(event_type = ANY (ARRAY['a'::text, 'b'::text]))`).
+fn quoted_literals(clause: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut chars = clause.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '\'' {
+            continue;
+        }
+        let mut lit = String::new();
+        while let Some(c) = chars.next() {
+            if c == '\'' {
+                if chars.peek() == Some(&'\'') {
+                    lit.push('\'');
+                    chars.next();
+                    continue;
+                }
+                break;
+            }
+            lit.push(c);
+        }
+        out.push(lit);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pg::{event_type_for, flow_event_kinds};
+
+    #[test]
+    fn event_types_cover_every_flow_event_kind() {
+        // Cada variante aparece una vez y su event_type es el nombre de la
+        // variante (la etiqueta de serde del payload) en minúsculas.
+        let mut seen = BTreeSet::new();
+        for kind in flow_event_kinds() {
+            let payload = serde_json::to_value(&kind).unwrap();
+            let tag = payload.as_object().and_then(|o| o.keys().next()).unwrap().to_lowercase();
+            assert!(seen.insert(tag.clone()), "variante repetida: {tag}");
+            assert_eq!(event_type_for(&kind), tag);
+        }
+        let expected: BTreeSet<&str> = EVENT_TYPES.iter().copied().collect();
+        assert_eq!(seen.iter().map(String::as_str).collect::<BTreeSet<_>>(), expected);
+    }
+
+    #[test]
+    fn column_specs_come_from_diesel_declarations() {
+        let tables = expected_tables();
+        let branches = tables.iter().find(|t| t.name == "workflow_branches").unwrap();
+        assert_eq!(branches.columns[2],
+                   ColumnSpec { name: "parent_flow_id",
+                                data_type: "uuid",
+                                nullable: true });
+        let log = tables.iter().find(|t| t.name == "event_log").unwrap();
+        assert_eq!(log.columns[2].data_type, "timestamp with time zone");
+    }
+
+    #[test]
+    fn check_clause_literals_are_compared() {
+        let clause =
+            "((event_type = lower(event_type)) AND (event_type = ANY (ARRAY['flowinitialized'::text, 'it''s'::text])))";
+        assert_eq!(quoted_literals(clause), vec!["flowinitialized", "it's"]);
+        let found: BTreeSet<String> = ["a", "c"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(compare_check("t", "c", &["a", "b"], Some(found)),
+                   Some(SchemaDrift::CheckValuesMismatch { table: "t".into(),
+                                                           column: "c".into(),
+                                                           missing: vec!["b".into()],
+                                                           unexpected: vec!["c".into()] }));
+        assert_eq!(compare_check("t", "c", &["a"], None),
+                   Some(SchemaDrift::MissingCheck { table: "t".into(),
+                                                    column: "c".into() }));
+    }
+}
@@ -0,0 +1,86 @@
+//! Aislamiento multi-tenant (por proyecto) mediante schemas de Postgres.
+//!
+//! Cada tenant posee un schema propio (`tenant_<id>`) con su copia completa
+//! de las tablas (`event_log`, `workflow_step_artifacts`,
+//! `step_execution_errors`, `workflow_branches`). Un `PgEventStore` asociado a
+//! un tenant (`PgEventStore::with_tenant`) fija `SET LOCAL search_path` al
+//! schema del tenant dentro de cada transacción, de modo que flows,
+//! artifacts, errores y ramas de proyectos distintos nunca se mezclan, aun
+//! compartiendo pool de conexiones.
+//!
+//! El schema de un tenant se crea y migra con
+//! `migrations::run_tenant_migrations` (o `pg::migrate_tenant` a partir de un
+//! pool); `chem-cli migrate-tenant <id>` expone lo mismo por línea de
+//! comandos.
+
+use std::fmt;
+
+use crate::config::is_valid_identifier;
+use crate::error::PersistenceError;
+
+/// Prefijo de los schemas de tenant.
+pub const TENANT_SCHEMA_PREFIX: &str = "tenant_";
+
+/// Identificador validado de tenant/proyecto.
+///
+/// Debe llegar ya en forma canónica: un identificador SQL simple en
+/// minúsculas (`[a-z_][a-z0-9_]*`), que puede interpolarse de forma segura
+/// como nombre de schema. No se reescribe: si `Project-A` y `project_a` se
+/// normalizaran al mismo id, dos proyectos compartirían schema.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn new(id: &str) -> Result<Self, PersistenceError> {
+        let schema = format!("{TENANT_SCHEMA_PREFIX}{id}");
+        if !is_valid_identifier(id) || !is_valid_identifier(&schema) {
+            return Err(PersistenceError::Config(format!("tenant id inválido (se espera [a-z_][a-z0-9_]*): {id}")));
+        }
+        Ok(Self(id.to_string()))
+    }
+
+    /// Recupera el tenant a partir de su nombre de schema (`tenant_<id>`).
+    pub fn from_schema_name(schema: &str) -> Option<Self> {
+        schema.strip_prefix(TENANT_SCHEMA_PREFIX).and_then(|id| Self::new(id).ok())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Nombre del schema Postgres que aloja las tablas del tenant.
+    pub fn schema_name(&self) -> String {
+        format!("{TENANT_SCHEMA_PREFIX}{}", self.0)
+    }
+
+    /// Sentencia que restringe la transacción en curso al schema del tenant.
+    pub(crate) fn set_local_search_path_sql(&self) -> String {
+        format!("SET LOCAL search_path TO {}", self.schema_name())
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tenant_id_must_be_canonical() {
+        let t = TenantId::new("project_a").unwrap();
+        assert_eq!(t.as_str(), "project_a");
+        assert_eq!(t.schema_name(), "tenant_project_a");
+        assert_eq!(TenantId::from_schema_name("tenant_project_a"), Some(t));
+        assert!(TenantId::new("").is_err());
+        for alias in ["Project-A", "PROJECT_A", "project-a", " project_a"] {
+            assert!(TenantId::new(alias).is_err(), "{alias}");
+        }
+        assert!(TenantId::new("a; drop schema public").is_err());
+        assert!(TenantId::new(&"x".repeat(80)).is_err());
+        assert_eq!(TenantId::from_schema_name("public"), None);
+    }
+}
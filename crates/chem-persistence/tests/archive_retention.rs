@@ -0,0 +1,87 @@
+use chem_core::event::{EventArtifact, FlowEventKind};
+use chem_core::EventStore;
+use chem_persistence::pg::build_pool;
+use chem_persistence::{
+    migrate_tenant, DirectorySink, FlowArchiver, FlowBundle, PgEventStore, PoolProvider, RetentionPolicy, TenantId,
+};
+use std::env;
+use uuid::Uuid;
+
+fn step_finished(hash: &str, with_payload: bool) -> FlowEventKind {
+    let payloads = with_payload.then(|| {
+                                   vec![EventArtifact { hash: hash.to_string(),
+                                                        kind: "GenericJson".into(),
+                                                        payload: serde_json::json!({"v": 1}),
+                                                        metadata: None }]
+                               });
+    FlowEventKind::StepFinished { step_index: 0,
+                                  step_id: "s1".into(),
+                                  outputs: vec![hash.to_string()],
+                                  fingerprint: "fp".into(),
+                                  outputs_payloads: payloads }
+}
+
+#[test]
+fn archive_exports_deletes_and_keeps_shared_artifacts() -> Result<(), Box<dyn std::error::Error>> {
+    let database_url = match env::var("DATABASE_URL") {
+        Ok(u) => u,
+        Err(_) => {
+            eprintln!("Skipping DB integration test: DATABASE_URL not set");
+            return Ok(());
+        }
+    };
+
+    // Schema de tenant dedicado: el archivado no toca datos de otros tests.
+    let pool = build_pool(&database_url, 1, 2)?;
+    let tenant = TenantId::new("itest_archive")?;
+    migrate_tenant(&pool, &tenant)?;
+    let mut store = PgEventStore::new(PoolProvider { pool: pool.clone() }).with_tenant(tenant.clone());
+
+    // Flow completado que produce dos artifacts; uno de ellos también lo
+    // referencia un flow vivo (no completado).
+    let shared = blake3_like(&Uuid::new_v4());
+    let private = blake3_like(&Uuid::new_v4());
+    let old_flow = Uuid::new_v4();
+    store.append_kind(old_flow,
+                      FlowEventKind::FlowInitialized { definition_hash: "d".into(),
+                                                       step_count: 2 });
+    store.append_kind(old_flow, step_finished(&shared, true));
+    store.append_kind(old_flow, step_finished(&private, true));
+    store.append_kind(old_flow, FlowEventKind::FlowCompleted { flow_fingerprint: "ff".into() });
+    let live_flow = Uuid::new_v4();
+    store.append_kind(live_flow,
+                      FlowEventKind::FlowInitialized { definition_hash: "d".into(),
+                                                       step_count: 2 });
+    store.append_kind(live_flow, step_finished(&shared, false));
+
+    let archiver = FlowArchiver::new(pool.clone()).with_tenant(tenant);
+    let policy = RetentionPolicy::older_than_days(0);
+
+    // Dry-run: reporta sin borrar.
+    let plan = archiver.plan(&policy)?;
+    let planned = plan.flows.iter().find(|f| f.flow_id == old_flow).expect("old flow planned");
+    assert!(plan.dry_run);
+    assert_eq!(planned.events, 4);
+    assert_eq!(planned.artifacts_retained, vec![shared.clone()]);
+    assert_eq!(planned.artifacts_deleted, 1);
+    assert!(plan.flows.iter().all(|f| f.flow_id != live_flow));
+    assert_eq!(store.list(old_flow).len(), 4);
+
+    let dir = env::temp_dir().join(format!("chemflow-archive-itest-{}", Uuid::new_v4()));
+    let mut sink = DirectorySink::new(&dir)?;
+    let report = archiver.archive(&policy, &mut sink)?;
+    assert!(report.flows.iter().any(|f| f.flow_id == old_flow));
+
+    let bundle = FlowBundle::read_from(&sink.bundle_path(old_flow))?;
+    assert_eq!(bundle.events.len(), 4);
+    assert_eq!(bundle.artifacts.len(), 2);
+    assert!(store.list(old_flow).is_empty());
+    assert_eq!(store.list(live_flow).len(), 2);
+    std::fs::remove_dir_all(dir).ok();
+    Ok(())
+}
+
+/// Hash hex de 64 caracteres único por test (cumple el CHECK de longitud).
+fn blake3_like(id: &Uuid) -> String {
+    format!("{:032x}{:032x}", id.as_u128(), id.as_u128().rotate_left(17))
+}
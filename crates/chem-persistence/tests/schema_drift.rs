@@ -0,0 +1,25 @@
+use chem_persistence::pg::build_pool;
+use chem_persistence::{check_schema, migrate_tenant, verify_tenant_schema, TenantId};
+use std::env;
+
+#[test]
+fn migrated_schema_has_no_drift() -> Result<(), Box<dyn std::error::Error>> {
+    let database_url = match env::var("DATABASE_URL") {
+        Ok(u) => u,
+        Err(_) => {
+            eprintln!("Skipping DB integration test: DATABASE_URL not set");
+            return Ok(());
+        }
+    };
+
+    let pool = build_pool(&database_url, 1, 2)?;
+    let mut conn = pool.get()?;
+    let drifts = check_schema(&mut conn)?;
+    assert!(drifts.is_empty(), "unexpected drift: {drifts:?}");
+
+    // Un schema de tenant recién migrado también debe coincidir.
+    let tenant = TenantId::new("itest_drift")?;
+    migrate_tenant(&pool, &tenant)?;
+    verify_tenant_schema(&mut conn, &tenant)?;
+    Ok(())
+}
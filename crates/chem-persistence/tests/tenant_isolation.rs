@@ -0,0 +1,52 @@
+use chem_core::event::FlowEventKind;
+use chem_core::EventStore;
+use chem_persistence::pg::build_pool;
+use chem_persistence::{list_tenants, migrate_tenant, PgEventStore, PoolProvider, TenantId};
+use std::env;
+use uuid::Uuid;
+
+#[test]
+fn tenants_do_not_see_each_other_events() -> Result<(), Box<dyn std::error::Error>> {
+    let database_url = match env::var("DATABASE_URL") {
+        Ok(u) => u,
+        Err(_) => {
+            eprintln!("Skipping DB integration test: DATABASE_URL not set");
+            return Ok(());
+        }
+    };
+
+    let pool = build_pool(&database_url, 1, 2)?;
+    let tenant_a = TenantId::new("itest_a")?;
+    let tenant_b = TenantId::new("itest_b")?;
+    migrate_tenant(&pool, &tenant_a)?;
+    migrate_tenant(&pool, &tenant_b)?;
+    // Idempotente: volver a migrar no falla.
+    migrate_tenant(&pool, &tenant_a)?;
+
+    let tenants = list_tenants(&pool)?;
+    assert!(tenants.contains(&tenant_a) && tenants.contains(&tenant_b));
+
+    let mut store_a = PgEventStore::new(PoolProvider { pool: pool.clone() }).with_tenant(tenant_a);
+    let mut store_b = PgEventStore::new(PoolProvider { pool: pool.clone() }).with_tenant(tenant_b);
+    let store_default = PgEventStore::new(PoolProvider { pool: pool.clone() });
+
+    // Mismo flow_id en ambos tenants: cada uno solo ve lo suyo.
+    let flow_id = Uuid::new_v4();
+    store_a.append_kind(flow_id,
+                        FlowEventKind::FlowInitialized { definition_hash: "tenant-a".into(),
+                                                         step_count: 1 });
+    store_b.append_kind(flow_id,
+                        FlowEventKind::FlowInitialized { definition_hash: "tenant-b".into(),
+                                                         step_count: 2 });
+    store_b.append_kind(flow_id, FlowEventKind::FlowCompleted { flow_fingerprint: "fp-b".into() });
+
+    let events_a = store_a.list(flow_id);
+    let events_b = store_b.list(flow_id);
+    assert_eq!(events_a.len(), 1);
+    assert_eq!(events_b.len(), 2);
+    assert!(matches!(&events_a[0].kind,
+                     FlowEventKind::FlowInitialized { definition_hash, .. } if definition_hash == "tenant-a"));
+    assert!(store_default.list(flow_id).is_empty(),
+            "default schema must not see tenant events");
+    Ok(())
+}
@@ -4,7 +4,9 @@
 //! preferencia de propiedad de manera determinista y auditable.
 
 use chem_core::hashing::{hash_str, to_canonical_json};
+use chem_domain::{DomainError, Unit};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Candidato a selección de propiedad.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
@@ -31,6 +33,60 @@ impl PropertyCandidate {
         let cj = to_canonical_json(&self.value);
         hash_str(&cj)
     }
+
+    /// Copia con el valor expresado en la unidad canónica de su dimensión
+    /// (ver `chem_domain::units`). Se convierten valores numéricos y objetos
+    /// con campo numérico `value`; sin unidades se devuelve igual.
+    pub fn canonicalized(&self) -> Result<PropertyCandidate, DomainError> {
+        let Some(units) = &self.units else {
+            return Ok(self.clone());
+        };
+        let unit = Unit::parse(units)?;
+        let convert = |v: &serde_json::Value| -> Result<serde_json::Value, DomainError> {
+            let x = v.as_f64()
+                     .ok_or_else(|| {
+                         DomainError::ValidationError(format!("Valor no numérico con unidades en {}", self.stable_key()))
+                     })?;
+            Ok(serde_json::json!(unit.to_canonical(x)))
+        };
+        let value = match &self.value {
+            serde_json::Value::Object(map) if map.contains_key("value") => {
+                let mut map = map.clone();
+                let converted = convert(&map["value"])?;
+                map.insert("value".to_string(), converted);
+                serde_json::Value::Object(map)
+            }
+            other => convert(other)?,
+        };
+        Ok(PropertyCandidate { value,
+                               units: Some(unit.canonical().symbol().to_string()),
+                               ..self.clone() })
+    }
+}
+
+/// Normaliza todos los candidatos a unidades canónicas y verifica que los
+/// candidatos de un mismo `property_kind` compartan dimensión (no se mezclan
+/// candidatos con y sin unidades).
+///
+/// # Errores
+/// `DomainError::ValidationError` ante unidades desconocidas, valores no
+/// numéricos con unidades o dimensiones incompatibles.
+pub fn normalize_candidates(candidates: &[PropertyCandidate]) -> Result<Vec<PropertyCandidate>, DomainError> {
+    let mut dimensions = BTreeMap::new();
+    for c in candidates {
+        let dimension = c.units.as_deref().map(Unit::parse).transpose()?.map(|u| u.dimension());
+        match dimensions.get(&c.property_kind) {
+            Some(first) if *first != dimension => {
+                return Err(DomainError::ValidationError(format!("Dimensiones incompatibles para '{}': {:?} vs {:?}",
+                                                                c.property_kind, first, dimension)));
+            }
+            Some(_) => {}
+            None => {
+                dimensions.insert(c.property_kind.clone(), dimension);
+            }
+        }
+    }
+    candidates.iter().map(PropertyCandidate::canonicalized).collect()
 }
 
 /// Parámetros de selección soportados en v1.
@@ -93,10 +149,17 @@ impl Rationale {
 /// Contrato de políticas de selección deterministas.
 pub trait PropertySelectionPolicy {
     fn id(&self) -> &'static str;
-    fn choose(&self, candidates: &[PropertyCandidate], params: &SelectionParams) -> SelectionDecision;
+    /// Elige un candidato.
+    ///
+    /// # Errores
+    /// `DomainError::ValidationError` si no hay candidatos o si no se pueden
+    /// normalizar (ver `normalize_candidates`): la decisión se rechaza en
+    /// lugar de comparar valores en unidades distintas.
+    fn choose(&self, candidates: &[PropertyCandidate], params: &SelectionParams) -> Result<SelectionDecision, DomainError>;
 }
 
 /// Política: seleccionar mayor score, con desempate estable.
+#[derive(Default)]
 pub struct MaxScorePolicy;
 
 impl MaxScorePolicy {
@@ -110,11 +173,16 @@ impl PropertySelectionPolicy for MaxScorePolicy {
         "max_score"
     }
 
-    fn choose(&self, candidates: &[PropertyCandidate], params: &SelectionParams) -> SelectionDecision {
+    fn choose(&self, candidates: &[PropertyCandidate], params: &SelectionParams) -> Result<SelectionDecision, DomainError> {
         let ms_params = match params {
             SelectionParams::MaxScore(p) => p.clone(),
         };
-        let mut sorted = candidates.to_vec();
+        if candidates.is_empty() {
+            return Err(DomainError::ValidationError("No hay candidatos para seleccionar".into()));
+        }
+        // Comparar en unidades canónicas: kcal/mol y kJ/mol del mismo valor
+        // producen el mismo value_hash.
+        let mut sorted = normalize_candidates(candidates)?;
         sorted.sort_by(|a, b| {
                   let sa = a.score.unwrap_or(0.0);
                   let sb = b.score.unwrap_or(0.0);
@@ -134,7 +202,7 @@ impl PropertySelectionPolicy for MaxScorePolicy {
                   }
               });
 
-        let selected = sorted.first().cloned().expect("non-empty candidates");
+        let selected = sorted[0].clone();
         let selected_key = selected.stable_key();
         let ties: Vec<String> =
             sorted.iter()
@@ -149,10 +217,10 @@ impl PropertySelectionPolicy for MaxScorePolicy {
                                     selected_key: selected_key.clone(),
                                     ties,
                                     tie_break_rule: ms_params.tie_break };
-        SelectionDecision { selected_key,
-                            policy_id: self.id().into(),
-                            params_hash,
-                            rationale }
+        Ok(SelectionDecision { selected_key,
+                               policy_id: self.id().into(),
+                               params_hash,
+                               rationale })
     }
 }
 
@@ -185,8 +253,8 @@ mod tests {
         let p = MaxScorePolicy::new();
         let params = SelectionParams::MaxScore(MaxScoreParams::default());
         let cands = vec![cand("A", "foo", 0.9), cand("B", "foo", 0.9), cand("C", "foo", 0.8)];
-        let d1 = p.choose(&cands, &params);
-        let d2 = p.choose(&cands, &params);
+        let d1 = p.choose(&cands, &params).unwrap();
+        let d2 = p.choose(&cands, &params).unwrap();
         assert_eq!(d1.selected_key, d2.selected_key);
         // Con tie break por key, A gana ante B con mismo score
         assert_eq!(d1.selected_key, "A|prop:foo");
@@ -195,14 +263,55 @@ mod tests {
         assert!(!d1.params_hash.is_empty());
     }
 
+    #[test]
+    fn candidates_are_compared_in_canonical_units() {
+        let mut kcal = cand("A", "dG", 0.5);
+        kcal.value = json!(-1.0);
+        kcal.units = Some("kcal/mol".into());
+        let mut kj = cand("A", "dG", 0.5);
+        kj.value = json!({"value": -4.184, "schema_version": 1});
+        kj.units = Some("kJ/mol".into());
+
+        let normalized = normalize_candidates(&[kcal.clone(), kj.clone()]).unwrap();
+        assert_eq!(normalized[0].value, json!(-4.184));
+        assert_eq!(normalized[0].units.as_deref(), Some("kJ/mol"));
+        assert_eq!(normalized[1].value["value"], json!(-4.184));
+
+        let mut area = cand("A", "dG", 0.5);
+        area.units = Some("Å²".into());
+        area.value = json!(1.0);
+        assert!(normalize_candidates(&[kcal, area]).is_err());
+        assert!(normalize_candidates(&[kj.clone(), cand("A", "dG", 0.1)]).is_err());
+    }
+
+    #[test]
+    fn choose_rejects_candidates_that_cannot_be_normalized() {
+        let p = MaxScorePolicy::new();
+        let params = SelectionParams::MaxScore(MaxScoreParams::default());
+        let mut unknown = cand("A", "dG", 0.9);
+        unknown.value = json!(1.0);
+        unknown.units = Some("furlong".into());
+        assert!(matches!(p.choose(&[unknown, cand("B", "dG", 0.1)], &params),
+                         Err(DomainError::ValidationError(_))));
+
+        let mut kj = cand("A", "dG", 0.9);
+        kj.value = json!(-4.184);
+        kj.units = Some("kJ/mol".into());
+        let mut area = cand("B", "dG", 0.1);
+        area.value = json!(1.0);
+        area.units = Some("Å²".into());
+        assert!(p.choose(&[kj, area], &params).is_err());
+        assert!(p.choose(&[], &params).is_err());
+    }
+
     #[test]
     fn params_hash_changes_with_params() {
         let p = MaxScorePolicy::new();
         let cands = vec![cand("A", "foo", 0.5), cand("B", "foo", 0.6)];
         let p1 = SelectionParams::MaxScore(MaxScoreParams { tie_break: TieRule::ByKeyThenValueHash });
         let p2 = SelectionParams::MaxScore(MaxScoreParams { tie_break: TieRule::ByKeyThenValueHash });
-        let d1 = p.choose(&cands, &p1);
-        let d2 = p.choose(&cands, &p2);
+        let d1 = p.choose(&cands, &p1).unwrap();
+        let d2 = p.choose(&cands, &p2).unwrap();
         assert_eq!(d1.params_hash, d2.params_hash);
     }
 }
@@ -282,15 +282,13 @@ fn main() {
     println!("\n🎉 ¡Demo completado exitosamente!");
     println!("==================================");
 
-
-
     if std::env::var("DATABASE_URL").is_ok() {
         println!("\n🗄️ Demo 5: Persistencia en Postgres y branching (si DATABASE_URL está presente)");
         println!("------------------------------------------------------------------");
 
-        use chem_persistence::{build_dev_pool_from_env, PgEventStore, PgFlowRepository, PoolProvider};
+        use chem_persistence::{build_dev_pool_and_config_from_env, PgEventStore, PgFlowRepository, PoolProvider};
 
-        let pool = match build_dev_pool_from_env() {
+        let (pool, cfg) = match build_dev_pool_and_config_from_env() {
             Ok(p) => p,
             Err(e) => {
                 println!("   ⚠️  No se pudo construir pool PG: {:?} - saltando demo PG", e);
@@ -298,7 +296,7 @@ fn main() {
             }
         };
         let provider = PoolProvider { pool };
-        let store = PgEventStore::new(provider);
+        let store = PgEventStore::from_config(provider, &cfg);
         let repo = PgFlowRepository::new();
 
         let mut pg_engine = FlowEngine::new_with_stores(store, repo);